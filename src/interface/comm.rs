@@ -1,18 +1,703 @@
-// // Author: Nicholas Renner
-// //
-// //
+// Author: Nicholas Renner
+//
+// Socket related interface
+// this is the only place host sockets are touched, everything else works with the Socket wrapper
+#![allow(dead_code)]
 
-// use std::collections::{HashMap, HashSet};
+use std::collections::VecDeque;
+use std::mem::size_of;
+use std::slice;
+use std::sync::{Condvar, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::interface;
+use crate::interface::{GenSockaddr, SockaddrV4, SockaddrV6};
 
+//MSG_NOSIGNAL is always passed to the host so that a closed peer gives us EPIPE rather than killing the whole process
+const MSG_NOSIGNAL: i32 = 0x4000;
 
-// static _BOUND_SOCKETS: HashMap = HashMap::new();
+fn get_errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(libc::EIO)
+}
 
-// static OPEN_SOCKET_INFO: HashMap = HashMap::new();
+//converts what the host wrote into a sockaddr_storage back into one of our sockaddr types
+fn storage_to_gensockaddr(storage: &libc::sockaddr_storage) -> GenSockaddr {
+    match storage.ss_family as i32 {
+        libc::AF_INET => GenSockaddr::V4(unsafe{*(storage as *const libc::sockaddr_storage as *const SockaddrV4)}),
+        libc::AF_INET6 => GenSockaddr::V6(unsafe{*(storage as *const libc::sockaddr_storage as *const SockaddrV6)}),
+        _ => panic!("Host returned a socket address of an unsupported family"),
+    }
+}
 
-// static PENDING_SOCKETS: HashSet = HashSet::new();
+fn gensockaddr_to_raw(addr: &GenSockaddr) -> (*const libc::sockaddr, libc::socklen_t) {
+    match addr {
+        GenSockaddr::V4(v4addr) => (v4addr as *const SockaddrV4 as *const libc::sockaddr, size_of::<SockaddrV4>() as libc::socklen_t),
+        GenSockaddr::V6(v6addr) => (v6addr as *const SockaddrV6 as *const libc::sockaddr, size_of::<SockaddrV6>() as libc::socklen_t),
+        GenSockaddr::Unix(_) => panic!("Unix domain sockets are emulated and never reach the host"),
+    }
+}
 
+//a host socket, closed when the last reference to it is dropped
+//the host fd is shared by every cage holding the socket, so its flags are never changed once it is made; it is always
+//nonblocking on the host, and a call which should wait does so in poll
+#[derive(Debug)]
+pub struct Socket {
+    raw_sys_fd: i32,
+    stream: bool,
+    refcount: AtomicU32,
+}
 
-// static user_ip_interface_preferences: bool = false;
+impl Socket {
+    pub fn new(domain: i32, socktype: i32, protocol: i32) -> Result<Socket, i32> {
+        //the host fd must never leak into anything the host execs
+        let fd = unsafe{libc::socket(domain, socktype | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK, protocol)};
+        if fd < 0 {return Err(get_errno());}
+        Ok(Socket {raw_sys_fd: fd, stream: socktype == libc::SOCK_STREAM, refcount: AtomicU32::new(1)})
+    }
 
-// static allow_nonspecified_ips: bool = true;
+    pub fn incr_ref(&self) {
+        self.refcount.fetch_add(1, Ordering::Relaxed);
+    }
 
+    //returns the number of references remaining
+    pub fn decr_ref(&self) -> u32 {
+        self.refcount.fetch_sub(1, Ordering::Relaxed) - 1
+    }
+
+    pub fn bind(&self, addr: &GenSockaddr) -> Result<(), i32> {
+        let (rawaddr, rawlen) = gensockaddr_to_raw(addr);
+        if unsafe{libc::bind(self.raw_sys_fd, rawaddr, rawlen)} < 0 {return Err(get_errno());}
+        Ok(())
+    }
+
+    //makes call until it stops failing with EAGAIN, waiting in between for the socket to be ready for events,
+    //unless nonblocking in which case the EAGAIN is given back
+    fn wait_for<T>(&self, nonblocking: bool, events: i16, mut call: impl FnMut() -> Result<T, i32>) -> Result<T, i32> {
+        loop {
+            match call() {
+                Err(errno) if errno == libc::EAGAIN && !nonblocking => {self.poll(events, -1);}
+                result => {return result;}
+            }
+        }
+    }
+
+    //a nonblocking connect which can't finish right away fails with EINPROGRESS, but carries on in the background,
+    //while a blocking one waits for it to finish and gives back how it went
+    pub fn connect(&self, addr: &GenSockaddr, nonblocking: bool) -> Result<(), i32> {
+        let (rawaddr, rawlen) = gensockaddr_to_raw(addr);
+        if unsafe{libc::connect(self.raw_sys_fd, rawaddr, rawlen)} == 0 {return Ok(());}
+        let errno = get_errno();
+        if errno != libc::EINPROGRESS || nonblocking {return Err(errno);}
+
+        while self.poll(libc::POLLOUT, -1) == 0 {}
+        match self.getsockopt_int(libc::SOL_SOCKET, libc::SO_ERROR)? {
+            0 => Ok(()),
+            errno => Err(errno)
+        }
+    }
+
+    pub fn listen(&self, backlog: i32) -> Result<(), i32> {
+        if unsafe{libc::listen(self.raw_sys_fd, backlog)} < 0 {return Err(get_errno());}
+        Ok(())
+    }
+
+    //blocks until a connection comes in, or fails with EAGAIN if nonblocking and there is none,
+    //returning the connected socket and the address of the peer
+    pub fn accept(&self, nonblocking: bool) -> Result<(Socket, GenSockaddr), i32> {
+        let mut storage: libc::sockaddr_storage = unsafe{std::mem::zeroed()};
+        let mut len = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let newfd = self.wait_for(nonblocking, libc::POLLIN, || {
+            let newfd = unsafe{libc::accept4(self.raw_sys_fd, &mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr, &mut len, libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK)};
+            if newfd < 0 {Err(get_errno())} else {Ok(newfd)}
+        })?;
+        Ok((Socket {raw_sys_fd: newfd, stream: true, refcount: AtomicU32::new(1)}, storage_to_gensockaddr(&storage)))
+    }
+
+    //waits unless MSG_DONTWAIT is given, and like a blocking send on the host, a stream send only returns early
+    //if it fails after sending part of the data
+    pub fn send(&self, buf: *const u8, len: usize, flags: i32) -> Result<usize, i32> {
+        self.sendto(buf, len, flags, None)
+    }
+
+    pub fn recv(&self, buf: *mut u8, len: usize, flags: i32) -> Result<usize, i32> {
+        self.recvfrom(buf, len, flags).map(|(received, _)| received)
+    }
+
+    //sends to the given address, or to the connected peer if there is none
+    pub fn sendto(&self, buf: *const u8, len: usize, flags: i32, addr: Option<&GenSockaddr>) -> Result<usize, i32> {
+        let (rawaddr, rawlen) = match addr {
+            Some(addr) => gensockaddr_to_raw(addr),
+            None => (std::ptr::null(), 0)
+        };
+        let nonblocking = flags & libc::MSG_DONTWAIT != 0;
+
+        let mut total = 0;
+        loop {
+            let result = self.wait_for(nonblocking, libc::POLLOUT, || {
+                let sent = unsafe{libc::sendto(self.raw_sys_fd, buf.add(total) as *const libc::c_void, len - total, flags | MSG_NOSIGNAL, rawaddr, rawlen)};
+                if sent < 0 {Err(get_errno())} else {Ok(sent as usize)}
+            });
+            match result {
+                Ok(sent) => {total += sent;}
+                Err(_) if total > 0 => {return Ok(total);}
+                Err(errno) => {return Err(errno);}
+            }
+            if total == len || nonblocking || !self.stream {return Ok(total);}
+        }
+    }
+
+    //receives a single datagram (or stream data), returning how much was read along with the sender's address
+    //waits unless MSG_DONTWAIT is given, and with MSG_WAITALL keeps reading a stream until len bytes have come in
+    pub fn recvfrom(&self, buf: *mut u8, len: usize, flags: i32) -> Result<(usize, Option<GenSockaddr>), i32> {
+        let mut storage: libc::sockaddr_storage = unsafe{std::mem::zeroed()};
+        let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let nonblocking = flags & libc::MSG_DONTWAIT != 0;
+        let waitall = flags & libc::MSG_WAITALL != 0 && self.stream && !nonblocking;
+
+        let mut total = 0;
+        loop {
+            let result = self.wait_for(nonblocking, libc::POLLIN, || {
+                addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+                let received = unsafe{libc::recvfrom(self.raw_sys_fd, buf.add(total) as *mut libc::c_void, len - total, flags,
+                                                     &mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr, &mut addrlen)};
+                if received < 0 {Err(get_errno())} else {Ok(received as usize)}
+            });
+            match result {
+                Ok(received) => {
+                    total += received;
+                    if received == 0 || total == len || !waitall {break;}
+                }
+                Err(_) if total > 0 => {break;}
+                Err(errno) => {return Err(errno);}
+            }
+        }
+
+        //connected stream sockets don't report an address
+        let addr = if addrlen == 0 {None} else {Some(storage_to_gensockaddr(&storage))};
+        Ok((total, addr))
+    }
+
+    pub fn shutdown(&self, how: i32) -> Result<(), i32> {
+        if unsafe{libc::shutdown(self.raw_sys_fd, how)} < 0 {return Err(get_errno());}
+        Ok(())
+    }
+
+    //waits up to timeout_ms for the socket to have something to read or accept
+    pub fn poll_readable(&self, timeout_ms: i32) -> bool {
+        self.poll(libc::POLLIN, timeout_ms) & libc::POLLIN != 0
+    }
+
+    //returns the events the host reports for the socket, waiting up to timeout_ms for any of them
+    pub fn poll(&self, events: i16, timeout_ms: i32) -> i16 {
+        let mut pollfd = libc::pollfd {fd: self.raw_sys_fd, events: events, revents: 0};
+        let ready = unsafe{libc::poll(&mut pollfd, 1, timeout_ms)};
+        if ready > 0 {pollfd.revents} else {0}
+    }
+
+    //waits until the host reports one of the events, or until anything within lind changes after the generation
+    pub fn wait_with_readiness(&self, events: i16, generation: u64) {
+        interface::wait_for_readiness_or_fd(generation, self.raw_sys_fd, events);
+    }
+
+    pub fn setsockopt(&self, level: i32, optname: i32, optval: &[u8]) -> Result<(), i32> {
+        if unsafe{libc::setsockopt(self.raw_sys_fd, level, optname, optval.as_ptr() as *const libc::c_void, optval.len() as libc::socklen_t)} < 0 {
+            return Err(get_errno());
+        }
+        Ok(())
+    }
+
+    //all the options we ask the host about are ints
+    pub fn getsockopt_int(&self, level: i32, optname: i32) -> Result<i32, i32> {
+        let mut optval: i32 = 0;
+        let mut optlen = size_of::<i32>() as libc::socklen_t;
+        if unsafe{libc::getsockopt(self.raw_sys_fd, level, optname, &mut optval as *mut i32 as *mut libc::c_void, &mut optlen)} < 0 {
+            return Err(get_errno());
+        }
+        Ok(optval)
+    }
+
+    pub fn getsockname(&self) -> Result<GenSockaddr, i32> {
+        let mut storage: libc::sockaddr_storage = unsafe{std::mem::zeroed()};
+        let mut len = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        if unsafe{libc::getsockname(self.raw_sys_fd, &mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr, &mut len)} < 0 {
+            return Err(get_errno());
+        }
+        Ok(storage_to_gensockaddr(&storage))
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe{libc::close(self.raw_sys_fd);}
+    }
+}
+
+//Sockets which only talk to other sockets in the same lind instance never touch the host, and are
+//emulated with the in-memory buffers below
+
+//one direction of an in-memory connection, datagram connections also remember where each message ends
+#[derive(Debug)]
+struct ConnectionBufferState {
+    data: VecDeque<u8>,
+    boundaries: Option<VecDeque<usize>>, //lengths of the queued messages, None for a byte stream
+    capacity: usize,
+    write_closed: bool, //no one can write anymore, so readers get end of file once the data runs out
+    read_closed: bool, //no one can read anymore, so writers get EPIPE and readers get end of file
+    arrivals: u64, //bumped whenever data is written, so edge triggered epoll can tell new data from old
+}
+
+#[derive(Debug)]
+pub struct ConnectionBuffer {
+    state: Mutex<ConnectionBufferState>,
+    cv: Condvar,
+}
+
+impl ConnectionBuffer {
+    //wakes both those blocked on this buffer and those waiting for any file descriptor to become ready
+    fn wake(&self) {
+        self.cv.notify_all();
+        interface::notify_readiness();
+    }
+
+    pub fn new(capacity: usize, datagram: bool) -> ConnectionBuffer {
+        ConnectionBuffer {
+            state: Mutex::new(ConnectionBufferState {
+                data: VecDeque::new(), boundaries: if datagram {Some(VecDeque::new())} else {None},
+                capacity: capacity, write_closed: false, read_closed: false, arrivals: 0
+            }),
+            cv: Condvar::new()
+        }
+    }
+
+    //a stream write writes the whole buffer, waiting for room as needed, unless nonblocking in which case we write
+    //whatever fits, while a datagram is only ever written whole
+    pub fn write(&self, buf: *const u8, len: usize, nonblocking: bool) -> Result<usize, i32> {
+        let src = unsafe{slice::from_raw_parts(buf, len)};
+        let mut state = self.state.lock().unwrap();
+
+        if state.boundaries.is_some() {
+            if len > state.capacity {return Err(libc::EMSGSIZE);}
+            loop {
+                if state.read_closed || state.write_closed {return Err(libc::EPIPE);}
+                if state.capacity - state.data.len() >= len {break;}
+                if nonblocking {return Err(libc::EAGAIN);}
+                state = self.cv.wait(state).unwrap();
+            }
+            state.data.extend(src);
+            state.boundaries.as_mut().unwrap().push_back(len);
+            state.arrivals += 1;
+            self.wake();
+            return Ok(len);
+        }
+
+        let mut written = 0;
+        loop {
+            if state.read_closed || state.write_closed {
+                return if written > 0 {Ok(written)} else {Err(libc::EPIPE)};
+            }
+
+            let room = state.capacity.saturating_sub(state.data.len());
+            let towrite = interface::rust_min(room, len - written);
+            state.data.extend(&src[written..written + towrite]);
+            written += towrite;
+            if towrite > 0 {
+                state.arrivals += 1;
+                self.wake();
+            }
+
+            if written == len {return Ok(written);}
+            if nonblocking {
+                return if written > 0 {Ok(written)} else {Err(libc::EAGAIN)};
+            }
+            state = self.cv.wait(state).unwrap();
+        }
+    }
+
+    //reads whatever is available up to len, or the next datagram with whatever doesn't fit discarded,
+    //waiting for data unless nonblocking, and returning 0 at end of file
+    pub fn read(&self, buf: *mut u8, len: usize, peek: bool, nonblocking: bool) -> Result<usize, i32> {
+        let mut state = self.state.lock().unwrap();
+
+        while state.data.is_empty() && state.boundaries.as_ref().map_or(true, |b| b.is_empty()) {
+            if state.write_closed || state.read_closed || (len == 0 && state.boundaries.is_none()) {return Ok(0);}
+            if nonblocking {return Err(libc::EAGAIN);}
+            state = self.cv.wait(state).unwrap();
+        }
+
+        let available = match &state.boundaries {
+            Some(boundaries) => *boundaries.front().unwrap(),
+            None => state.data.len()
+        };
+        let toread = interface::rust_min(len, available);
+        let dst = unsafe{slice::from_raw_parts_mut(buf, toread)};
+        for (dstbyte, srcbyte) in dst.iter_mut().zip(state.data.iter()) {
+            *dstbyte = *srcbyte;
+        }
+        if !peek {
+            //the rest of a datagram that didn't fit is lost
+            let consumed = if let Some(boundaries) = state.boundaries.as_mut() {
+                boundaries.pop_front().unwrap()
+            } else {
+                toread
+            };
+            state.data.drain(..consumed);
+            self.wake();
+        }
+        Ok(toread)
+    }
+
+    //shrinking below what is already queued just keeps writers waiting until enough is read
+    pub fn set_capacity(&self, capacity: usize) {
+        self.state.lock().unwrap().capacity = capacity;
+        self.wake();
+    }
+
+    //there is something to read, or reading would give end of file
+    pub fn readable(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        !state.data.is_empty() || state.boundaries.as_ref().map_or(false, |b| !b.is_empty()) || state.write_closed || state.read_closed
+    }
+
+    pub fn arrivals(&self) -> u64 {
+        self.state.lock().unwrap().arrivals
+    }
+
+    //there is room to write, or writing would fail right away
+    pub fn writable(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.data.len() < state.capacity || state.write_closed || state.read_closed
+    }
+
+    pub fn is_write_closed(&self) -> bool {
+        self.state.lock().unwrap().write_closed
+    }
+
+    pub fn is_read_closed(&self) -> bool {
+        self.state.lock().unwrap().read_closed
+    }
+
+    pub fn close_write(&self) {
+        self.state.lock().unwrap().write_closed = true;
+        self.wake();
+    }
+
+    pub fn close_read(&self) {
+        self.state.lock().unwrap().read_closed = true;
+        self.wake();
+    }
+}
+
+//one end of an in-memory connection, the connection is closed in both directions once this is dropped
+#[derive(Debug)]
+pub struct EmulatedConnection {
+    inbound: interface::RustRfc<ConnectionBuffer>,
+    outbound: interface::RustRfc<ConnectionBuffer>,
+}
+
+impl EmulatedConnection {
+    pub fn new_pair(capacity: usize) -> (EmulatedConnection, EmulatedConnection) {
+        Self::_new_pair(capacity, false)
+    }
+
+    //a connection over which messages keep their boundaries, as for a datagram socketpair
+    pub fn new_datagram_pair(capacity: usize) -> (EmulatedConnection, EmulatedConnection) {
+        Self::_new_pair(capacity, true)
+    }
+
+    fn _new_pair(capacity: usize, datagram: bool) -> (EmulatedConnection, EmulatedConnection) {
+        let atob = interface::RustRfc::new(ConnectionBuffer::new(capacity, datagram));
+        let btoa = interface::RustRfc::new(ConnectionBuffer::new(capacity, datagram));
+        (EmulatedConnection {inbound: btoa.clone(), outbound: atob.clone()},
+         EmulatedConnection {inbound: atob, outbound: btoa})
+    }
+
+    pub fn send(&self, buf: *const u8, len: usize, nonblocking: bool) -> Result<usize, i32> {
+        self.outbound.write(buf, len, nonblocking)
+    }
+
+    pub fn recv(&self, buf: *mut u8, len: usize, peek: bool, nonblocking: bool) -> Result<usize, i32> {
+        self.inbound.read(buf, len, peek, nonblocking)
+    }
+
+    //the direction we read from, which can be waited on without holding on to the connection
+    pub fn receiver(&self) -> interface::RustRfc<ConnectionBuffer> {
+        self.inbound.clone()
+    }
+
+    pub fn set_send_capacity(&self, capacity: usize) {
+        self.outbound.set_capacity(capacity);
+    }
+
+    pub fn set_recv_capacity(&self, capacity: usize) {
+        self.inbound.set_capacity(capacity);
+    }
+
+    //whether we can read, whether we can write, and whether both directions are closed
+    pub fn poll_state(&self) -> (bool, bool, bool) {
+        let hungup = self.inbound.is_write_closed() && self.outbound.is_read_closed();
+        (self.inbound.readable(), self.outbound.writable(), hungup)
+    }
+
+    //how many times data has been sent our way
+    pub fn arrivals(&self) -> u64 {
+        self.inbound.arrivals()
+    }
+
+    //we won't read anymore, so the peer's writes fail with EPIPE
+    pub fn shutdown_read(&self) {
+        self.inbound.close_read();
+    }
+
+    //we won't write anymore, so the peer reads end of file once it has read what we already sent
+    pub fn shutdown_write(&self) {
+        self.outbound.close_write();
+    }
+}
+
+impl Drop for EmulatedConnection {
+    fn drop(&mut self) {
+        self.shutdown_read();
+        self.shutdown_write();
+    }
+}
+
+//the receive queue of a datagram socket, each datagram is kept whole along with who sent it
+#[derive(Debug)]
+struct DatagramQueueState {
+    datagrams: VecDeque<(Vec<u8>, GenSockaddr)>,
+    queuedbytes: usize,
+    capacity: usize,
+    closed: bool, //every socket bound to the queue is gone, so nothing will ever make room in it
+    arrivals: u64, //how many datagrams have ever been delivered
+}
+
+#[derive(Debug)]
+pub struct DatagramQueue {
+    state: Mutex<DatagramQueueState>,
+    cv: Condvar,
+}
+
+impl DatagramQueue {
+    pub fn new(capacity: usize) -> DatagramQueue {
+        DatagramQueue {
+            state: Mutex::new(DatagramQueueState {datagrams: VecDeque::new(), queuedbytes: 0, capacity: capacity, closed: false, arrivals: 0}),
+            cv: Condvar::new()
+        }
+    }
+
+    //like a unix socket on linux, a datagram is never dropped, the sender waits for room in the queue instead
+    //or gets EAGAIN if it is nonblocking
+    pub fn deliver(&self, buf: *const u8, len: usize, srcaddr: GenSockaddr, nonblocking: bool) -> Result<usize, i32> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {return Err(libc::ECONNREFUSED);}
+            //a datagram bigger than the whole queue could never be delivered
+            if len > state.capacity {return Err(libc::EMSGSIZE);}
+            if state.queuedbytes + len <= state.capacity {break;}
+            if nonblocking {return Err(libc::EAGAIN);}
+            state = self.cv.wait(state).unwrap();
+        }
+
+        let datagram = unsafe{slice::from_raw_parts(buf, len)}.to_vec();
+        state.datagrams.push_back((datagram, srcaddr));
+        state.queuedbytes += len;
+        state.arrivals += 1;
+        self.cv.notify_all();
+        interface::notify_readiness();
+        Ok(len)
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        self.state.lock().unwrap().capacity = capacity;
+        self.cv.notify_all();
+    }
+
+    //wakes any senders waiting for room, which there will never be
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.cv.notify_all();
+    }
+
+    pub fn has_datagrams(&self) -> bool {
+        !self.state.lock().unwrap().datagrams.is_empty()
+    }
+
+    pub fn arrivals(&self) -> u64 {
+        self.state.lock().unwrap().arrivals
+    }
+
+    //receives exactly one datagram, the part of it that doesn't fit in the buffer is discarded
+    //a timeout of None waits as long as it takes, and EAGAIN is returned if the timeout runs out
+    pub fn receive(&self, buf: *mut u8, len: usize, peek: bool, timeout: Option<interface::RustDuration>) -> Result<(usize, GenSockaddr), i32> {
+        let mut state = self.state.lock().unwrap();
+
+        while state.datagrams.is_empty() {
+            match timeout {
+                None => {state = self.cv.wait(state).unwrap();}
+                Some(duration) => {
+                    let (newstate, waitresult) = self.cv.wait_timeout(state, duration).unwrap();
+                    state = newstate;
+                    if waitresult.timed_out() && state.datagrams.is_empty() {return Err(libc::EAGAIN);}
+                }
+            }
+        }
+
+        let (datagram, srcaddr) = if peek {
+            state.datagrams.front().unwrap().clone()
+        } else {
+            let (datagram, srcaddr) = state.datagrams.pop_front().unwrap();
+            state.queuedbytes -= datagram.len();
+            //there is room now for any senders waiting on it
+            self.cv.notify_all();
+            (datagram, srcaddr)
+        };
+
+        let toread = interface::rust_min(len, datagram.len());
+        unsafe{std::ptr::copy_nonoverlapping(datagram.as_ptr(), buf, toread);}
+        Ok((toread, srcaddr))
+    }
+}
+
+//a connection waiting to be accepted
+#[derive(Debug)]
+pub struct PendingConnection {
+    pub connection: EmulatedConnection,
+    pub remoteaddr: GenSockaddr, //address of the socket which connected
+}
+
+#[derive(Debug)]
+struct ListenerState {
+    listening: bool,
+    pending: VecDeque<PendingConnection>,
+    arrivals: u64, //how many connections have ever come in
+}
+
+//a bound stream socket, which others can connect to once it is listening
+#[derive(Debug)]
+pub struct EmulatedListener {
+    state: Mutex<ListenerState>,
+    cv: Condvar,
+}
+
+impl EmulatedListener {
+    pub fn new() -> EmulatedListener {
+        EmulatedListener {state: Mutex::new(ListenerState {listening: false, pending: VecDeque::new(), arrivals: 0}), cv: Condvar::new()}
+    }
+
+    pub fn listen(&self) {
+        self.state.lock().unwrap().listening = true;
+    }
+
+    pub fn connect(&self, pending: PendingConnection) -> Result<(), i32> {
+        let mut state = self.state.lock().unwrap();
+        if !state.listening {return Err(libc::ECONNREFUSED);}
+        state.pending.push_back(pending);
+        state.arrivals += 1;
+        self.cv.notify_all();
+        interface::notify_readiness();
+        Ok(())
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.state.lock().unwrap().pending.is_empty()
+    }
+
+    pub fn arrivals(&self) -> u64 {
+        self.state.lock().unwrap().arrivals
+    }
+
+    //a timeout of None waits as long as it takes, and EAGAIN is returned if the timeout runs out
+    pub fn accept(&self, timeout: Option<interface::RustDuration>) -> Result<PendingConnection, i32> {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if let Some(pending) = state.pending.pop_front() {return Ok(pending);}
+            match timeout {
+                None => {state = self.cv.wait(state).unwrap();}
+                Some(duration) => {
+                    let (newstate, waitresult) = self.cv.wait_timeout(state, duration).unwrap();
+                    state = newstate;
+                    if waitresult.timed_out() && state.pending.is_empty() {return Err(libc::EAGAIN);}
+                }
+            }
+        }
+    }
+}
+
+//what other sockets find when they look up a bound address
+#[derive(Debug, Clone)]
+pub enum BoundSocket {
+    Stream(interface::RustRfc<EmulatedListener>),
+    Datagram(interface::RustRfc<DatagramQueue>),
+}
+
+//inet sockets are keyed by the address they are bound to, and unix sockets by the inode of their socket file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindKey {
+    Inet(GenSockaddr),
+    Unix(usize),
+}
+
+impl BindKey {
+    //only the family, address, and port of an inet address identify it
+    pub fn inet(addr: &GenSockaddr) -> BindKey {
+        let mut keyaddr = match addr {
+            GenSockaddr::V4(v4addr) => GenSockaddr::V4(SockaddrV4 {sin_family: v4addr.sin_family, sin_addr: v4addr.sin_addr, ..Default::default()}),
+            GenSockaddr::V6(v6addr) => GenSockaddr::V6(SockaddrV6 {sin6_family: v6addr.sin6_family, sin6_addr: v6addr.sin6_addr, ..Default::default()}),
+            GenSockaddr::Unix(_) => panic!("Unix domain sockets are keyed by inode"),
+        };
+        keyaddr.set_port(addr.port());
+        BindKey::Inet(keyaddr)
+    }
+}
+
+//an entry in BOUND_SOCKETS, sockets which all set SO_REUSEPORT share one entry and so one listen or receive queue
+#[derive(Debug)]
+struct BoundEntry {
+    bound: BoundSocket,
+    reuseport: bool,
+    holders: usize,
+}
+
+//every socket which can be reached from within lind, keyed by socket type and bound address
+static BOUND_SOCKETS: interface::RustLazyGlobal<interface::RustLock<interface::RustHashMap<(i32, BindKey), BoundEntry>>> =
+    interface::RustLazyGlobal::new(|| interface::RustLock::new(interface::RustHashMap::new()));
+
+//a socket's hold on an entry in BOUND_SOCKETS, the entry is removed once the last socket holding it is closed
+#[derive(Debug)]
+pub struct EmulatedBinding {
+    key: (i32, BindKey),
+    pub bound: BoundSocket,
+}
+
+impl EmulatedBinding {
+    //binding to an address that is already taken only works if every socket bound to it set SO_REUSEPORT,
+    //in which case we get the existing socket's queue rather than the one we brought
+    pub fn register(socktype: i32, key: BindKey, bound: BoundSocket, reuseport: bool) -> Result<EmulatedBinding, i32> {
+        let mut boundtable = BOUND_SOCKETS.write().unwrap();
+
+        if let Some(entry) = boundtable.get_mut(&(socktype, key)) {
+            if !(reuseport && entry.reuseport) {return Err(libc::EADDRINUSE);}
+            entry.holders += 1;
+            return Ok(EmulatedBinding {key: (socktype, key), bound: entry.bound.clone()});
+        }
+
+        boundtable.insert((socktype, key), BoundEntry {bound: bound.clone(), reuseport: reuseport, holders: 1});
+        Ok(EmulatedBinding {key: (socktype, key), bound: bound})
+    }
+
+    pub fn lookup(socktype: i32, key: BindKey) -> Option<BoundSocket> {
+        BOUND_SOCKETS.read().unwrap().get(&(socktype, key)).map(|entry| entry.bound.clone())
+    }
+}
+
+impl Drop for EmulatedBinding {
+    fn drop(&mut self) {
+        let mut boundtable = BOUND_SOCKETS.write().unwrap();
+        let entry = boundtable.get_mut(&self.key).unwrap();
+        entry.holders -= 1;
+        if entry.holders == 0 {
+            if let BoundSocket::Datagram(queue) = &entry.bound {queue.close();}
+            boundtable.remove(&self.key);
+        }
+    }
+}
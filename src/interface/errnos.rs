@@ -5,7 +5,7 @@ use crate::interface;
 static VERBOSE : bool = true;
 
 #[derive(Debug)]
-
+#[repr(i32)]
 pub enum Errno {
   EPERM = 1,	// Operation not permitted
   ENOENT = 2, // No such file or directory
@@ -139,6 +139,17 @@ pub enum Errno {
   ENOTRECOVERABLE = 131// State not recoverable
 }
 
+impl Errno {
+  //converts an errno value reported by the host back into our enum, values we don't know map to EIO
+  pub fn from_discriminant(discriminant: i32) -> Errno {
+    match discriminant {
+      //41 and 58 are unused by linux, everything else up to ENOTRECOVERABLE has a variant
+      1..=40 | 42..=57 | 59..=131 => unsafe {std::mem::transmute::<i32, Errno>(discriminant)},
+      _ => Errno::EIO
+    }
+  }
+}
+
 pub fn syscall_error(e: Errno, syscall: &str, message: &str) -> i32 {
     if VERBOSE {
         let msg = format!("Error in syscall: {} - {:?}: {}", syscall, e, message);
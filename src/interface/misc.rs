@@ -8,10 +8,13 @@ use std::fs::File;
 use std::io::{self, Read, Write};
 pub use std::collections::HashMap as RustHashMap;
 pub use std::cmp::{max as rust_max, min as rust_min};
+pub use std::mem::size_of as rust_size_of;
+pub use std::sync::atomic::{AtomicBool as RustAtomicBool, AtomicI32 as RustAtomicI32, AtomicU32 as RustAtomicU32, Ordering as RustAtomicOrdering};
 use std::str::{from_utf8, Utf8Error};
 
-pub use std::sync::{RwLock as RustLock, Arc as RustRfc};
+pub use std::sync::{RwLock as RustLock, Arc as RustRfc, Weak as RustWeak};
 use std::sync::{Mutex, Condvar};
+use crate::interface::{RustLazyGlobal, RustDuration};
 
 use libc::mmap;
 use std::ffi::c_void;
@@ -51,6 +54,13 @@ pub fn fillzero(bufptr: *mut u8, count: usize) -> i32 {
     for i in 0..count {slice[i] = 0u8;}
     count as i32
 }
+//copies as much of src as fits in count bytes at bufptr, returning how much that was
+pub fn fillfromslice(bufptr: *mut u8, count: usize, src: &[u8]) -> i32 {
+    let tocopy = rust_min(count, src.len());
+    let slice = unsafe{std::slice::from_raw_parts_mut(bufptr, tocopy)};
+    slice.copy_from_slice(&src[..tocopy]);
+    tocopy as i32
+}
 
 // Wrapper to return a dictionary (hashmap)
 pub fn new_hashmap<K, V>() -> RustHashMap<K, V> {
@@ -124,3 +134,66 @@ impl AdvisoryLock {
         } else {false}
     }
 }
+
+//Every change which could make a file descriptor ready (data arriving, buffer space freeing up, an end
+//closing) bumps the generation, waking everyone blocked in poll, select or epoll_wait to check again
+struct ReadinessNotifier {
+    generation: Mutex<u64>,
+    cv: Condvar,
+    wakefds: Mutex<Vec<i32>>, //eventfds of those waiting in the host's poll, which the condvar can't wake
+}
+
+static READINESS: RustLazyGlobal<ReadinessNotifier> =
+    RustLazyGlobal::new(|| ReadinessNotifier {generation: Mutex::new(0), cv: Condvar::new(), wakefds: Mutex::new(Vec::new())});
+
+pub fn notify_readiness() {
+    let mut generation = READINESS.generation.lock().unwrap();
+    *generation += 1;
+    for wakefd in READINESS.wakefds.lock().unwrap().iter() {
+        let one: u64 = 1;
+        unsafe{libc::write(*wakefd, &one as *const u64 as *const c_void, 8);}
+    }
+    drop(generation);
+    READINESS.cv.notify_all();
+}
+
+//read this before checking whether anything is ready, so that a change made while checking isn't missed
+pub fn readiness_generation() -> u64 {
+    *READINESS.generation.lock().unwrap()
+}
+
+//waits until something changes after the given generation, or until the timeout runs out if there is one
+pub fn wait_for_readiness(generation: u64, timeout: Option<RustDuration>) {
+    let guard = READINESS.generation.lock().unwrap();
+    match timeout {
+        None => {let _guard = READINESS.cv.wait_while(guard, |current| *current == generation).unwrap();}
+        Some(duration) => {let _guard = READINESS.cv.wait_timeout_while(guard, duration, |current| *current == generation).unwrap();}
+    }
+}
+
+//waits until something changes after the given generation, or until the host reports one of the events on fd
+pub fn wait_for_readiness_or_fd(generation: u64, fd: i32, events: i16) {
+    let mut pollfds = [libc::pollfd {fd: fd, events: events, revents: 0}, libc::pollfd {fd: -1, events: libc::POLLIN, revents: 0}];
+    let wakefd = unsafe{libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK)};
+    //without an eventfd we can't be woken, so we check back shortly instead, which our callers loop on anyway
+    if wakefd < 0 {
+        unsafe{libc::poll(pollfds.as_mut_ptr(), 1, 10);}
+        return;
+    }
+
+    //registering under the generation lock means a change made after we checked it can't be missed
+    {
+        let guard = READINESS.generation.lock().unwrap();
+        if *guard != generation {
+            unsafe{libc::close(wakefd);}
+            return;
+        }
+        READINESS.wakefds.lock().unwrap().push(wakefd);
+    }
+
+    pollfds[1].fd = wakefd;
+    unsafe{libc::poll(pollfds.as_mut_ptr(), 2, -1);}
+
+    READINESS.wakefds.lock().unwrap().retain(|registered| *registered != wakefd);
+    unsafe{libc::close(wakefd);}
+}
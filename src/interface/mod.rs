@@ -6,6 +6,7 @@
 mod comm;
 mod file;
 mod pipe;
+mod stdio;
 mod misc;
 mod timer;
 pub mod errnos;
@@ -13,6 +14,7 @@ pub mod types;
 pub use comm::*;
 pub use file::*;
 pub use pipe::*;
+pub use stdio::*;
 pub use misc::*;
 pub use timer::*;
 pub use types::*;
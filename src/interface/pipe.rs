@@ -1,42 +1,70 @@
 // Author: Nicholas Renner
 //
-// Pipes for SafePOSIX based on Lock-Free Circular Buffer
+// Pipes for SafePOSIX, a bounded buffer which any number of readers and writers may share
 
 #![allow(dead_code)]
 use crate::interface;
 
+use std::collections::VecDeque;
 use std::slice;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use ringbuf::{RingBuffer, Producer, Consumer};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::cmp::min;
 
 const O_RDONLY: i32 = 0o0;
 const O_WRONLY: i32 = 0o1;
 const O_RDWRFLAGS: i32 = 0o3;
 
+// Writes up to this size are never split up
+pub const PIPE_BUF: usize = 4096;
+
 pub fn new_pipe(size: usize) -> EmulatedPipe {
     EmulatedPipe::new_with_capacity(size)
 }
 
+// A pipe with neither end open yet, each end being counted as it is opened, as for the pipe behind a fifo
+pub fn new_unopened_pipe(size: usize) -> EmulatedPipe {
+    let pipe = EmulatedPipe::new_with_capacity(size);
+    pipe.refcount_write.store(0, Ordering::Relaxed);
+    pipe.refcount_read.store(0, Ordering::Relaxed);
+    pipe
+}
+
 pub struct EmulatedPipe {
-    write_end: Arc<Mutex<Producer<u8>>>,
-    read_end: Arc<Mutex<Consumer<u8>>>,
+    buffer: Mutex<VecDeque<u8>>,
+    // Readers take turns, so that a splice can hand what is at the front of the pipe to a file without the buffer
+    // locked and then remove exactly what was written, with no other reader getting to those bytes in between
+    read_turn: Mutex<()>,
+    // Readers wait on this for data or for the last writer to go away, writers on the other for room or for the
+    // last reader to go away
+    data_available: Condvar,
+    room_available: Condvar,
     pub refcount_write: AtomicU32,
     pub refcount_read: AtomicU32,
-    size: usize,
-    eof: AtomicBool,
+    // How many times each end has ever been opened, so that opening a fifo can wait for the other end to be opened
+    // even if it is closed again right away
+    opened_write: AtomicU32,
+    opened_read: AtomicU32,
+    // Only changed while holding the buffer lock, so anyone holding it sees a size that fits what is buffered
+    size: AtomicUsize,
+    // Bumped whenever data is put in the pipe, so edge triggered epoll can tell new data from data it already reported
+    arrivals: AtomicU64,
 }
 
 impl EmulatedPipe {
     pub fn new_with_capacity(size: usize) -> EmulatedPipe {
-        let rb = RingBuffer::<u8>::new(size);
-        let (prod, cons) = rb.split();
-        EmulatedPipe { write_end: Arc::new(Mutex::new(prod)), read_end: Arc::new(Mutex::new(cons)), refcount_write: AtomicU32::new(1), refcount_read: AtomicU32::new(1), size: size, eof: AtomicBool::new(false)}
+        EmulatedPipe { buffer: Mutex::new(VecDeque::with_capacity(size)), read_turn: Mutex::new(()), data_available: Condvar::new(), room_available: Condvar::new(),
+                       refcount_write: AtomicU32::new(1), refcount_read: AtomicU32::new(1),
+                       opened_write: AtomicU32::new(1), opened_read: AtomicU32::new(1), size: AtomicUsize::new(size), arrivals: AtomicU64::new(0)}
     }
 
-    pub fn set_eof(&self) {
-        self.eof.store(true, Ordering::Relaxed);
+    // Wakes everyone waiting on the pipe, as well as anyone polling it
+    fn wake_all(&self) {
+        // Taking the lock means no one can be between checking the pipe and waiting on it, so no wakeup is missed
+        let _buffer = self.buffer.lock().unwrap();
+        self.data_available.notify_all();
+        self.room_available.notify_all();
+        interface::notify_readiness();
     }
 
     pub fn get_write_ref(&self) -> u32 {
@@ -47,61 +75,257 @@ impl EmulatedPipe {
         self.refcount_read.load(Ordering::Relaxed)
     }
 
+    // A reference opened for both reading and writing, which only fifos can have, counts as both ends
+    // Every new reference is a chance for a fifo being opened to find its other end
     pub fn incr_ref(&self, flags: i32) {
-        if (flags & O_RDWRFLAGS) == O_RDONLY {self.refcount_read.fetch_add(1, Ordering::Relaxed);}
-        if (flags & O_RDWRFLAGS) == O_WRONLY {self.refcount_write.fetch_add(1, Ordering::Relaxed);}
+        if (flags & O_RDWRFLAGS) != O_WRONLY {
+            self.refcount_read.fetch_add(1, Ordering::Relaxed);
+            self.opened_read.fetch_add(1, Ordering::Relaxed);
+        }
+        if (flags & O_RDWRFLAGS) != O_RDONLY {
+            self.refcount_write.fetch_add(1, Ordering::Relaxed);
+            self.opened_write.fetch_add(1, Ordering::Relaxed);
+        }
+        self.wake_all();
     }
 
+    // Every reference going away is a chance for a waiting reader to see eof or a waiting writer to see EPIPE
     pub fn decr_ref(&self, flags: i32) {
-        if (flags & O_RDWRFLAGS) == O_RDONLY {self.refcount_read.fetch_sub(1, Ordering::Relaxed);}
-        if (flags & O_RDWRFLAGS) == O_WRONLY {self.refcount_write.fetch_sub(1, Ordering::Relaxed);}
+        if (flags & O_RDWRFLAGS) != O_WRONLY {self.refcount_read.fetch_sub(1, Ordering::Relaxed);}
+        if (flags & O_RDWRFLAGS) != O_RDONLY {self.refcount_write.fetch_sub(1, Ordering::Relaxed);}
+        self.wake_all();
     }
 
-    // Write length bytes from pointer into pipe
-    // BUG: This only currently works as SPSC
-    pub fn write_to_pipe(&self, ptr: *const u8, length: usize) -> usize {
+    // How many times the end opposite to one opened with flags has been opened, to be passed to wait_for_peer
+    pub fn peer_opens(&self, flags: i32) -> u32 {
+        match flags & O_RDWRFLAGS {
+            O_RDONLY => self.opened_write.load(Ordering::Relaxed),
+            O_WRONLY => self.opened_read.load(Ordering::Relaxed),
+            _ => 0,
+        }
+    }
 
-        let mut bytes_written = 0;
+    // Wait until the end opposite to one opened with flags is open, or has been opened since peer_opens returned seen
+    // An end open for both reading and writing is its own peer and never waits
+    pub fn wait_for_peer(&self, flags: i32, seen: u32) {
+        let (peer_refcount, peer_opened, waiton) = match flags & O_RDWRFLAGS {
+            O_RDONLY => (&self.refcount_write, &self.opened_write, &self.data_available),
+            O_WRONLY => (&self.refcount_read, &self.opened_read, &self.room_available),
+            _ => {return;}
+        };
+
+        let mut buffer = self.buffer.lock().unwrap();
+        while peer_refcount.load(Ordering::Relaxed) == 0 && peer_opened.load(Ordering::Relaxed) == seen {
+            buffer = waiton.wait(buffer).unwrap();
+        }
+    }
+
+    pub fn readable(&self) -> bool {
+        !self.buffer.lock().unwrap().is_empty()
+    }
+
+    pub fn arrivals(&self) -> u64 {
+        self.arrivals.load(Ordering::Relaxed)
+    }
+
+    // Like linux, we only call the pipe writable when a write of PIPE_BUF bytes wouldn't block
+    pub fn writable(&self) -> bool {
+        let buffer = self.buffer.lock().unwrap();
+        let size = self.size.load(Ordering::Relaxed);
+        size - buffer.len() >= min(PIPE_BUF, size)
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    // How many bytes could be written right now without waiting
+    pub fn room(&self) -> usize {
+        let buffer = self.buffer.lock().unwrap();
+        self.size.load(Ordering::Relaxed) - buffer.len()
+    }
+
+    // Change how much the pipe can hold, keeping whatever is in it
+    // Fails with EBUSY if more than the new size is buffered right now
+    pub fn set_size(&self, newsize: usize) -> Result<usize, i32> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() > newsize {return Err(libc::EBUSY);}
+
+        let oldsize = self.size.swap(newsize, Ordering::Relaxed);
+        if newsize > oldsize {
+            let len = buffer.len();
+            buffer.reserve(newsize - len);
+            self.room_available.notify_all();
+            interface::notify_readiness();
+        } else {
+            buffer.shrink_to(newsize);
+        }
+        Ok(newsize)
+    }
+
+    // Write length bytes from pointer into pipe, waiting for room as needed
+    // A write of at most PIPE_BUF bytes waits until it fits and goes in all at once, so that writes from different
+    // writers are never interleaved, while a larger one goes in as room frees up
+    // If nonblocking, write what can go in right away instead, failing with EAGAIN if that is nothing
+    // Once there are no readers, fails with EPIPE unless some of the data has already gone in
+    pub fn write_to_pipe(&self, ptr: *const u8, length: usize, nonblocking: bool) -> Result<usize, i32> {
 
         let buf = unsafe {
             assert!(!ptr.is_null());
             slice::from_raw_parts(ptr, length)
         };
 
-        let mut write_end = self.write_end.lock().unwrap();
+        let needed = if length <= PIPE_BUF {length} else {1};
+        let mut bytes_written = 0;
+        let mut buffer = self.buffer.lock().unwrap();
 
         while bytes_written < length {
-            let bytes_to_write = min(length, bytes_written + write_end.remaining());
-            write_end.push_slice(&buf[bytes_written..bytes_to_write]);
-            bytes_written = bytes_to_write;
-        }   
+            // TODO: raise SIGPIPE here as well, once cages have signals
+            if self.get_read_ref() == 0 {
+                return if bytes_written > 0 {Ok(bytes_written)} else {Err(libc::EPIPE)};
+            }
 
-        bytes_written
-    }
+            let size = self.size.load(Ordering::Relaxed);
+            let room = size - buffer.len();
+            if room < min(needed, size) {
+                if nonblocking {
+                    return if bytes_written > 0 {Ok(bytes_written)} else {Err(libc::EAGAIN)};
+                }
+                buffer = self.room_available.wait(buffer).unwrap();
+                continue;
+            }
 
-    // Read length bytes from the pipe into pointer
-    // Will wait for bytes unless pipe is empty and eof is set.
-    // BUG: This only currently works as SPSC
-    pub fn read_from_pipe(&self, ptr: *mut u8, length: usize) -> usize {
+            let bytes_to_write = min(room, length - bytes_written);
+            buffer.extend(&buf[bytes_written..bytes_written + bytes_to_write]);
+            bytes_written += bytes_to_write;
+            self.arrivals.fetch_add(1, Ordering::Relaxed);
+            self.data_available.notify_all();
+            interface::notify_readiness();
+        }
 
-        let mut bytes_read = 0;
+        Ok(bytes_written)
+    }
+
+    // Read up to length bytes from the pipe into pointer, returning as soon as there are any
+    // Will wait for bytes unless there are no writers left, in which case an empty pipe is at eof and reads 0 bytes
+    // If nonblocking, fail with EAGAIN instead of waiting
+    pub fn read_from_pipe(&self, ptr: *mut u8, length: usize, nonblocking: bool) -> Result<usize, i32> {
 
         let buf = unsafe {
             assert!(!ptr.is_null());
             slice::from_raw_parts_mut(ptr, length)
         };
 
-        let mut read_end = self.read_end.lock().unwrap();
+        if length == 0 {return Ok(0);}
 
-        while bytes_read < length {
-            if (read_end.len() == 0) & self.eof.load(Ordering::Relaxed) { break; }
-            let bytes_to_read = min(length, bytes_read + read_end.len());
-            read_end.pop_slice(&mut buf[bytes_read..bytes_to_read]);
-            bytes_read = bytes_to_read;
+        let (_turn, mut buffer) = match self.wait_to_read(nonblocking)? {
+            Some(guards) => guards,
+            None => {return Ok(0);}
+        };
+
+        let bytes_read = min(length, buffer.len());
+        let (front, back) = buffer.as_slices();
+        let from_front = min(bytes_read, front.len());
+        buf[..from_front].copy_from_slice(&front[..from_front]);
+        buf[from_front..bytes_read].copy_from_slice(&back[..bytes_read - from_front]);
+        buffer.drain(..bytes_read);
+
+        self.room_available.notify_all();
+        interface::notify_readiness();
+        Ok(bytes_read)
+    }
+
+    // Hand up to length bytes from the front of the pipe to mover, then remove as many of them as it says it used
+    // The buffer isn't locked while mover runs, so it may take other locks, but we keep our turn to read throughout
+    // Waits for data as read_from_pipe does, returning 0 without calling mover at eof, and an error from mover is
+    // passed back as it is
+    pub fn read_with<F: FnOnce(&[u8]) -> Result<usize, i32>>(&self, length: usize, nonblocking: bool, mover: F) -> Result<usize, i32> {
+        if length == 0 {return Ok(0);}
+
+        let (_turn, buffer) = match self.wait_to_read(nonblocking)? {
+            Some(guards) => guards,
+            None => {return Ok(0);}
+        };
+        let bytes: Vec<u8> = buffer.range(..min(length, buffer.len())).copied().collect();
+        drop(buffer);
+
+        let used = min(mover(&bytes)?, bytes.len());
+        if used > 0 {
+            // Anything else that takes from the pipe, splices included, waits for its turn, so only writers have touched
+            // the pipe since, and they add to the back, so these are still at the front
+            self.buffer.lock().unwrap().drain(..used);
+            self.room_available.notify_all();
+            interface::notify_readiness();
+        }
+        Ok(used)
+    }
+
+    // Move (or if not consume, copy) up to length bytes from the front of this pipe to the back of out in one step,
+    // so no other reader or writer of either pipe can come in between
+    // Like linux, waits for data here and room there with only the one pipe locked, and locks both in a fixed order
+    // to move what it can, so that splices going opposite ways between two pipes can't deadlock
+    // Taking from this pipe is done in our turn to read it, which is let go of while waiting like the locks are
+    pub fn splice_to(&self, out: &EmulatedPipe, length: usize, consume: bool, nonblocking_in: bool, nonblocking_out: bool) -> Result<usize, i32> {
+        if length == 0 {return Ok(0);}
+
+        loop {
+            let turn = if consume {Some(self.read_turn.lock().unwrap())} else {None};
+            let (mut inbuffer, mut outbuffer) = if (self as *const EmulatedPipe) < (out as *const EmulatedPipe) {
+                let inbuffer = self.buffer.lock().unwrap();
+                (inbuffer, out.buffer.lock().unwrap())
+            } else {
+                let outbuffer = out.buffer.lock().unwrap();
+                (self.buffer.lock().unwrap(), outbuffer)
+            };
+
+            if inbuffer.is_empty() {
+                if self.get_write_ref() == 0 {return Ok(0);}
+                if nonblocking_in {return Err(libc::EAGAIN);}
+                drop(outbuffer);
+                drop(turn);
+                drop(self.data_available.wait(inbuffer).unwrap());
+                continue;
+            }
+
+            // TODO: raise SIGPIPE here as well, once cages have signals
+            if out.get_read_ref() == 0 {return Err(libc::EPIPE);}
+            let room = out.size.load(Ordering::Relaxed) - outbuffer.len();
+            if room == 0 {
+                if nonblocking_out {return Err(libc::EAGAIN);}
+                drop(inbuffer);
+                drop(turn);
+                drop(out.room_available.wait(outbuffer).unwrap());
+                continue;
+            }
+
+            let count = min(length, min(inbuffer.len(), room));
+            outbuffer.extend(inbuffer.range(..count));
+            if consume {
+                inbuffer.drain(..count);
+                self.room_available.notify_all();
+            }
+            out.arrivals.fetch_add(1, Ordering::Relaxed);
+            out.data_available.notify_all();
+            interface::notify_readiness();
+            return Ok(count);
         }
+    }
 
-        bytes_read
+    // Wait until there is data in the pipe and it is our turn to read it, giving back our turn and the locked buffer,
+    // or None at eof
+    // If nonblocking, fail with EAGAIN instead of waiting
+    fn wait_to_read(&self, nonblocking: bool) -> Result<Option<(MutexGuard<'_, ()>, MutexGuard<'_, VecDeque<u8>>)>, i32> {
+        loop {
+            let turn = self.read_turn.lock().unwrap();
+            let buffer = self.buffer.lock().unwrap();
+            if !buffer.is_empty() {return Ok(Some((turn, buffer)));}
+            if self.get_write_ref() == 0 {return Ok(None);}
+            if nonblocking {return Err(libc::EAGAIN);}
+
+            // Other readers may take their turn while we wait
+            drop(turn);
+            drop(self.data_available.wait(buffer).unwrap());
+        }
     }
 
 }
-
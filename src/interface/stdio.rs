@@ -0,0 +1,162 @@
+// Standard streams for SafePOSIX
+//
+// Host stdin is read by a thread of our own into a buffer which cages read from, so that they can read it without
+// blocking and poll it without us ever changing the flags of the host's stdin, which we share with whoever started us
+// A cage's standard streams may instead be attached to an in-memory StdioBuffer, keeping them off the host entirely
+
+#![allow(dead_code)]
+use crate::interface;
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::slice;
+use std::sync::{Condvar, Mutex};
+use std::cmp::min;
+
+// How much the reader thread will buffer before waiting for some of it to be read
+const INPUT_BUFFER_MAX: usize = 65536;
+const INPUT_CHUNK_SIZE: usize = 4096;
+
+static STDIN: interface::RustLazyGlobal<interface::RustRfc<HostInput>> = interface::RustLazyGlobal::new(|| {
+    // The thread is only started once something actually uses stdin, so no input is taken from the host otherwise
+    let input = interface::RustRfc::new(HostInput::new());
+    input.spawn_reader(io::stdin());
+    input
+});
+
+// Read up to length bytes of host stdin into pointer, see HostInput::read
+pub fn read_stdin(ptr: *mut u8, length: usize, nonblocking: bool) -> Result<usize, i32> {
+    STDIN.read(ptr, length, nonblocking)
+}
+
+// Whether host stdin has data buffered, whether it has reached end of file, and how many times input has come in
+pub fn stdin_poll_state() -> (bool, bool, u64) {
+    STDIN.poll_state()
+}
+
+struct InputState {
+    buffer: VecDeque<u8>,
+    eof: bool,
+    arrivals: u64,
+}
+
+pub struct HostInput {
+    state: Mutex<InputState>,
+    data_available: Condvar,
+    room_available: Condvar,
+}
+
+impl HostInput {
+    pub fn new() -> HostInput {
+        HostInput {state: Mutex::new(InputState {buffer: VecDeque::new(), eof: false, arrivals: 0}), data_available: Condvar::new(), room_available: Condvar::new()}
+    }
+
+    // Start a thread filling the buffer from source until source reaches end of file or fails
+    pub fn spawn_reader<R: Read + Send + 'static>(self: &interface::RustRfc<Self>, mut source: R) {
+        let input = self.clone();
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; INPUT_CHUNK_SIZE];
+            loop {
+                {
+                    let mut state = input.state.lock().unwrap();
+                    while state.buffer.len() >= INPUT_BUFFER_MAX {
+                        state = input.room_available.wait(state).unwrap();
+                    }
+                }
+
+                let result = source.read(&mut chunk);
+                let mut state = input.state.lock().unwrap();
+                match result {
+                    Ok(0) => {state.eof = true;}
+                    Ok(bytesread) => {
+                        state.buffer.extend(&chunk[..bytesread]);
+                        state.arrivals += 1;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {continue;}
+                    // Like linux, we can't tell a failed stdin from a finished one
+                    Err(_) => {state.eof = true;}
+                }
+                input.data_available.notify_all();
+                interface::notify_readiness();
+                if state.eof {return;}
+            }
+        });
+    }
+
+    // Read up to length bytes into pointer, returning as soon as there are any
+    // Will wait for bytes unless the input is at end of file, in which case reads 0 bytes
+    // If nonblocking, fail with EAGAIN instead of waiting
+    pub fn read(&self, ptr: *mut u8, length: usize, nonblocking: bool) -> Result<usize, i32> {
+        let buf = unsafe {
+            assert!(!ptr.is_null());
+            slice::from_raw_parts_mut(ptr, length)
+        };
+
+        if length == 0 {return Ok(0);}
+
+        let mut state = self.state.lock().unwrap();
+        while state.buffer.is_empty() {
+            if state.eof {return Ok(0);}
+            if nonblocking {return Err(libc::EAGAIN);}
+            state = self.data_available.wait(state).unwrap();
+        }
+
+        let bytes_read = min(length, state.buffer.len());
+        for (dest, byte) in buf.iter_mut().zip(state.buffer.drain(..bytes_read)) {
+            *dest = byte;
+        }
+
+        self.room_available.notify_all();
+        Ok(bytes_read)
+    }
+
+    pub fn poll_state(&self) -> (bool, bool, u64) {
+        let state = self.state.lock().unwrap();
+        (!state.buffer.is_empty(), state.eof, state.arrivals)
+    }
+}
+
+// An in-memory stand-in for a standard stream, which output is captured into and input is read from
+// Reading never waits, an empty buffer being at end of file, so input should be given in full before it is read
+#[derive(Debug)]
+pub struct StdioBuffer {
+    data: Mutex<VecDeque<u8>>,
+}
+
+impl StdioBuffer {
+    pub fn new() -> StdioBuffer {
+        StdioBuffer {data: Mutex::new(VecDeque::new())}
+    }
+
+    pub fn from_input(input: &[u8]) -> StdioBuffer {
+        StdioBuffer {data: Mutex::new(input.iter().cloned().collect())}
+    }
+
+    pub fn write(&self, ptr: *const u8, length: usize) -> usize {
+        let buf = unsafe {
+            assert!(!ptr.is_null());
+            slice::from_raw_parts(ptr, length)
+        };
+        self.data.lock().unwrap().extend(buf);
+        interface::notify_readiness();
+        length
+    }
+
+    pub fn read(&self, ptr: *mut u8, length: usize) -> usize {
+        let buf = unsafe {
+            assert!(!ptr.is_null());
+            slice::from_raw_parts_mut(ptr, length)
+        };
+        let mut data = self.data.lock().unwrap();
+        let bytes_read = min(length, data.len());
+        for (dest, byte) in buf.iter_mut().zip(data.drain(..bytes_read)) {
+            *dest = byte;
+        }
+        bytes_read
+    }
+
+    // Everything written and not yet read
+    pub fn contents(&self) -> Vec<u8> {
+        self.data.lock().unwrap().iter().cloned().collect()
+    }
+}
@@ -8,8 +8,19 @@ use std::time::SystemTime;
 pub use std::time::Instant as RustInstant;
 pub use std::time::Duration as RustDuration;
 
+//the current time in nanoseconds since the epoch, which is how inode times are kept
 pub fn timestamp() -> u64 {
-    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_nanos() as u64
+}
+
+//a timestamp split into seconds and nanoseconds, as in a timespec
+pub fn timestamp_to_timespec(time: u64) -> (u64, u64) {
+    (time / 1_000_000_000, time % 1_000_000_000)
+}
+
+//None if the time is too far off to be kept, which is a little after the year 2554
+pub fn timespec_to_timestamp(sec: u64, nsec: u64) -> Option<u64> {
+    sec.checked_mul(1_000_000_000)?.checked_add(nsec)
 }
 
 // Create a new timer
@@ -38,7 +38,7 @@ pub struct StatData {
   pub st_size: usize,
   pub st_blksize: isize,
   pub st_blocks: usize,
-  //currently we don't populate or care about the time bits here
+  //the times as seconds and nanoseconds since the epoch, as in a timespec
   pub st_atim: (u64, u64),
   pub st_mtim: (u64, u64),
   pub st_ctim: (u64, u64)
@@ -58,6 +58,20 @@ pub struct PipeArray {
   pub writefd: i32,
 }
 
+#[derive(Eq, PartialEq, Default, Copy, Clone)]
+#[repr(C)]
+pub struct Linger {
+  pub l_onoff: i32,
+  pub l_linger: i32,
+}
+
+#[derive(Eq, PartialEq, Default, Copy, Clone)]
+#[repr(C)]
+pub struct SockPair {
+  pub sock1: i32,
+  pub sock2: i32,
+}
+
 
 //redefining the Arg union to maintain the flow of the program
 #[repr(C)]
@@ -75,7 +89,15 @@ pub union Arg {
   pub dispatch_rlimitstruct: *mut Rlimit,
   pub dispatch_statdatastruct: *mut StatData,
   pub dispatch_fsdatastruct: *mut FSData,
-  pub dispatch_pipearray: *mut PipeArray
+  pub dispatch_pipearray: *mut PipeArray,
+  pub dispatch_sockpair: *mut SockPair,
+  pub dispatch_pollstructarray: *mut PollStruct,
+  pub dispatch_fdset: *mut FdSet,
+  pub dispatch_timeval: *mut TimeVal,
+  pub dispatch_epollevent: *mut EpollEvent,
+  pub dispatch_sockaddrstruct: *mut SockaddrDummy, //Typically corresponds to a struct sockaddr* filled in by the call, as in accept
+  pub dispatch_constsockaddrstruct: *const SockaddrDummy, //Typically corresponds to a const struct sockaddr* as in bind
+  pub dispatch_socklen_t_ptr: *mut u32
 }
 
 
@@ -251,20 +273,338 @@ pub fn get_pipearray<'a>(union_argument: Arg) -> Result<&'a mut PipeArray, i32>
     return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
 }
 
-#[derive(Debug)]
+pub fn get_sockpair<'a>(union_argument: Arg) -> Result<&'a mut SockPair, i32> {
+    let pointer = unsafe{union_argument.dispatch_sockpair};
+    if !pointer.is_null() {
+        return Ok(unsafe{&mut *pointer});
+    }
+    return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+}
+
+pub fn get_pollstruct_slice<'a>(union_argument: Arg, size: usize) -> Result<&'a mut [PollStruct], i32> {
+    let pointer = unsafe{union_argument.dispatch_pollstructarray};
+    if size == 0 {
+        return Ok(&mut []);
+    }
+    if !pointer.is_null() {
+        return Ok(unsafe{std::slice::from_raw_parts_mut(pointer, size)});
+    }
+    return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+}
+
+pub fn get_epollevent<'a>(union_argument: Arg) -> Result<&'a mut EpollEvent, i32> {
+    let pointer = unsafe{union_argument.dispatch_epollevent};
+    if !pointer.is_null() {
+        return Ok(unsafe{&mut *pointer});
+    }
+    return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+}
+
+pub fn get_epollevent_slice<'a>(union_argument: Arg, size: usize) -> Result<&'a mut [EpollEvent], i32> {
+    let pointer = unsafe{union_argument.dispatch_epollevent};
+    if size == 0 {
+        return Ok(&mut []);
+    }
+    if !pointer.is_null() {
+        return Ok(unsafe{std::slice::from_raw_parts_mut(pointer, size)});
+    }
+    return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+}
+
+//select takes null for the sets and timeout it doesn't care about
+pub fn get_fdset<'a>(union_argument: Arg) -> Result<Option<&'a mut FdSet>, i32> {
+    let pointer = unsafe{union_argument.dispatch_fdset};
+    if !pointer.is_null() {
+        return Ok(Some(unsafe{&mut *pointer}));
+    }
+    Ok(None)
+}
+
+pub fn get_timeval<'a>(union_argument: Arg) -> Result<Option<&'a mut TimeVal>, i32> {
+    let pointer = unsafe{union_argument.dispatch_timeval};
+    if !pointer.is_null() {
+        return Ok(Some(unsafe{&mut *pointer}));
+    }
+    Ok(None)
+}
+
+pub fn get_socklen_t_ptr(union_argument: Arg) -> Result<*mut u32, i32> {
+    let data = unsafe{union_argument.dispatch_socklen_t_ptr};
+    if !data.is_null() {
+        return Ok(data);
+    }
+    return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+}
+
+//true if the pointer passed in the argument is null, used for optional output arguments like the address in accept
+pub fn arg_nullity(union_argument: &Arg) -> bool {
+    unsafe{union_argument.dispatch_cbuf}.is_null()
+}
+
+pub fn get_sockaddr(union_argument: Arg, addrlen: u32) -> Result<GenSockaddr, i32> {
+    let pointer = unsafe{union_argument.dispatch_constsockaddrstruct};
+    if pointer.is_null() {
+        return Err(syscall_error(Errno::EFAULT, "dispatcher", "input data not valid"));
+    }
+
+    //the family field is common to every sockaddr, so we check it to know how much we are allowed to read
+    if (addrlen as usize) < size_of::<u16>() {
+        return Err(syscall_error(Errno::EINVAL, "dispatcher", "input address length is too small"));
+    }
+    match unsafe{(*pointer).sa_family} {
+        AF_UNIX => {
+            //the path is whatever follows the family, and need not be nul terminated
+            let pathlen = interface::rust_min(addrlen as usize - size_of::<u16>(), UNIX_PATH_MAX);
+            let mut unixaddr = SockaddrUnix {sun_family: AF_UNIX, sun_path: [0; UNIX_PATH_MAX]};
+            unsafe{std::ptr::copy_nonoverlapping((pointer as *const u8).add(size_of::<u16>()), unixaddr.sun_path.as_mut_ptr(), pathlen);}
+            return Ok(GenSockaddr::Unix(unixaddr));
+        }
+        AF_INET => {
+            if (addrlen as usize) < size_of::<SockaddrV4>() {
+                return Err(syscall_error(Errno::EINVAL, "dispatcher", "input address length is too small for an AF_INET address"));
+            }
+            let v4addr = unsafe{*(pointer as *const SockaddrV4)};
+            return Ok(GenSockaddr::V4(v4addr));
+        }
+        AF_INET6 => {
+            if (addrlen as usize) < size_of::<SockaddrV6>() {
+                return Err(syscall_error(Errno::EINVAL, "dispatcher", "input address length is too small for an AF_INET6 address"));
+            }
+            let v6addr = unsafe{*(pointer as *const SockaddrV6)};
+            return Ok(GenSockaddr::V6(v6addr));
+        }
+        _ => {
+            return Err(syscall_error(Errno::EAFNOSUPPORT, "dispatcher", "input address family is not supported"));
+        }
+    }
+}
+
+//copies a sockaddr out to the user's buffer, truncating it to the length the user gave and
+//reporting the real length of the address back through the length pointer, as linux does
+pub fn copy_out_sockaddr(addrptr: *mut SockaddrDummy, addrlenptr: *mut u32, gensockaddr: GenSockaddr) {
+    let (srcptr, srclen) = match gensockaddr {
+        //a unix address is only as long as its path plus the terminating nul, or just the family if it has no path
+        GenSockaddr::Unix(ref unixaddr) => {
+            let pathlen = gensockaddr.path().len();
+            let addrlen = if pathlen == 0 {size_of::<u16>()} else {interface::rust_min(size_of::<u16>() + pathlen + 1, size_of::<SockaddrUnix>())};
+            (unixaddr as *const SockaddrUnix as *const u8, addrlen)
+        }
+        GenSockaddr::V4(ref v4addr) => (v4addr as *const SockaddrV4 as *const u8, size_of::<SockaddrV4>()),
+        GenSockaddr::V6(ref v6addr) => (v6addr as *const SockaddrV6 as *const u8, size_of::<SockaddrV6>()),
+    };
+
+    let initaddrlen = unsafe{*addrlenptr} as usize;
+    unsafe{
+        std::ptr::copy_nonoverlapping(srcptr, addrptr as *mut u8, interface::rust_min(initaddrlen, srclen));
+        *addrlenptr = srclen as u32;
+    }
+}
+
+//laid out like struct pollfd
+#[derive(Debug, Default, Copy, Clone)]
 #[repr(C)]
 pub struct PollStruct {
-    pub events: u32,
-    pub revents: u32,
-    pub fd: i32 
+    pub fd: i32,
+    pub events: i16,
+    pub revents: i16
 }
 
-//EPOLL
+//SELECT
+pub const FD_SET_MAX_FD: i32 = 1024;
+
+//laid out like the kernel's fd_set, a bitmap of the file descriptors up to FD_SET_MAX_FD
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
+pub struct FdSet {
+    pub fds_bits: [u64; (FD_SET_MAX_FD / 64) as usize]
+}
+
+impl FdSet {
+    pub fn new() -> FdSet {
+        FdSet {fds_bits: [0; (FD_SET_MAX_FD / 64) as usize]}
+    }
+
+    pub fn is_set(&self, fd: i32) -> bool {
+        self.fds_bits[(fd / 64) as usize] & (1 << (fd % 64)) != 0
+    }
+
+    pub fn set(&mut self, fd: i32) {
+        self.fds_bits[(fd / 64) as usize] |= 1 << (fd % 64);
+    }
+
+    pub fn clear(&mut self, fd: i32) {
+        self.fds_bits[(fd / 64) as usize] &= !(1 << (fd % 64));
+    }
+
+    pub fn zero(&mut self) {
+        self.fds_bits = [0; (FD_SET_MAX_FD / 64) as usize];
+    }
+}
+
+//laid out like struct timeval
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct TimeVal {
+    pub tv_sec: i64,
+    pub tv_usec: i64
+}
+
+//EPOLL
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
 pub struct EpollEvent {
     pub events: u32,
     pub fd: i32 
     //in native this is a union which could be one of a number of things
     //however, we only support EPOLL_CTL subcommands which take the fd
 }
+
+//Address families, redefined here so that the interface doesn't depend on safeposix constants
+const AF_UNIX: u16 = 1;
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+
+//the part of a sockaddr which every address family shares, we read this to know which real struct we were passed
+#[derive(Debug, Copy, Clone, Default)]
+#[repr(C)]
+pub struct SockaddrDummy {
+    pub sa_family: u16,
+    pub _sa_data: [u8; 14]
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct V4Addr {
+    pub s_addr: u32 //in network byte order
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct SockaddrV4 {
+    pub sin_family: u16,
+    pub sin_port: u16, //in network byte order
+    pub sin_addr: V4Addr,
+    pub padding: u64
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct V6Addr {
+    pub s6_addr: [u8; 16]
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct SockaddrV6 {
+    pub sin6_family: u16,
+    pub sin6_port: u16, //in network byte order
+    pub sin6_flowinfo: u32,
+    pub sin6_addr: V6Addr,
+    pub sin6_scope_id: u32
+}
+
+pub const UNIX_PATH_MAX: usize = 108;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct SockaddrUnix {
+    pub sun_family: u16,
+    pub sun_path: [u8; UNIX_PATH_MAX] //nul terminated unless the path fills the whole array
+}
+
+//a socket address of any of the families we support
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GenSockaddr {
+    Unix(SockaddrUnix),
+    V4(SockaddrV4),
+    V6(SockaddrV6)
+}
+
+impl GenSockaddr {
+    const LOOPBACK_V6: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+
+    //the unspecified address (i.e. 0.0.0.0 or ::) with port 0, for the given family
+    pub fn new_unspecified(family: u16) -> Option<GenSockaddr> {
+        match family {
+            AF_UNIX => Some(GenSockaddr::Unix(SockaddrUnix {sun_family: AF_UNIX, sun_path: [0; UNIX_PATH_MAX]})),
+            AF_INET => Some(GenSockaddr::V4(SockaddrV4 {sin_family: AF_INET, ..Default::default()})),
+            AF_INET6 => Some(GenSockaddr::V6(SockaddrV6 {sin6_family: AF_INET6, ..Default::default()})),
+            _ => None
+        }
+    }
+
+    //the loopback address (i.e. 127.0.0.1 or ::1) with port 0, for the given family
+    pub fn new_loopback(family: u16) -> Option<GenSockaddr> {
+        match family {
+            AF_INET => Some(GenSockaddr::V4(SockaddrV4 {sin_family: AF_INET, sin_addr: V4Addr {s_addr: u32::from_ne_bytes([127, 0, 0, 1])}, ..Default::default()})),
+            AF_INET6 => {
+                let mut s6_addr = [0; 16];
+                s6_addr[15] = 1;
+                Some(GenSockaddr::V6(SockaddrV6 {sin6_family: AF_INET6, sin6_addr: V6Addr {s6_addr: s6_addr}, ..Default::default()}))
+            }
+            _ => None
+        }
+    }
+
+    //paths longer than a sockaddr_un can hold are truncated
+    pub fn new_unix(path: &str) -> GenSockaddr {
+        let mut sun_path = [0; UNIX_PATH_MAX];
+        let len = interface::rust_min(path.len(), UNIX_PATH_MAX);
+        sun_path[..len].copy_from_slice(&path.as_bytes()[..len]);
+        GenSockaddr::Unix(SockaddrUnix {sun_family: AF_UNIX, sun_path: sun_path})
+    }
+
+    //the path of a unix socket address, empty for unnamed sockets and for other families
+    pub fn path(&self) -> &str {
+        match self {
+            GenSockaddr::Unix(unixaddr) => {
+                let len = unixaddr.sun_path.iter().position(|&c| c == 0).unwrap_or(UNIX_PATH_MAX);
+                std::str::from_utf8(&unixaddr.sun_path[..len]).unwrap_or("")
+            }
+            _ => ""
+        }
+    }
+
+    //true for 127.0.0.0/8 and ::1
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            GenSockaddr::Unix(_) => false,
+            GenSockaddr::V4(v4addr) => v4addr.sin_addr.s_addr.to_ne_bytes()[0] == 127,
+            GenSockaddr::V6(v6addr) => v6addr.sin6_addr.s6_addr == GenSockaddr::LOOPBACK_V6
+        }
+    }
+
+    //true for 0.0.0.0 and ::
+    pub fn is_unspecified(&self) -> bool {
+        match self {
+            GenSockaddr::Unix(_) => false,
+            GenSockaddr::V4(v4addr) => v4addr.sin_addr.s_addr == 0,
+            GenSockaddr::V6(v6addr) => v6addr.sin6_addr.s6_addr == [0; 16]
+        }
+    }
+
+    pub fn get_family(&self) -> u16 {
+        match self {
+            GenSockaddr::Unix(unixaddr) => unixaddr.sun_family,
+            GenSockaddr::V4(v4addr) => v4addr.sin_family,
+            GenSockaddr::V6(v6addr) => v6addr.sin6_family
+        }
+    }
+
+    //port in host byte order
+    pub fn port(&self) -> u16 {
+        match self {
+            GenSockaddr::Unix(_) => 0,
+            GenSockaddr::V4(v4addr) => u16::from_be(v4addr.sin_port),
+            GenSockaddr::V6(v6addr) => u16::from_be(v6addr.sin6_port)
+        }
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        match self {
+            GenSockaddr::Unix(_) => {}, //unix sockets have no ports
+            GenSockaddr::V4(v4addr) => v4addr.sin_port = port.to_be(),
+            GenSockaddr::V6(v6addr) => v6addr.sin6_port = port.to_be()
+        }
+    }
+}
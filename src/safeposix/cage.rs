@@ -2,7 +2,7 @@
 use crate::interface;
 //going to get the datatypes and errnos from the cage file from now on
 pub use crate::interface::errnos::{Errno, syscall_error};
-pub use crate::interface::types::{Arg, EpollEvent, FSData, Rlimit, StatData, PipeArray, PollStruct};
+pub use crate::interface::types::{Arg, EpollEvent, FSData, Rlimit, StatData, PipeArray, SockPair, PollStruct};
 
 pub use super::syscalls::fs_constants::*;
 pub use super::syscalls::sys_constants::*;
@@ -16,6 +16,18 @@ pub static PIPE_TABLE: interface::RustLazyGlobal<interface::RustLock<interface::
         interface::RustLock::new(interface::new_hashmap())
 );
 
+//the pipes behind fifos which are currently open, by inode number of the fifo, each mapping to a key into PIPE_TABLE
+pub static FIFO_TABLE: interface::RustLazyGlobal<interface::RustLock<interface::RustHashMap<usize, i32>>> = 
+    interface::RustLazyGlobal::new(|| 
+        interface::RustLock::new(interface::new_hashmap())
+);
+
+//host socket objects, referred to by the socketobjectid of socket descriptors
+pub static SOCKET_OBJECT_TABLE: interface::RustLazyGlobal<interface::RustLock<interface::RustHashMap<i32, interface::RustRfc<interface::Socket>>>> = 
+    interface::RustLazyGlobal::new(|| 
+        interface::RustLock::new(interface::new_hashmap())
+);
+
 #[derive(Debug)]
 pub enum FileDescriptor {
     File(FileDesc),
@@ -37,6 +49,7 @@ pub struct FileDesc {
 pub struct StreamDesc {
     pub position: usize,
     pub stream: i32, //0 for stdin, 1 for stdout, 2 for stderr
+    pub buffer: Option<interface::RustRfc<interface::StdioBuffer>>, //set when attached to an in-memory buffer rather than the host
     pub flags: i32,
     pub advlock: interface::AdvisoryLock
 }
@@ -47,23 +60,36 @@ pub struct SocketDesc {
     pub domain: i32,
     pub socktype: i32,
     pub protocol: i32,
-    pub options: i32,
-    pub sndbuf: i32,
+    pub options: i32, //bitmask of the boolean SOL_SOCKET options which are set
+    pub tcpoptions: i32, //bitmask of the IPPROTO_TCP options which are set
+    pub linger: i32, //seconds to linger on close, only meaningful while SO_LINGER is set in options
+    pub sndbuf: i32, //0 until set, in which case the default size applies
     pub rcvbuf: i32,
-    //pub state: ConnState,
+    pub state: ConnState,
+    pub shutdown: interface::RustAtomicI32, //RCV_SHUTDOWN and SEND_SHUTDOWN bits, atomic so shutdown can wake a blocked recv
     pub flags: i32,
-    pub errno: i32,
     //pub pendingconnections: Vec<(Result<interface::Socket, i32>, interface::GenSockaddr)>,
-    //pub localaddr: Option<interface::GenSockaddr>,
-    //pub remoteaddr: Option<interface::GenSockaddr>,
+    pub localaddr: Option<interface::GenSockaddr>,
+    pub remoteaddr: Option<interface::GenSockaddr>,
     //pub last_peek: interface::RustDeque<u8>,
-    pub socketobjectid: Option<i32>,
+    pub socketobjectid: Option<i32>, //key into SOCKET_OBJECT_TABLE for sockets backed by a host socket
+    pub connection: Option<interface::EmulatedConnection>, //set when connected to another socket within lind
+    pub binding: Option<interface::EmulatedBinding>, //set when bound to an address other lind sockets can reach
     pub advlock: interface::AdvisoryLock
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConnState {
+    NOTCONNECTED,
+    CONNECTING, //a nonblocking connect on the host which may not have finished yet
+    CONNECTED,
+    LISTEN
+}
+
 #[derive(Debug)]
 pub struct PipeDesc {
     pub pipe: i32,
+    pub inode: Option<usize>, //set for either end of a fifo, to the fifo's inode
     pub flags: i32,
     pub advlock: interface::AdvisoryLock
 }
@@ -72,19 +98,100 @@ pub struct PipeDesc {
 pub struct EpollDesc {
     pub mode: i32,
     pub registered_fds: interface::RustHashMap<i32, EpollEvent>,
+    //the descriptor each fd referred to when it was registered, so that a descriptor which later gets the same fd
+    //number doesn't inherit the registration
+    pub registered_descs: interface::RustHashMap<i32, interface::RustWeak<interface::RustLock<FileDescriptor>>>,
+    //for edge triggered registrations, the events which were ready when we last looked and how many arrivals the fd had seen then
+    pub lastready: interface::RustHashMap<i32, (u32, u64)>,
     pub advlock: interface::AdvisoryLock,
     pub errno: i32,
     pub flags: i32
 }
 
-pub type FdTable = interface::RustHashMap<i32, interface::RustRfc<interface::RustLock<FileDescriptor>>>;
+//what one of a cage's standard streams can be attached to, see Cage::redirect_stdio
+pub enum StdioTarget {
+    Host, //the host's own stdin, stdout, or stderr
+    File(String), //a file in the lind filesystem, created and truncated if it is for output
+    Buffer(interface::RustRfc<interface::StdioBuffer>), //an in-memory buffer holding input to be read, or capturing output
+    Pipe(interface::RustRfc<interface::EmulatedPipe>), //a pipe from outside of lind, of which the cage gets the end it needs
+}
+
+//a slot in a cage's fdtable; the descriptor is shared with dups and forked cages, while close-on-exec belongs to the slot alone
+#[derive(Debug, Clone)]
+pub struct FdEntry {
+    pub desc: interface::RustRfc<interface::RustLock<FileDescriptor>>,
+    pub cloexec: bool
+}
+
+impl FdEntry {
+    pub fn new(descriptor: FileDescriptor, cloexec: bool) -> FdEntry {
+        FdEntry {desc: interface::RustRfc::new(interface::RustLock::new(descriptor)), cloexec: cloexec}
+    }
+
+    //another slot for the same descriptor, which like on linux is never close-on-exec
+    pub fn dup(&self) -> FdEntry {
+        FdEntry {desc: self.desc.clone(), cloexec: false}
+    }
+
+    //a reference to the descriptor which doesn't keep it open
+    pub fn downgrade(&self) -> interface::RustWeak<interface::RustLock<FileDescriptor>> {
+        interface::RustRfc::downgrade(&self.desc)
+    }
+
+    pub fn refers_to(&self, desc: &interface::RustWeak<interface::RustLock<FileDescriptor>>) -> bool {
+        interface::RustRfc::as_ptr(&self.desc) == desc.as_ptr()
+    }
+}
+
+impl std::ops::Deref for FdEntry {
+    type Target = interface::RustLock<FileDescriptor>;
+    fn deref(&self) -> &Self::Target {
+        &self.desc
+    }
+}
+
+pub type FdTable = interface::RustHashMap<i32, FdEntry>;
+
+//the user and group ids a cage acts as, the effective ones being what permission checks go by
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub ruid: u32,
+    pub euid: u32,
+    pub suid: u32, //saved, so that an unprivileged cage can switch its effective uid back to it
+    pub rgid: u32,
+    pub egid: u32,
+    pub sgid: u32,
+    pub groups: Vec<u32> //supplementary groups
+}
+
+impl Credentials {
+    pub fn new(uid: u32, gid: u32) -> Credentials {
+        Credentials {ruid: uid, euid: uid, suid: uid, rgid: gid, egid: gid, sgid: gid, groups: vec![]}
+    }
+
+    //whether files of group gid get the group permission bits applied to this cage
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.egid == gid || self.groups.contains(&gid)
+    }
+
+    pub fn is_privileged(&self) -> bool {
+        self.euid == ROOT_UID
+    }
+
+    //the same credentials acting as their real ids, which is how access checks
+    pub fn as_real(&self) -> Credentials {
+        Credentials {euid: self.ruid, egid: self.rgid, ..self.clone()}
+    }
+}
 
 #[derive(Debug)]
 pub struct Cage {
     pub cageid: u64,
     pub cwd: interface::RustLock<interface::RustRfc<interface::RustPathBuf>>,
     pub parent: u64,
-    pub filedescriptortable: interface::RustLock<FdTable>
+    pub filedescriptortable: interface::RustLock<FdTable>,
+    pub creds: interface::RustLock<Credentials>,
+    pub umask: interface::RustAtomicU32 //permission bits cleared from the modes of files the cage creates
 }
 
 impl Cage {
@@ -109,13 +216,13 @@ impl Cage {
         None
     }
 
-    pub fn add_to_fd_table(&self, fd: i32, descriptor: FileDescriptor, fdtable_option: Option<&mut FdTable>) {
+    pub fn add_to_fd_table(&self, fd: i32, descriptor: FileDescriptor, cloexec: bool, fdtable_option: Option<&mut FdTable>) {
         let mut ourwriter;
         let writeguard = if let Some(fdtable) = fdtable_option {fdtable} else {
             ourwriter = self.filedescriptortable.write().unwrap();
             &mut ourwriter
         };
-        writeguard.insert(fd, interface::RustRfc::new(interface::RustLock::new(descriptor)));
+        writeguard.insert(fd, FdEntry::new(descriptor, cloexec));
     }
 
     pub fn rm_from_fd_table(&self, fd: &i32) {
@@ -129,15 +236,58 @@ impl Cage {
     }
 
     pub fn load_lower_handle_stubs(&mut self) {
-        let stdin = interface::RustRfc::new(interface::RustLock::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 0, flags: O_RDONLY, advlock: interface::AdvisoryLock::new()})));
-        let stdout = interface::RustRfc::new(interface::RustLock::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 1, flags: O_WRONLY, advlock: interface::AdvisoryLock::new()})));
-        let stderr = interface::RustRfc::new(interface::RustLock::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 2, flags: O_WRONLY, advlock: interface::AdvisoryLock::new()})));
+        let stdin = FdEntry::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 0, buffer: None, flags: O_RDONLY, advlock: interface::AdvisoryLock::new()}), false);
+        let stdout = FdEntry::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 1, buffer: None, flags: O_WRONLY, advlock: interface::AdvisoryLock::new()}), false);
+        let stderr = FdEntry::new(FileDescriptor::Stream(StreamDesc {position: 0, stream: 2, buffer: None, flags: O_WRONLY, advlock: interface::AdvisoryLock::new()}), false);
         let mut fdtable = self.filedescriptortable.write().unwrap();
         fdtable.insert(0, stdin);
         fdtable.insert(1, stdout);
         fdtable.insert(2, stderr);
     }
 
+    //attaches fd 0, 1, or 2 of the cage to target in place of whatever it was before, which is closed
+    //meant for starting a cage with its standard streams somewhere other than the host's, as a harness running cages would
+    pub fn redirect_stdio(&self, fd: i32, target: StdioTarget) -> i32 {
+        if fd < 0 || fd > 2 {
+            return syscall_error(Errno::EBADF, "redirect_stdio", "only the standard streams can be redirected");
+        }
+        let flags = if fd == 0 {O_RDONLY} else {O_WRONLY};
+
+        let newdesc = match target {
+            StdioTarget::Host => {
+                FileDescriptor::Stream(StreamDesc {position: 0, stream: fd, buffer: None, flags: flags, advlock: interface::AdvisoryLock::new()})
+            }
+            StdioTarget::Buffer(buffer) => {
+                FileDescriptor::Stream(StreamDesc {position: 0, stream: fd, buffer: Some(buffer), flags: flags, advlock: interface::AdvisoryLock::new()})
+            }
+            StdioTarget::Pipe(pipe) => {
+                let pipenumber = if let Some(pipeno) = add_pipe(pipe.clone()) {
+                    pipeno
+                } else {
+                    return syscall_error(Errno::ENFILE, "redirect_stdio", "no available pipe number could be found");
+                };
+                pipe.incr_ref(flags);
+                FileDescriptor::Pipe(PipeDesc {pipe: pipenumber, inode: None, flags: flags, advlock: interface::AdvisoryLock::new()})
+            }
+            //a file is opened and moved into place like a shell would
+            StdioTarget::File(path) => {
+                let openflags = if fd == 0 {O_RDONLY} else {O_WRONLY | O_CREAT | O_TRUNC};
+                let filefd = self.open_syscall(&path, openflags, S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH);
+                if filefd < 0 || filefd == fd {return if filefd < 0 {filefd} else {0};}
+                let dupresult = self.dup2_syscall(filefd, fd);
+                self.close_syscall(filefd);
+                return if dupresult < 0 {dupresult} else {0};
+            }
+        };
+
+        let mut fdtable = self.filedescriptortable.write().unwrap();
+        if fdtable.contains_key(&fd) {
+            self._close_helper(fd, Some(&mut *fdtable));
+        }
+        fdtable.insert(fd, FdEntry::new(newdesc, false));
+        0
+    }
+
 }
 
 pub fn get_next_pipe() -> Option<i32> {
@@ -150,3 +300,43 @@ pub fn get_next_pipe() -> Option<i32> {
 
     return None;
 }
+
+//adds a pipe from outside of lind to the table, returning the number it was given or None if the table is full
+//a pipe which is already in the table keeps the number it has
+pub fn add_pipe(pipe: interface::RustRfc<interface::EmulatedPipe>) -> Option<i32> {
+    let mut table = PIPE_TABLE.write().unwrap();
+    if let Some((pipeno, _)) = table.iter().find(|(_, existing)| interface::RustRfc::ptr_eq(existing, &pipe)) {
+        return Some(*pipeno);
+    }
+    for pipeno in STARTINGPIPE..MAXPIPE {
+        if !table.contains_key(&pipeno) {
+            table.insert(pipeno, pipe);
+            return Some(pipeno);
+        }
+    }
+
+    return None;
+}
+
+//gives up a reference to the end of a pipe which something outside of lind holds, as given to add_pipe,
+//dropping the pipe from the table along with the last reference
+pub fn release_pipe_end(pipe: &interface::RustRfc<interface::EmulatedPipe>, flags: i32) {
+    let mut table = PIPE_TABLE.write().unwrap();
+    pipe.decr_ref(flags);
+    if pipe.get_write_ref() + pipe.get_read_ref() == 0 {
+        table.retain(|_, existing| !interface::RustRfc::ptr_eq(existing, pipe));
+    }
+}
+
+//adds a socket object to the table, returning the id it was given or None if the table is full
+pub fn add_socket_object(sockobj: interface::Socket) -> Option<i32> {
+    let mut table = SOCKET_OBJECT_TABLE.write().unwrap();
+    for id in STARTINGSOCKETOBJ..MAXSOCKETOBJ {
+        if !table.contains_key(&id) {
+            table.insert(id, interface::RustRfc::new(sockobj));
+            return Some(id);
+        }
+    }
+
+    return None;
+}
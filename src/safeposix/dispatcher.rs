@@ -12,7 +12,9 @@ const READ_SYSCALL: i32 = 12;
 const WRITE_SYSCALL: i32 = 13;
 const LSEEK_SYSCALL: i32 = 14;
 const LIND_FS_IOCTL: i32 = 15;
+const TRUNCATE_SYSCALL: i32 = 16;
 const FXSTAT_SYSCALL: i32 = 17;
+const FTRUNCATE_SYSCALL: i32 = 18;
 const FSTATFS_SYSCALL: i32 = 19;
 const MMAP_SYSCALL: i32 = 21;
 const MUNMAP_SYSCALL: i32 = 22;
@@ -62,14 +64,18 @@ const PWRITE_SYSCALL: i32 = 127;
 const CHDIR_SYSCALL: i32 = 130;
 const MKDIR_SYSCALL: i32 = 131;
 const RMDIR_SYSCALL: i32 = 132;
+const CHMOD_SYSCALL: i32 = 133;
+const FCHMOD_SYSCALL: i32 = 134;
+const FCHMODAT_SYSCALL: i32 = 135;
 const SOCKET_SYSCALL: i32 = 136;
+const UMASK_SYSCALL: i32 = 137;
 
 const GETSOCKNAME_SYSCALL: i32 = 144;
 const GETPEERNAME_SYSCALL: i32 = 145;
 
 
 use crate::interface;
-use super::cage::{Arg, CAGE_TABLE, Cage, FSData, StatData};
+use super::cage::{Arg, CAGE_TABLE, Cage, Credentials, DEFAULT_GID, DEFAULT_UID, DEFAULT_UMASK, Errno, FSData, StatData, syscall_error};
 use super::filesystem::{FS_METADATA, load_fs, incref_root, persist_metadata};
 
 
@@ -180,9 +186,15 @@ pub extern "C" fn dispatcher(cageid: u64, callnum: i32, arg1: Arg, arg2: Arg, ar
         PWRITE_SYSCALL => {
             check_and_dispatch!(cage.pwrite_syscall, interface::get_int(arg1), interface::get_mutcbuf(arg2), interface::get_usize(arg3), interface::get_isize(arg4))
         }
-        // CHMOD_SYSCALL => {
-        //     check_and_dispatch!(cage.chmod_syscall, interface::get_cstr(arg1), interface::get_uint(arg2))
-        // }
+        CHMOD_SYSCALL => {
+            check_and_dispatch!(cage.chmod_syscall, interface::get_cstr(arg1), interface::get_uint(arg2))
+        }
+        FCHMOD_SYSCALL => {
+            check_and_dispatch!(cage.fchmod_syscall, interface::get_int(arg1), interface::get_uint(arg2))
+        }
+        FCHMODAT_SYSCALL => {
+            check_and_dispatch!(cage.fchmodat_syscall, interface::get_int(arg1), interface::get_cstr(arg2), interface::get_uint(arg3), interface::get_int(arg4))
+        }
         RMDIR_SYSCALL => {
             check_and_dispatch!(cage.rmdir_syscall, interface::get_cstr(arg1))
         }
@@ -201,6 +213,150 @@ pub extern "C" fn dispatcher(cageid: u64, callnum: i32, arg1: Arg, arg2: Arg, ar
         PIPE_SYSCALL => {
             check_and_dispatch!(cage.pipe_syscall, interface::get_pipearray(arg1))
         }
+        PIPE2_SYSCALL => {
+            check_and_dispatch!(cage.pipe2_syscall, interface::get_pipearray(arg1), interface::get_int(arg2))
+        }
+        UMASK_SYSCALL => {
+            check_and_dispatch!(cage.umask_syscall, interface::get_uint(arg1))
+        }
+        SOCKET_SYSCALL => {
+            check_and_dispatch!(cage.socket_syscall, interface::get_int(arg1), interface::get_int(arg2), interface::get_int(arg3))
+        }
+        SOCKETPAIR_SYSCALL => {
+            check_and_dispatch!(cage.socketpair_syscall, interface::get_int(arg1), interface::get_int(arg2), interface::get_int(arg3), interface::get_sockpair(arg4))
+        }
+        BIND_SYSCALL => {
+            let addr = match interface::get_uint(arg3).and_then(|addrlen| interface::get_sockaddr(arg2, addrlen)) {
+                Ok(addr) => addr,
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.bind_syscall, interface::get_int(arg1), Ok::<&interface::GenSockaddr, i32>(&addr))
+        }
+        LISTEN_SYSCALL => {
+            check_and_dispatch!(cage.listen_syscall, interface::get_int(arg1), interface::get_int(arg2))
+        }
+        CONNECT_SYSCALL => {
+            let addr = match interface::get_uint(arg3).and_then(|addrlen| interface::get_sockaddr(arg2, addrlen)) {
+                Ok(addr) => addr,
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.connect_syscall, interface::get_int(arg1), Ok::<&interface::GenSockaddr, i32>(&addr))
+        }
+        ACCEPT_SYSCALL => {
+            //the address we pass in is just a placeholder, accept fills it in with the peer's address
+            let mut addr = interface::GenSockaddr::V4(interface::SockaddrV4::default());
+            let addrref = &mut addr;
+            let nullity1 = interface::arg_nullity(&arg2);
+            let nullity2 = interface::arg_nullity(&arg3);
+
+            if nullity1 && nullity2 {
+                check_and_dispatch!(cage.accept_syscall, interface::get_int(arg1), Ok::<&mut interface::GenSockaddr, i32>(addrref))
+            } else if !(nullity1 || nullity2) {
+                let rv = check_and_dispatch!(cage.accept_syscall, interface::get_int(arg1), Ok::<&mut interface::GenSockaddr, i32>(addrref));
+                if rv >= 0 {
+                    interface::copy_out_sockaddr(unsafe{arg2.dispatch_sockaddrstruct}, unsafe{arg3.dispatch_socklen_t_ptr}, addr);
+                }
+                rv
+            } else {
+                syscall_error(Errno::EINVAL, "accept", "exactly one of the last two arguments was zero")
+            }
+        }
+        SENDTO_SYSCALL => {
+            //with no destination given sendto behaves just like send
+            if interface::arg_nullity(&arg5) {
+                return check_and_dispatch!(cage.send_syscall, interface::get_int(arg1), interface::get_cbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4));
+            }
+            let addr = match interface::get_uint(arg6).and_then(|addrlen| interface::get_sockaddr(arg5, addrlen)) {
+                Ok(addr) => addr,
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.sendto_syscall, interface::get_int(arg1), interface::get_cbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4), Ok::<&interface::GenSockaddr, i32>(&addr))
+        }
+        RECVFROM_SYSCALL => {
+            //the address we pass in is just a placeholder, recvfrom fills it in with the sender's address
+            let mut addr = interface::GenSockaddr::V4(interface::SockaddrV4::default());
+            let nullity1 = interface::arg_nullity(&arg5);
+            let nullity2 = interface::arg_nullity(&arg6);
+
+            if nullity1 && nullity2 {
+                check_and_dispatch!(cage.recvfrom_syscall, interface::get_int(arg1), interface::get_mutcbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4), Ok::<&mut Option<&mut interface::GenSockaddr>, i32>(&mut None))
+            } else if !(nullity1 || nullity2) {
+                let mut addrref = Some(&mut addr);
+                let rv = check_and_dispatch!(cage.recvfrom_syscall, interface::get_int(arg1), interface::get_mutcbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4), Ok::<&mut Option<&mut interface::GenSockaddr>, i32>(&mut addrref));
+                if rv >= 0 {
+                    interface::copy_out_sockaddr(unsafe{arg5.dispatch_sockaddrstruct}, unsafe{arg6.dispatch_socklen_t_ptr}, addr);
+                }
+                rv
+            } else {
+                syscall_error(Errno::EINVAL, "recvfrom", "exactly one of the last two arguments was zero")
+            }
+        }
+        GETSOCKNAME_SYSCALL => {
+            let mut addr = interface::GenSockaddr::V4(interface::SockaddrV4::default());
+            let addrref = &mut addr;
+            if interface::arg_nullity(&arg2) || interface::arg_nullity(&arg3) {
+                return syscall_error(Errno::EFAULT, "getsockname", "the address or its length pointer was null");
+            }
+            let rv = check_and_dispatch!(cage.getsockname_syscall, interface::get_int(arg1), Ok::<&mut interface::GenSockaddr, i32>(addrref));
+            if rv >= 0 {
+                interface::copy_out_sockaddr(unsafe{arg2.dispatch_sockaddrstruct}, unsafe{arg3.dispatch_socklen_t_ptr}, addr);
+            }
+            rv
+        }
+        GETPEERNAME_SYSCALL => {
+            let mut addr = interface::GenSockaddr::V4(interface::SockaddrV4::default());
+            let addrref = &mut addr;
+            if interface::arg_nullity(&arg2) || interface::arg_nullity(&arg3) {
+                return syscall_error(Errno::EFAULT, "getpeername", "the address or its length pointer was null");
+            }
+            let rv = check_and_dispatch!(cage.getpeername_syscall, interface::get_int(arg1), Ok::<&mut interface::GenSockaddr, i32>(addrref));
+            if rv >= 0 {
+                interface::copy_out_sockaddr(unsafe{arg2.dispatch_sockaddrstruct}, unsafe{arg3.dispatch_socklen_t_ptr}, addr);
+            }
+            rv
+        }
+        SELECT_SYSCALL => {
+            check_and_dispatch!(cage.select_syscall, interface::get_int(arg1), interface::get_fdset(arg2), interface::get_fdset(arg3), interface::get_fdset(arg4), interface::get_timeval(arg5))
+        }
+        POLL_SYSCALL => {
+            let nfds = match interface::get_usize(arg2) {
+                Ok(nfds) => nfds,
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.poll_syscall, interface::get_pollstruct_slice(arg1, nfds), interface::get_int(arg3))
+        }
+        EPOLL_CREATE_SYSCALL => {
+            check_and_dispatch!(cage.epoll_create_syscall, interface::get_int(arg1))
+        }
+        EPOLL_CTL_SYSCALL => {
+            check_and_dispatch!(cage.epoll_ctl_syscall, interface::get_int(arg1), interface::get_int(arg2), interface::get_int(arg3), interface::get_epollevent(arg4))
+        }
+        EPOLL_WAIT_SYSCALL => {
+            let maxevents = match interface::get_int(arg3) {
+                Ok(maxevents) => maxevents,
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.epoll_wait_syscall, interface::get_int(arg1), interface::get_epollevent_slice(arg2, interface::rust_max(maxevents, 0) as usize), Ok::<i32, i32>(maxevents), interface::get_int(arg4))
+        }
+        SHUTDOWN_SYSCALL => {
+            check_and_dispatch!(cage.shutdown_syscall, interface::get_int(arg1), interface::get_int(arg2))
+        }
+        SETSOCKOPT_SYSCALL => {
+            check_and_dispatch!(cage.setsockopt_syscall, interface::get_int(arg1), interface::get_int(arg2), interface::get_int(arg3), interface::get_cbuf(arg4), interface::get_uint(arg5))
+        }
+        GETSOCKOPT_SYSCALL => {
+            let optlen = match interface::get_socklen_t_ptr(arg5) {
+                Ok(optlen) => unsafe{&mut *optlen},
+                Err(e) => {return e;}
+            };
+            check_and_dispatch!(cage.getsockopt_syscall, interface::get_int(arg1), interface::get_int(arg2), interface::get_int(arg3), interface::get_mutcbuf(arg4), Ok::<&mut u32, i32>(optlen))
+        }
+        SEND_SYSCALL => {
+            check_and_dispatch!(cage.send_syscall, interface::get_int(arg1), interface::get_cbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4))
+        }
+        RECV_SYSCALL => {
+            check_and_dispatch!(cage.recv_syscall, interface::get_int(arg1), interface::get_mutcbuf(arg2), interface::get_usize(arg3), interface::get_int(arg4))
+        }
         _ => {//unknown syscall
             -1
         }
@@ -216,13 +372,17 @@ pub extern "C" fn lindrustinit() {
 
     let mut utilcage = Cage{
         cageid: 0, cwd: interface::RustLock::new(interface::RustRfc::new(interface::RustPathBuf::from("/"))),
-        parent: 0, filedescriptortable: interface::RustLock::new(interface::RustHashMap::new())};
+        parent: 0, filedescriptortable: interface::RustLock::new(interface::RustHashMap::new()),
+        creds: interface::RustLock::new(Credentials::new(DEFAULT_UID, DEFAULT_GID)),
+        umask: interface::RustAtomicU32::new(DEFAULT_UMASK)};
     mutcagetable.insert(0, interface::RustRfc::new(utilcage));
 
     //init cage is its own parent
     let mut initcage = Cage{
         cageid: 1, cwd: interface::RustLock::new(interface::RustRfc::new(interface::RustPathBuf::from("/"))),
-        parent: 1, filedescriptortable: interface::RustLock::new(interface::RustHashMap::new())};
+        parent: 1, filedescriptortable: interface::RustLock::new(interface::RustHashMap::new()),
+        creds: interface::RustLock::new(Credentials::new(DEFAULT_UID, DEFAULT_GID)),
+        umask: interface::RustAtomicU32::new(DEFAULT_UMASK)};
     initcage.load_lower_handle_stubs();
     mutcagetable.insert(1, interface::RustRfc::new(initcage));
 
@@ -5,9 +5,12 @@ use crate::interface;
 use super::syscalls::fs_constants::*;
 use super::syscalls::sys_constants::*;
 
-use super::cage::Cage;
+use super::cage::{Cage, Credentials, Errno};
 
 pub const METADATAFILENAME: &str = "lind.metadata";
+//bumped whenever what's persisted in the metadata changes meaning, so that older metadata can be converted when loaded
+//version 1 keeps inode times in nanoseconds rather than seconds
+pub const METADATA_VERSION: u32 = 1;
 
 pub static FS_METADATA: interface::RustLazyGlobal<interface::RustRfc<interface::RustLock<FilesystemMetadata>>> = 
     interface::RustLazyGlobal::new(||
@@ -24,6 +27,138 @@ pub enum Inode {
     File(GenericInode),
     CharDev(DeviceInode),
     Dir(DirectoryInode),
+    Socket(SocketInode),
+    Fifo(GenericInode), //its contents live in the pipe FIFO_TABLE maps it to while it is open, and are never persisted
+    Symlink(SymlinkInode),
+}
+
+impl Inode {
+    //the mode, owner, and group of the inode, whatever kind it is
+    pub fn ownership(&self) -> (u32, u32, u32) {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => (f.mode, f.uid, f.gid),
+            Inode::CharDev(f) => (f.mode, f.uid, f.gid),
+            Inode::Socket(f) => (f.mode, f.uid, f.gid),
+            Inode::Dir(f) => (f.mode, f.uid, f.gid),
+            Inode::Symlink(f) => (f.mode, f.uid, f.gid),
+        }
+    }
+
+    //gives the inode a new owner and group, which is a status change
+    pub fn set_ownership(&mut self, uid: u32, gid: u32) {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => {f.uid = uid; f.gid = gid;}
+            Inode::CharDev(f) => {f.uid = uid; f.gid = gid;}
+            Inode::Socket(f) => {f.uid = uid; f.gid = gid;}
+            Inode::Dir(f) => {f.uid = uid; f.gid = gid;}
+            Inode::Symlink(f) => {f.uid = uid; f.gid = gid;}
+        }
+        self.mark_changed();
+    }
+
+    //how many directory entries refer to the inode, counting . and .. for a directory
+    pub fn linkcount_mut(&mut self) -> &mut u32 {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => &mut f.linkcount,
+            Inode::CharDev(f) => &mut f.linkcount,
+            Inode::Socket(f) => &mut f.linkcount,
+            Inode::Dir(f) => &mut f.linkcount,
+            Inode::Symlink(f) => &mut f.linkcount,
+        }
+    }
+
+    //how many open descriptors refer to the inode
+    pub fn refcount(&self) -> u32 {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => f.refcount,
+            Inode::CharDev(f) => f.refcount,
+            Inode::Socket(f) => f.refcount,
+            Inode::Dir(f) => f.refcount,
+            Inode::Symlink(f) => f.refcount,
+        }
+    }
+
+    //replaces the permission, setuid, setgid, and sticky bits of the inode's mode with those in mode, which is a status change
+    pub fn set_permissions(&mut self, mode: u32) {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => {f.mode = (f.mode & !S_IALLUGO) | mode;}
+            Inode::CharDev(f) => {f.mode = (f.mode & !S_IALLUGO) | mode;}
+            Inode::Socket(f) => {f.mode = (f.mode & !S_IALLUGO) | mode;}
+            Inode::Dir(f) => {f.mode = (f.mode & !S_IALLUGO) | mode;}
+            Inode::Symlink(f) => {f.mode = (f.mode & !S_IALLUGO) | mode;}
+        }
+        self.mark_changed();
+    }
+
+    //the access, modification, and status change times of the inode, in nanoseconds since the epoch
+    pub fn times(&self) -> (u64, u64, u64) {
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => (f.atime, f.mtime, f.ctime),
+            Inode::CharDev(f) => (f.atime, f.mtime, f.ctime),
+            Inode::Socket(f) => (f.atime, f.mtime, f.ctime),
+            Inode::Dir(f) => (f.atime, f.mtime, f.ctime),
+            Inode::Symlink(f) => (f.atime, f.mtime, f.ctime),
+        }
+    }
+
+    //sets the access and modification times which are given, which like any change to the inode is a status change
+    pub fn set_times(&mut self, atime: Option<u64>, mtime: Option<u64>) {
+        self.update_times(atime, mtime, interface::timestamp());
+    }
+
+    fn update_times(&mut self, atime: Option<u64>, mtime: Option<u64>, ctime: u64) {
+        let (a, m, c) = match self {
+            Inode::File(f) | Inode::Fifo(f) => (&mut f.atime, &mut f.mtime, &mut f.ctime),
+            Inode::CharDev(f) => (&mut f.atime, &mut f.mtime, &mut f.ctime),
+            Inode::Socket(f) => (&mut f.atime, &mut f.mtime, &mut f.ctime),
+            Inode::Dir(f) => (&mut f.atime, &mut f.mtime, &mut f.ctime),
+            Inode::Symlink(f) => (&mut f.atime, &mut f.mtime, &mut f.ctime),
+        };
+        if let Some(atime) = atime {*a = atime;}
+        if let Some(mtime) = mtime {*m = mtime;}
+        *c = ctime;
+    }
+
+    //the contents were read
+    pub fn mark_accessed(&mut self) {
+        let now = interface::timestamp();
+        match self {
+            Inode::File(f) | Inode::Fifo(f) => {f.atime = now;}
+            Inode::CharDev(f) => {f.atime = now;}
+            Inode::Socket(f) => {f.atime = now;}
+            Inode::Dir(f) => {f.atime = now;}
+            Inode::Symlink(f) => {f.atime = now;}
+        }
+    }
+
+    //the contents, or the entries of a directory, were changed
+    pub fn mark_modified(&mut self) {
+        let now = interface::timestamp();
+        self.update_times(None, Some(now), now);
+    }
+
+    //something about the inode other than its contents, such as its mode, owner, or link count, was changed
+    pub fn mark_changed(&mut self) {
+        self.set_times(None, None);
+    }
+}
+
+//whether someone with the effective ids of creds may access inode in the ways amode asks for, an or of R_OK, W_OK, and X_OK
+//as on linux, only the owner bits apply to the owner, then only the group bits to the group, and the other bits to everyone else,
+//while root may do anything but execute a file which nobody may execute
+pub fn inode_permits(inode: &Inode, creds: &Credentials, amode: u32) -> bool {
+    let (mode, owner, group) = inode.ownership();
+    if creds.is_privileged() {
+        return amode & X_OK == 0 || is_dir(mode) || mode & (S_IXUSR | S_IXGRP | S_IXOTH) != 0;
+    }
+    let granted = if creds.euid == owner {
+        (mode & S_IRWXU) >> 6
+    } else if creds.in_group(group) {
+        (mode & S_IRWXG) >> 3
+    } else {
+        mode & S_IRWXO
+    };
+    granted & amode == amode
 }
 
 #[derive(interface::SerdeSerialize, interface::SerdeDeserialize, Debug)]
@@ -54,6 +189,37 @@ pub struct DeviceInode {
     pub dev: DevNo,
 }
 
+//the file a unix domain socket is bound to, it has no contents and exists to be found by connect
+#[derive(interface::SerdeSerialize, interface::SerdeDeserialize, Debug)]
+pub struct SocketInode {
+    pub size: usize,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    pub linkcount: u32,
+    #[serde(skip)] //skips serializing and deserializing field, will populate with u32 default of 0 (refcount should not be persisted)
+    pub refcount: u32,
+    pub atime: u64,
+    pub ctime: u64,
+    pub mtime: u64
+}
+
+//a symbolic link, whose target is the path it was made with, which need not exist
+#[derive(interface::SerdeSerialize, interface::SerdeDeserialize, Debug)]
+pub struct SymlinkInode {
+    pub size: usize, //the length of target, as with linux
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    pub linkcount: u32,
+    #[serde(skip)] //skips serializing and deserializing field, will populate with u32 default of 0 (refcount should not be persisted)
+    pub refcount: u32,
+    pub atime: u64,
+    pub ctime: u64,
+    pub mtime: u64,
+    pub target: String,
+}
+
 #[derive(interface::SerdeSerialize, interface::SerdeDeserialize, Debug)]
 pub struct DirectoryInode {
     pub size: usize,
@@ -71,6 +237,8 @@ pub struct DirectoryInode {
 
 #[derive(interface::SerdeSerialize, interface::SerdeDeserialize, Debug)]
 pub struct FilesystemMetadata {
+    #[serde(default)] //metadata persisted before it was versioned has none, and is version 0
+    pub version: u32,
     pub nextinode: usize,
     pub dev_id: u64,
     pub inodetable: interface::RustHashMap<usize, Inode>
@@ -86,7 +254,7 @@ pub fn init_filename_to_inode_dict(curinode: usize, parentinode: usize) -> inter
 impl FilesystemMetadata {
     pub fn blank_fs_init() -> FilesystemMetadata {
         //remove open files?
-        let mut retval = FilesystemMetadata {nextinode: STREAMINODE + 1, dev_id: 20, inodetable: interface::RustHashMap::new()};
+        let mut retval = FilesystemMetadata {version: METADATA_VERSION, nextinode: STREAMINODE + 1, dev_id: 20, inodetable: interface::RustHashMap::new()};
         let time = interface::timestamp(); //We do a real timestamp now
         let dirinode = DirectoryInode {size: 0, uid: DEFAULT_UID, gid: DEFAULT_GID,
         //linkcount is how many entries the directory has (as per linux kernel), . and .. making 2 for the root directory initially,
@@ -107,7 +275,9 @@ pub fn load_fs() {
     let utilcage = Cage{cageid: 0,
         cwd: interface::RustLock::new(interface::RustRfc::new(interface::RustPathBuf::from("/"))),
         parent: 0, 
-        filedescriptortable: interface::RustLock::new(interface::RustHashMap::new())};
+        filedescriptortable: interface::RustLock::new(interface::RustHashMap::new()),
+        creds: interface::RustLock::new(Credentials::new(DEFAULT_UID, DEFAULT_GID)),
+        umask: interface::RustAtomicU32::new(DEFAULT_UMASK)};
 
     let mut mutmetadata = FS_METADATA.write().unwrap();
 
@@ -136,19 +306,19 @@ pub fn load_fs_special_files(utilcage: &Cage) {
         interface::log_to_stderr("making /dev failed. Skipping");
     }
 
-    if utilcage.mknod_syscall("/dev/null", S_IFCHR as u32, makedev(&DevNo {major: 1, minor: 3})) != 0 {
+    if utilcage.mknod_syscall("/dev/null", S_IFCHR as u32 | DEVICE_MODE, makedev(&DevNo {major: 1, minor: 3})) != 0 {
         interface::log_to_stderr("making /dev/null failed. Skipping");
     }
 
-    if utilcage.mknod_syscall("/dev/zero", S_IFCHR as u32, makedev(&DevNo {major: 1, minor: 5})) != 0 {
+    if utilcage.mknod_syscall("/dev/zero", S_IFCHR as u32 | DEVICE_MODE, makedev(&DevNo {major: 1, minor: 5})) != 0 {
         interface::log_to_stderr("making /dev/zero failed. Skipping");
     }
 
-    if utilcage.mknod_syscall("/dev/urandom", S_IFCHR as u32, makedev(&DevNo {major: 1, minor: 9})) != 0 {
+    if utilcage.mknod_syscall("/dev/urandom", S_IFCHR as u32 | DEVICE_MODE, makedev(&DevNo {major: 1, minor: 9})) != 0 {
         interface::log_to_stderr("making /dev/urandom failed. Skipping");
     }
 
-    if utilcage.mknod_syscall("/dev/random", S_IFCHR as u32, makedev(&DevNo {major: 1, minor: 8})) != 0 {
+    if utilcage.mknod_syscall("/dev/random", S_IFCHR as u32 | DEVICE_MODE, makedev(&DevNo {major: 1, minor: 8})) != 0 {
         interface::log_to_stderr("making /dev/random failed. Skipping");
     }
 }
@@ -178,6 +348,19 @@ pub fn restore_metadata(metadata: &mut FilesystemMetadata) {
 
     // Restore metadata
     *metadata = interface::serde_deserialize_from_string(&metadatastring).unwrap();
+    upgrade_metadata(metadata);
+}
+
+// Convert metadata persisted by an older version of lind to the current format
+fn upgrade_metadata(metadata: &mut FilesystemMetadata) {
+    if metadata.version < 1 {
+        //times used to be kept in seconds
+        for inode in metadata.inodetable.values_mut() {
+            let (atime, mtime, ctime) = inode.times();
+            inode.update_times(Some(atime.saturating_mul(1_000_000_000)), Some(mtime.saturating_mul(1_000_000_000)), ctime.saturating_mul(1_000_000_000));
+        }
+    }
+    metadata.version = METADATA_VERSION;
 }
 
 pub fn convpath(cpath: &str) -> interface::RustPathBuf {
@@ -240,6 +423,80 @@ pub fn metawalkandparent(path: &interface::RustPath, guard: Option<&FilesystemMe
 pub fn metawalk(path: &interface::RustPath, guard: Option<&FilesystemMetadata>) -> Option<usize> {
     metawalkandparent(path, guard).0
 }
+
+//follows the symbolic links along a normed path, returning the path they lead to, which has no links along it
+//nor at its end unless follow_last is false, or ELOOP if more than MAXSYMLINKS links are followed, as when they loop
+//every directory looked in must be searchable by the effective ids of creds, or it's EACCES
+//whatever comes after a component which doesn't exist or isn't a directory is left as is, for metawalk not to find
+pub fn resolvepath(path: &interface::RustPath, follow_last: bool, creds: &Credentials, guard: Option<&FilesystemMetadata>) -> Result<interface::RustPathBuf, Errno> {
+    let ourreader;
+    //Acquire a readlock if we were not passed in a reference
+    let md = if let Some(rl) = guard {rl} else {
+        ourreader = FS_METADATA.read().unwrap(); 
+        &ourreader
+    };
+
+    let mut resolved = interface::RustPathBuf::from("/");
+    //the inodes of the directories in resolved, so that a .. in the target of a link can go back up them
+    let mut dirinodes = vec![ROOTDIRECTORYINODE];
+    //the components left to walk, the next one last
+    let mut remaining: Vec<String> = pathcomponents(path).into_iter().rev().collect();
+    let mut walking = true;
+    let mut linksfollowed = 0;
+
+    while let Some(name) = remaining.pop() {
+        if !walking {
+            resolved.push(name);
+            continue;
+        }
+        if name == ".." {
+            if dirinodes.len() > 1 {
+                dirinodes.pop();
+                resolved.pop();
+            }
+            continue;
+        }
+
+        let dirinode = md.inodetable.get(dirinodes.last().unwrap()).unwrap();
+        if !inode_permits(dirinode, creds, X_OK) {return Err(Errno::EACCES);}
+        let child = if let Inode::Dir(d) = dirinode {
+            d.filename_to_inode_dict.get(&name).cloned()
+        } else {None};
+
+        match child.map(|num| (num, md.inodetable.get(&num).unwrap())) {
+            //a link is replaced by its target, which is walked from the root if absolute or from where the link is if not
+            Some((_, Inode::Symlink(link))) if follow_last || !remaining.is_empty() => {
+                linksfollowed += 1;
+                if linksfollowed > MAXSYMLINKS {return Err(Errno::ELOOP);}
+                let target = interface::RustPath::new(&link.target);
+                if target.is_absolute() {
+                    resolved = interface::RustPathBuf::from("/");
+                    dirinodes.truncate(1);
+                }
+                remaining.extend(pathcomponents(target).into_iter().rev());
+            }
+            Some((num, Inode::Dir(_))) => {
+                resolved.push(name);
+                dirinodes.push(num);
+            }
+            _ => {
+                resolved.push(name);
+                walking = false;
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+//the names and ..s of a path, in order
+fn pathcomponents(path: &interface::RustPath) -> Vec<String> {
+    path.components().filter_map(|comp| match comp {
+        interface::RustPathComponent::Normal(f) => Some(f.to_str().unwrap().to_string()),
+        interface::RustPathComponent::ParentDir => Some("..".to_string()),
+        _ => None,
+    }).collect()
+}
+
 pub fn normpath(origp: interface::RustPathBuf, cage: &Cage) -> interface::RustPathBuf {
     //If path is relative, prefix it with the current working directory, otherwise populate it with rootdir
     let mut newp = if origp.is_relative() {(**cage.cwd.read().unwrap()).clone()} else {interface::RustPathBuf::from("/")};
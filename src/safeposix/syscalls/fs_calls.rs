@@ -8,19 +8,65 @@ use super::fs_constants::*;
 
 impl Cage {
 
+    //normalizes path and follows the symbolic links along it, and the one at its end too if follow_last is set,
+    //giving the error for syscallname if they can't be followed or a directory along the way can't be searched
+    pub fn _resolve_path(&self, path: &str, follow_last: bool, metadata: Option<&FilesystemMetadata>, syscallname: &str) -> Result<interface::RustPathBuf, i32> {
+        let creds = self.creds.read().unwrap().clone();
+        self._resolve_path_as(path, follow_last, &creds, metadata, syscallname)
+    }
+
+    //path resolution on behalf of particular credentials, for access which goes by the real ids
+    fn _resolve_path_as(&self, path: &str, follow_last: bool, creds: &Credentials, metadata: Option<&FilesystemMetadata>, syscallname: &str) -> Result<interface::RustPathBuf, i32> {
+        match resolvepath(&normpath(convpath(path), self), follow_last, creds, metadata) {
+            Ok(truepath) => Ok(truepath),
+            Err(Errno::ELOOP) => Err(syscall_error(Errno::ELOOP, syscallname, "too many levels of symbolic links")),
+            Err(errno) => Err(syscall_error(errno, syscallname, "search permission is denied for a directory in the path")),
+        }
+    }
+
+    //whether this cage may access inode in the ways amode asks for, an or of R_OK, W_OK, and X_OK, going by its effective ids
+    pub fn _permits(&self, inode: &Inode, amode: u32) -> bool {
+        inode_permits(inode, &*self.creds.read().unwrap(), amode)
+    }
+
+    //whether this cage may remove or rename the entry for inode in the directory dir,
+    //which if dir is sticky only its owner, the owner of inode, or root may
+    pub fn _sticky_permits(&self, dir: &Inode, inode: &Inode) -> bool {
+        let (dirmode, dirowner, _) = dir.ownership();
+        if dirmode & S_ISVTX == 0 {return true;}
+        let creds = self.creds.read().unwrap();
+        creds.is_privileged() || creds.euid == dirowner || creds.euid == inode.ownership().1
+    }
+
+    //the group a new file in the directory parent belongs to, which like linux is the directory's own if it's setgid
+    pub fn _new_inode_gid(&self, parent: &Inode) -> u32 {
+        let (parentmode, _, parentgroup) = parent.ownership();
+        if parentmode & S_ISGID != 0 {parentgroup} else {self.getegid_syscall() as u32}
+    }
+
+    //the mode a new file asked to have mode actually gets, with the bits in the umask cleared
+    pub fn _apply_umask(&self, mode: u32) -> u32 {
+        mode & !self.umask.load(interface::RustAtomicOrdering::Relaxed)
+    }
+
     //------------------------------------OPEN SYSCALL------------------------------------
 
     pub fn open_syscall(&self, path: &str, flags: i32, mode: u32) -> i32 {
         //Check that path is not empty
         if path.len() == 0 {return syscall_error(Errno::ENOENT, "open", "given path was null");}
 
-        let truepath = normpath(convpath(path), self);
-
         //file descriptor table write lock held for the whole function to prevent TOCTTOU
         let mut fdtable = self.filedescriptortable.write().unwrap();
         //file system metadata table write lock held for the whole function to prevent TOCTTOU
         let mut mutmetadata = FS_METADATA.write().unwrap();
 
+        //like linux, a link at the end of the path isn't followed given O_NOFOLLOW, or O_CREAT and O_EXCL
+        let follow_last = flags & O_NOFOLLOW == 0 && flags & (O_CREAT | O_EXCL) != (O_CREAT | O_EXCL);
+        let truepath = match self._resolve_path(path, follow_last, Some(&mutmetadata), "open") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
         let thisfd = if let Some(fd) = self.get_next_fd(None, Some(&fdtable)) {
             fd
         } else {
@@ -45,19 +91,24 @@ impl Cage {
 
                 let filename = truepath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
 
+                if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                    return syscall_error(Errno::EACCES, "open", "write permission is denied for the directory the file would be created in");
+                }
+
                 if S_IFCHR == (S_IFCHR & flags) {
                     return syscall_error(Errno::EINVAL, "open", "Invalid value in flags");
                 } 
 
-                let effective_mode = S_IFREG as u32 | mode;
+                let effective_mode = S_IFREG as u32 | self._apply_umask(mode & S_IALLUGO);
 
-                if mode & (S_IRWXA | S_FILETYPEFLAGS as u32) != mode {
+                if mode & (S_IALLUGO | S_FILETYPEFLAGS as u32) != mode {
                     return syscall_error(Errno::EPERM, "open", "Mode bits were not sane");
-                } //assert sane mode bits
+                } //assert sane mode bits, which like for chmod include the setuid, setgid, and sticky bits
 
                 let time = interface::timestamp(); //We do a real timestamp now
+                let newgid = self._new_inode_gid(mutmetadata.inodetable.get(&pardirinode).unwrap());
                 let newinode = Inode::File(GenericInode {
-                    size: 0, uid: DEFAULT_UID, gid: DEFAULT_GID,
+                    size: 0, uid: self.geteuid_syscall() as u32, gid: newgid,
                     mode: effective_mode, linkcount: 1, refcount: 0,
                     atime: time, ctime: time, mtime: time,
                 });
@@ -68,6 +119,7 @@ impl Cage {
                     ind.filename_to_inode_dict.insert(filename, newinodenum);
                     ind.linkcount += 1;
                 } //insert a reference to the file in the parent directory
+                mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
                 mutmetadata.inodetable.insert(newinodenum, newinode);
                 persist_metadata(&mutmetadata);
             }
@@ -78,7 +130,22 @@ impl Cage {
                     return syscall_error(Errno::EEXIST, "open", "file already exists and O_CREAT and O_EXCL were used");
                 }
 
-                if O_TRUNC == (flags & O_TRUNC) {
+                //an existing file must allow the access it's opened for, and a directory can't be opened for writing
+                let inodeobj = mutmetadata.inodetable.get(&inodenum).unwrap();
+                let writing = !is_rdonly(flags) || flags & O_TRUNC != 0;
+                let mut amode = 0;
+                if !is_wronly(flags) {amode |= R_OK;}
+                if writing {amode |= W_OK;}
+                if let Inode::Dir(_) = inodeobj {
+                    if writing {return syscall_error(Errno::EISDIR, "open", "a directory cannot be opened for writing");}
+                }
+                if !self._permits(inodeobj, amode) {
+                    return syscall_error(Errno::EACCES, "open", "the file does not allow the access it was opened for");
+                }
+
+                //like linux, truncating a fifo is silently skipped
+                let isfifo = if let Inode::Fifo(_) = mutmetadata.inodetable.get(&inodenum).unwrap() {true} else {false};
+                if O_TRUNC == (flags & O_TRUNC) && !isfifo {
                     //close the file object if another cage has it open
                     let mut fobjtable = FILEOBJECTTABLE.write().unwrap();
                     if fobjtable.contains_key(&inodenum) {
@@ -92,6 +159,7 @@ impl Cage {
                             return syscall_error(Errno::EINVAL, "open", "file is not a normal file and thus cannot be truncated");
                         }
                     }
+                    mutmetadata.inodetable.get_mut(&inodenum).unwrap().mark_modified();
 
                     //remove the previous file and add a new one of 0 length
                     fobjtable.remove(&inodenum); //remove bookkeeping so it'll get re-created if it already is opened
@@ -103,6 +171,22 @@ impl Cage {
 
         //We redo our metawalk in case of O_CREAT, but this is somewhat inefficient
         if let Some(inodenum) = metawalk(truepath.as_path(), Some(&mutmetadata)) {
+            //a fifo is opened as one or both ends of the pipe behind it
+            if let Inode::Fifo(_) = mutmetadata.inodetable.get(&inodenum).unwrap() {
+                let (pipe, seen) = match self._open_fifo(thisfd, inodenum, flags, &mut fdtable, &mut mutmetadata) {
+                    Ok(opened) => opened,
+                    Err(e) => {return e;}
+                };
+                drop(mutmetadata);
+                drop(fdtable);
+
+                //like linux, opening one end of a fifo waits for the other end to be opened, unless it's nonblocking
+                if flags & O_NONBLOCK == 0 {
+                    pipe.wait_for_peer(flags, seen);
+                }
+                return thisfd;
+            }
+
             let inodeobj = mutmetadata.inodetable.get_mut(&inodenum).unwrap();
             let mode;
             let size;
@@ -112,6 +196,9 @@ impl Cage {
                 Inode::File(f) => {size = f.size; mode = f.mode; f.refcount += 1;}
                 Inode::Dir(f) => {size = f.size; mode = f.mode; f.refcount += 1;}
                 Inode::CharDev(f) => {size = f.size; mode = f.mode; f.refcount += 1;}
+                Inode::Socket(_) => {return syscall_error(Errno::ENXIO, "open", "file is a socket, which cannot be opened");}
+                Inode::Fifo(_) => {unreachable!("fifos are opened above");}
+                Inode::Symlink(_) => {return syscall_error(Errno::ELOOP, "open", "path is a symbolic link and O_NOFOLLOW was given");}
             }
 
             //If the file is a regular file, open the file object
@@ -126,22 +213,65 @@ impl Cage {
             //insert file descriptor into fdtableable of the cage
             let position = if 0 != flags & O_APPEND {size} else {0};
             let newfd = File(FileDesc {position: position, inode: inodenum, flags: flags & O_RDWRFLAGS, advlock: interface::AdvisoryLock::new()});
-            let wrappedfd = interface::RustRfc::new(interface::RustLock::new(newfd));
-            fdtable.insert(thisfd, wrappedfd);
+            fdtable.insert(thisfd, FdEntry::new(newfd, flags & O_CLOEXEC != 0));
         } else {panic!("Inode not created for some reason");}
         thisfd //open returns the opened file descriptr
     }
 
+    //sets up an fd for the fifo at inodenum as a pipe descriptor, creating the pipe behind the fifo if it isn't open yet
+    //returns the pipe along with what to pass to its wait_for_peer
+    fn _open_fifo(&self, fd: i32, inodenum: usize, flags: i32, fdtable: &mut FdTable, mutmetadata: &mut FilesystemMetadata) -> Result<(interface::RustRfc<interface::EmulatedPipe>, u32), i32> {
+        let mut fifotable = FIFO_TABLE.write().unwrap();
+
+        let existing = fifotable.get(&inodenum).cloned();
+
+        //a nonblocking open for writing fails rather than waits when there are no readers
+        if (flags & O_RDWRFLAGS) == O_WRONLY && flags & O_NONBLOCK != 0 {
+            let readers = existing.map_or(0, |pipenumber| PIPE_TABLE.read().unwrap().get(&pipenumber).unwrap().get_read_ref());
+            if readers == 0 {
+                return Err(syscall_error(Errno::ENXIO, "open", "fifo opened for writing without blocking has no readers"));
+            }
+        }
+
+        let pipenumber = match existing {
+            Some(pipenumber) => pipenumber,
+            None => {
+                let pipenumber = if let Some(pipeno) = get_next_pipe() {
+                    pipeno
+                } else {
+                    return Err(syscall_error(Errno::ENFILE, "open", "no available pipe number could be found"));
+                };
+                PIPE_TABLE.write().unwrap().insert(pipenumber, interface::RustRfc::new(interface::new_unopened_pipe(PIPE_CAPACITY)));
+                fifotable.insert(inodenum, pipenumber);
+                pipenumber
+            }
+        };
+
+        let pipe = PIPE_TABLE.read().unwrap().get(&pipenumber).unwrap().clone();
+        let seen = pipe.peer_opens(flags);
+        pipe.incr_ref(flags);
+        if let Inode::Fifo(ref mut fifo_inode_obj) = mutmetadata.inodetable.get_mut(&inodenum).unwrap() {
+            fifo_inode_obj.refcount += 1;
+        }
+
+        let newfd = Pipe(PipeDesc {pipe: pipenumber, inode: Some(inodenum), flags: flags & (O_RDWRFLAGS | O_NONBLOCK), advlock: interface::AdvisoryLock::new()});
+        fdtable.insert(fd, FdEntry::new(newfd, flags & O_CLOEXEC != 0));
+        Ok((pipe, seen))
+    }
+
     //------------------MKDIR SYSCALL------------------
 
     pub fn mkdir_syscall(&self, path: &str, mode: u32) -> i32 {
         //Check that path is not empty
         if path.len() == 0 {return syscall_error(Errno::ENOENT, "mkdir", "given path was null");}
 
-        let truepath = normpath(convpath(path), self);
-
         let mut mutmetadata = FS_METADATA.write().unwrap();
 
+        let truepath = match self._resolve_path(path, false, Some(&mutmetadata), "mkdir") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
         match metawalkandparent(truepath.as_path(), Some(&mutmetadata)) {
             //If neither the file nor parent exists
             (None, None) => {
@@ -152,19 +282,29 @@ impl Cage {
             (None, Some(pardirinode)) => {
                 let filename = truepath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
 
-                let effective_mode = S_IFDIR as u32 | mode;
+                if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                    return syscall_error(Errno::EACCES, "mkdir", "write permission is denied for the parent directory");
+                }
+
+                //as on linux, setuid and setgid asked for by mkdir are ignored, but subdirectories of a setgid
+                //directory are setgid themselves, so that its group carries down
+                let mut effective_mode = S_IFDIR as u32 | self._apply_umask(mode & (S_IRWXA | S_ISVTX));
+                if mutmetadata.inodetable.get(&pardirinode).unwrap().ownership().0 & S_ISGID != 0 {
+                    effective_mode |= S_ISGID;
+                }
 
                 //assert sane mode bits
-                if mode & (S_IRWXA | S_FILETYPEFLAGS as u32) != mode {
+                if mode & (S_IALLUGO | S_FILETYPEFLAGS as u32) != mode {
                     return syscall_error(Errno::EPERM, "mkdir", "Mode bits were not sane");
                 }
 
                 let newinodenum = mutmetadata.nextinode;
                 mutmetadata.nextinode += 1;
                 let time = interface::timestamp(); //We do a real timestamp now
+                let newgid = self._new_inode_gid(mutmetadata.inodetable.get(&pardirinode).unwrap());
 
                 let newinode = Inode::Dir(DirectoryInode {
-                    size: 0, uid: DEFAULT_UID, gid: DEFAULT_GID,
+                    size: 0, uid: self.geteuid_syscall() as u32, gid: newgid,
                     mode: effective_mode, linkcount: 3, refcount: 0, //2 because ., and .., as well as reference in parent directory
                     atime: time, ctime: time, mtime: time, 
                     filename_to_inode_dict: init_filename_to_inode_dict(newinodenum, pardirinode)
@@ -175,6 +315,7 @@ impl Cage {
                     parentdir.linkcount += 1;
                 } //insert a reference to the file in the parent directory
                 else {unreachable!();}
+                mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
                 mutmetadata.inodetable.insert(newinodenum, newinode);
 
                 persist_metadata(&mutmetadata);
@@ -193,10 +334,13 @@ impl Cage {
         //Check that path is not empty
         if path.len() == 0 {return syscall_error(Errno::ENOENT, "mknod", "given path was null");}
 
-        let truepath = normpath(convpath(path), self);
-
         let mut mutmetadata = FS_METADATA.write().unwrap();
 
+        let truepath = match self._resolve_path(path, false, Some(&mutmetadata), "mknod") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
         match metawalkandparent(truepath.as_path(), Some(&mutmetadata)) {
             //If neither the file nor parent exists
             (None, None) => {
@@ -207,19 +351,30 @@ impl Cage {
             (None, Some(pardirinode)) => {
                 let filename = truepath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
 
+                if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                    return syscall_error(Errno::EACCES, "mknod", "write permission is denied for the parent directory");
+                }
+
                 //assert sane mode bits (asserting that the mode bits make sense)
-                if mode & (S_IRWXA | S_FILETYPEFLAGS as u32) != mode {
+                if mode & (S_IALLUGO | S_FILETYPEFLAGS as u32) != mode {
                     return syscall_error(Errno::EPERM, "mknod", "Mode bits were not sane");
                 }
-                if mode as i32 & S_IFCHR == 0 {
-                    return syscall_error(Errno::EINVAL, "mknod", "only character files are supported");
-                }
+                let mode = self._apply_umask(mode);
                 let time = interface::timestamp(); //We do a real timestamp now
-                let newinode = Inode::CharDev(DeviceInode {
-                    size: 0, uid: DEFAULT_UID, gid: DEFAULT_GID,
-                    mode: mode, linkcount: 1, refcount: 0,
-                    atime: time, ctime: time, mtime: time, dev: devtuple(dev)
-                });
+                let newgid = self._new_inode_gid(mutmetadata.inodetable.get(&pardirinode).unwrap());
+                let newinode = match mode as i32 & S_FILETYPEFLAGS {
+                    S_IFCHR => Inode::CharDev(DeviceInode {
+                        size: 0, uid: self.geteuid_syscall() as u32, gid: newgid,
+                        mode: mode, linkcount: 1, refcount: 0,
+                        atime: time, ctime: time, mtime: time, dev: devtuple(dev)
+                    }),
+                    S_IFIFO => Inode::Fifo(GenericInode {
+                        size: 0, uid: self.geteuid_syscall() as u32, gid: newgid,
+                        mode: mode, linkcount: 1, refcount: 0,
+                        atime: time, ctime: time, mtime: time
+                    }),
+                    _ => {return syscall_error(Errno::EINVAL, "mknod", "only character files and fifos are supported");}
+                };
 
                 let newinodenum = mutmetadata.nextinode;
                 mutmetadata.nextinode += 1;
@@ -227,6 +382,7 @@ impl Cage {
                     parentdir.filename_to_inode_dict.insert(filename, newinodenum);
                     parentdir.linkcount += 1;
                 } //insert a reference to the file in the parent directory
+                mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
                 mutmetadata.inodetable.insert(newinodenum, newinode);
 
                 persist_metadata(&mutmetadata);
@@ -239,63 +395,70 @@ impl Cage {
         }
     }
 
+    //------------------MKFIFO SYSCALL------------------
+
+    pub fn mkfifo_syscall(&self, path: &str, mode: u32) -> i32 {
+        self.mknod_syscall(path, S_IFIFO as u32 | mode, 0)
+    }
+
+    //------------------------------------UMASK SYSCALL------------------------------------
+
+    //sets the permission bits to clear from the modes of files this cage creates, giving back the old mask
+    pub fn umask_syscall(&self, mask: u32) -> i32 {
+        self.umask.swap(mask & S_IRWXA, interface::RustAtomicOrdering::Relaxed) as i32
+    }
+
     //------------------------------------LINK SYSCALL------------------------------------
 
     pub fn link_syscall(&self, oldpath: &str, newpath: &str) -> i32 {
         if oldpath.len() == 0 {return syscall_error(Errno::ENOENT, "link", "given oldpath was null");}
         if newpath.len() == 0 {return syscall_error(Errno::ENOENT, "link", "given newpath was null");}
-        let trueoldpath = normpath(convpath(oldpath), self);
-        let truenewpath = normpath(convpath(newpath), self);
-        let filename = truenewpath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
 
         let mut mutmetadata = FS_METADATA.write().unwrap();
 
+        //like linux, a link at the end of oldpath is itself linked to rather than followed
+        let trueoldpath = match self._resolve_path(oldpath, false, Some(&mutmetadata), "link") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+        let truenewpath = match self._resolve_path(newpath, false, Some(&mutmetadata), "link") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+        let filename = truenewpath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
+
+        if let (None, Some(pardirinode)) = metawalkandparent(truenewpath.as_path(), Some(&mutmetadata)) {
+            if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                return syscall_error(Errno::EACCES, "link", "write permission is denied for the directory newpath would be in");
+            }
+        }
+
         match metawalk(trueoldpath.as_path(), Some(&mutmetadata)) {
             //If neither the file nor parent exists
             None => {
                 syscall_error(Errno::ENOENT, "link", "a directory component in pathname does not exist or is a dangling symbolic link")
             }
             Some(inodenum) => {
-                let inodeobj = mutmetadata.inodetable.get_mut(&inodenum).unwrap();
-
-                match inodeobj {
-                    Inode::File(ref mut normalfile_inode_obj) => {
-                        normalfile_inode_obj.linkcount += 1; //add link to inode
-                        match metawalkandparent(truenewpath.as_path(), Some(&mutmetadata)) {
-                            (None, None) => {syscall_error(Errno::ENOENT, "link", "newpath cannot be created")}
-
-                            (None, Some(pardirinode)) => {
-                                if let Inode::Dir(ind) = mutmetadata.inodetable.get_mut(&pardirinode).unwrap() {
-                                    ind.filename_to_inode_dict.insert(filename, inodenum);
-                                    ind.linkcount += 1;
-                                } //insert a reference to the inode in the parent directory
-                                persist_metadata(&mutmetadata);
-                                0 //link has succeeded
-                            }
-
-                            (Some(_), ..) => {syscall_error(Errno::EEXIST, "link", "newpath already exists")}
-                        }
-                    }
-
-                    Inode::CharDev(ref mut chardev_inode_obj) => {
-                        chardev_inode_obj.linkcount += 1; //add link to inode
-                        match metawalkandparent(truenewpath.as_path(), Some(&mutmetadata)) {
-                            (None, None) => {syscall_error(Errno::ENOENT, "link", "newpath cannot be created")}
-
-                            (None, Some(pardirinode)) => {
-                                if let Inode::Dir(ind) = mutmetadata.inodetable.get_mut(&pardirinode).unwrap() {
-                                    ind.filename_to_inode_dict.insert(filename, inodenum);
-                                    ind.linkcount += 1;
-                                } //insert a reference to the inode in the parent directory
-                                persist_metadata(&mutmetadata);
-                                0 //link has succeeded
-                            }
+                if let Inode::Dir(_) = mutmetadata.inodetable.get(&inodenum).unwrap() {
+                    return syscall_error(Errno::EPERM, "link", "oldpath is a directory");
+                }
 
-                            (Some(_), ..) => {syscall_error(Errno::EEXIST, "link", "newpath already exists")}
-                        }
+                match metawalkandparent(truenewpath.as_path(), Some(&mutmetadata)) {
+                    (None, None) => {syscall_error(Errno::ENOENT, "link", "newpath cannot be created")}
+
+                    (None, Some(pardirinode)) => {
+                        *mutmetadata.inodetable.get_mut(&inodenum).unwrap().linkcount_mut() += 1; //add link to inode
+                        if let Inode::Dir(ind) = mutmetadata.inodetable.get_mut(&pardirinode).unwrap() {
+                            ind.filename_to_inode_dict.insert(filename, inodenum);
+                            ind.linkcount += 1;
+                        } //insert a reference to the inode in the parent directory
+                        mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
+                        mutmetadata.inodetable.get_mut(&inodenum).unwrap().mark_changed();
+                        persist_metadata(&mutmetadata);
+                        0 //link has succeeded
                     }
 
-                    Inode::Dir(_) => {syscall_error(Errno::EPERM, "link", "oldpath is a directory")}
+                    (Some(_), ..) => {syscall_error(Errno::EEXIST, "link", "newpath already exists")}
                 }
             }
         }
@@ -305,10 +468,15 @@ impl Cage {
 
     pub fn unlink_syscall(&self, path: &str) -> i32 {
         if path.len() == 0 {return syscall_error(Errno::ENOENT, "unmknod", "given oldpath was null");}
-        let truepath = normpath(convpath(path), self);
 
         let mut mutmetadata = FS_METADATA.write().unwrap();
 
+        //a link is unlinked itself, not what it points to
+        let truepath = match self._resolve_path(path, false, Some(&mutmetadata), "unlink") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
         match metawalkandparent(truepath.as_path(), Some(&mutmetadata)) {
             //If the file does not exist
             (None, ..) => {
@@ -322,13 +490,21 @@ impl Cage {
 
             //If both the file and the parent directory exists
             (Some(inodenum), Some(parentinodenum)) => {
+                if !self._permits(mutmetadata.inodetable.get(&parentinodenum).unwrap(), W_OK | X_OK) {
+                    return syscall_error(Errno::EACCES, "unlink", "write permission is denied for the parent directory");
+                }
+                if !self._sticky_permits(mutmetadata.inodetable.get(&parentinodenum).unwrap(), mutmetadata.inodetable.get(&inodenum).unwrap()) {
+                    return syscall_error(Errno::EPERM, "unlink", "the parent directory is sticky and the caller owns neither it nor the file");
+                }
+
                 let inodeobj = mutmetadata.inodetable.get_mut(&inodenum).unwrap();
+                if let Inode::Dir(_) = inodeobj {
+                    return syscall_error(Errno::EISDIR, "unlink", "cannot unlink directory");
+                }
 
-                let (currefcount, curlinkcount, has_fobj) = match inodeobj {
-                    Inode::File(f) => {f.linkcount -= 1; (f.refcount, f.linkcount, true)},
-                    Inode::CharDev(f) => {f.linkcount -= 1; (f.refcount, f.linkcount, false)},
-                    Inode::Dir(_) => {return syscall_error(Errno::EISDIR, "unlink", "cannot unlink directory");},
-                }; //count current number of links and references
+                let has_fobj = matches!(inodeobj, Inode::File(_));
+                *inodeobj.linkcount_mut() -= 1;
+                let (currefcount, curlinkcount) = (inodeobj.refcount(), *inodeobj.linkcount_mut()); //count current number of links and references
 
                 let parentinodeobj = mutmetadata.inodetable.get_mut(&parentinodenum).unwrap();
                 let directory_parent_inode_obj = if let Inode::Dir(x) = parentinodeobj {x} else {
@@ -337,6 +513,8 @@ impl Cage {
                 directory_parent_inode_obj.filename_to_inode_dict.remove(&truepath.file_name().unwrap().to_str().unwrap().to_string()); //for now we assume this is sane, but maybe this should be checked later
                 directory_parent_inode_obj.linkcount -= 1;
                 //remove reference to file in parent directory
+                parentinodeobj.mark_modified();
+                mutmetadata.inodetable.get_mut(&inodenum).unwrap().mark_changed();
 
                 if curlinkcount == 0 {
                     if currefcount == 0  {
@@ -358,6 +536,86 @@ impl Cage {
         }
     }
 
+    //------------------------------------SYMLINK SYSCALL------------------------------------
+
+    pub fn symlink_syscall(&self, target: &str, linkpath: &str) -> i32 {
+        if target.len() == 0 {return syscall_error(Errno::ENOENT, "symlink", "given target was null");}
+        if linkpath.len() == 0 {return syscall_error(Errno::ENOENT, "symlink", "given linkpath was null");}
+
+        let mut mutmetadata = FS_METADATA.write().unwrap();
+
+        let truepath = match self._resolve_path(linkpath, false, Some(&mutmetadata), "symlink") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
+        match metawalkandparent(truepath.as_path(), Some(&mutmetadata)) {
+            //If neither the file nor parent exists
+            (None, None) => {
+                syscall_error(Errno::ENOENT, "symlink", "a directory component in linkpath does not exist or is a dangling symbolic link")
+            }
+
+            //If the file doesn't exist but the parent does
+            (None, Some(pardirinode)) => {
+                let filename = truepath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
+
+                if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                    return syscall_error(Errno::EACCES, "symlink", "write permission is denied for the parent directory");
+                }
+
+                //the target is kept as given, and only looked up when the link is followed
+                let time = interface::timestamp(); //We do a real timestamp now
+                let newgid = self._new_inode_gid(mutmetadata.inodetable.get(&pardirinode).unwrap());
+                let newinode = Inode::Symlink(SymlinkInode {
+                    size: target.len(), uid: self.geteuid_syscall() as u32, gid: newgid,
+                    mode: S_IFLNK as u32 | S_IRWXA, linkcount: 1, refcount: 0,
+                    atime: time, ctime: time, mtime: time,
+                    target: target.to_string(),
+                });
+
+                let newinodenum = mutmetadata.nextinode;
+                mutmetadata.nextinode += 1;
+                if let Inode::Dir(parentdir) = mutmetadata.inodetable.get_mut(&pardirinode).unwrap() {
+                    parentdir.filename_to_inode_dict.insert(filename, newinodenum);
+                    parentdir.linkcount += 1;
+                } //insert a reference to the link in the parent directory
+                mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
+                mutmetadata.inodetable.insert(newinodenum, newinode);
+
+                persist_metadata(&mutmetadata);
+                0 //symlink has succeeded
+            }
+
+            (Some(_), ..) => {
+                syscall_error(Errno::EEXIST, "symlink", "linkpath already exists")
+            }
+        }
+    }
+
+    //------------------------------------READLINK SYSCALL------------------------------------
+
+    //writes the target of the link at path into buf, truncated to bufsize and without a null terminator, as with linux
+    pub fn readlink_syscall(&self, path: &str, buf: *mut u8, bufsize: usize) -> i32 {
+        if path.len() == 0 {return syscall_error(Errno::ENOENT, "readlink", "given path was null");}
+        if bufsize == 0 {return syscall_error(Errno::EINVAL, "readlink", "bufsize is not positive");}
+
+        let metadata = FS_METADATA.read().unwrap();
+        let truepath = match self._resolve_path(path, false, Some(&metadata), "readlink") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
+        if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
+            if let Inode::Symlink(link) = metadata.inodetable.get(&inodenum).unwrap() {
+                interface::fillfromslice(buf, bufsize, link.target.as_bytes())
+            } else {
+                syscall_error(Errno::EINVAL, "readlink", "path is not a symbolic link")
+            }
+        } else {
+            syscall_error(Errno::ENOENT, "readlink", "path does not exist")
+        }
+    }
+
     //------------------------------------CREAT SYSCALL------------------------------------
     
     pub fn creat_syscall(&self, path: &str, mode: u32) -> i32 {
@@ -367,8 +625,22 @@ impl Cage {
     //------------------------------------STAT SYSCALL------------------------------------
 
     pub fn stat_syscall(&self, path: &str, statbuf: &mut StatData) -> i32 {
-        let truepath = normpath(convpath(path), self);
+        self._stat_path_helper(path, statbuf, true, "stat")
+    }
+
+    //------------------------------------LSTAT SYSCALL------------------------------------
+
+    //like stat, except that a link at the end of path is described itself rather than what it points to
+    pub fn lstat_syscall(&self, path: &str, statbuf: &mut StatData) -> i32 {
+        self._stat_path_helper(path, statbuf, false, "lstat")
+    }
+
+    fn _stat_path_helper(&self, path: &str, statbuf: &mut StatData, follow_last: bool, syscallname: &str) -> i32 {
         let metadata = FS_METADATA.read().unwrap();
+        let truepath = match self._resolve_path(path, follow_last, Some(&metadata), syscallname) {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         //Walk the file tree to get inode from path
         if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
@@ -380,19 +652,25 @@ impl Cage {
 
             //delegate the rest of populating statbuf to the relevant helper
             match inodeobj {
-                Inode::File(f) => {
+                Inode::File(f) | Inode::Fifo(f) => {
                     Self::_istat_helper(f, statbuf);
                 },
                 Inode::CharDev(f) => {
                     Self::_istat_helper_chr_file(f, statbuf);
                 },
+                Inode::Socket(f) => {
+                    Self::_istat_helper_sock(f, statbuf);
+                },
                 Inode::Dir(f) => {
                     Self::_istat_helper_dir(f, statbuf);
                 },
+                Inode::Symlink(f) => {
+                    Self::_istat_helper_symlink(f, statbuf);
+                },
             }
             0 //stat has succeeded!
         } else {
-            syscall_error(Errno::ENOENT, "stat", "path refers to an invalid file")
+            syscall_error(Errno::ENOENT, syscallname, "path refers to an invalid file")
         }
     }
 
@@ -403,6 +681,9 @@ impl Cage {
         statbuf.st_gid = inodeobj.gid;
         statbuf.st_rdev = 0;
         statbuf.st_size = inodeobj.size;
+        statbuf.st_atim = interface::timestamp_to_timespec(inodeobj.atime);
+        statbuf.st_mtim = interface::timestamp_to_timespec(inodeobj.mtime);
+        statbuf.st_ctim = interface::timestamp_to_timespec(inodeobj.ctime);
         statbuf.st_blksize = 0;
         statbuf.st_blocks = 0;
     }
@@ -414,6 +695,9 @@ impl Cage {
         statbuf.st_gid = inodeobj.gid;
         statbuf.st_rdev = 0;
         statbuf.st_size = inodeobj.size;
+        statbuf.st_atim = interface::timestamp_to_timespec(inodeobj.atime);
+        statbuf.st_mtim = interface::timestamp_to_timespec(inodeobj.mtime);
+        statbuf.st_ctim = interface::timestamp_to_timespec(inodeobj.ctime);
         statbuf.st_blksize = 0;
         statbuf.st_blocks = 0;
     }
@@ -427,6 +711,35 @@ impl Cage {
         //compose device number into u64
         statbuf.st_rdev = makedev(&inodeobj.dev);
         statbuf.st_size = inodeobj.size;
+        statbuf.st_atim = interface::timestamp_to_timespec(inodeobj.atime);
+        statbuf.st_mtim = interface::timestamp_to_timespec(inodeobj.mtime);
+        statbuf.st_ctim = interface::timestamp_to_timespec(inodeobj.ctime);
+    }
+
+    fn _istat_helper_sock(inodeobj: &SocketInode, statbuf: &mut StatData) {
+        statbuf.st_mode = inodeobj.mode;
+        statbuf.st_nlink = inodeobj.linkcount;
+        statbuf.st_uid = inodeobj.uid;
+        statbuf.st_gid = inodeobj.gid;
+        statbuf.st_rdev = 0;
+        statbuf.st_size = inodeobj.size;
+        statbuf.st_atim = interface::timestamp_to_timespec(inodeobj.atime);
+        statbuf.st_mtim = interface::timestamp_to_timespec(inodeobj.mtime);
+        statbuf.st_ctim = interface::timestamp_to_timespec(inodeobj.ctime);
+    }
+
+    fn _istat_helper_symlink(inodeobj: &SymlinkInode, statbuf: &mut StatData) {
+        statbuf.st_mode = inodeobj.mode;
+        statbuf.st_nlink = inodeobj.linkcount;
+        statbuf.st_uid = inodeobj.uid;
+        statbuf.st_gid = inodeobj.gid;
+        statbuf.st_rdev = 0;
+        statbuf.st_size = inodeobj.size;
+        statbuf.st_atim = interface::timestamp_to_timespec(inodeobj.atime);
+        statbuf.st_mtim = interface::timestamp_to_timespec(inodeobj.mtime);
+        statbuf.st_ctim = interface::timestamp_to_timespec(inodeobj.ctime);
+        statbuf.st_blksize = 0;
+        statbuf.st_blocks = 0;
     }
 
     //Streams and pipes don't have associated inodes so we populate them from mostly dummy information
@@ -435,8 +748,8 @@ impl Cage {
         statbuf.st_ino = inodenum;
         statbuf.st_mode = 49590; //r and w priveliged 
         statbuf.st_nlink = 1;
-        statbuf.st_uid = DEFAULT_UID;
-        statbuf.st_gid = DEFAULT_GID;
+        statbuf.st_uid = self.geteuid_syscall() as u32;
+        statbuf.st_gid = self.getegid_syscall() as u32;
         statbuf.st_rdev = 0;
         statbuf.st_size = 0;
         statbuf.st_blksize = 0;
@@ -466,22 +779,37 @@ impl Cage {
                     statbuf.st_dev = metadata.dev_id;
 
                     match inode {
-                        Inode::File(f) => {
+                        Inode::File(f) | Inode::Fifo(f) => {
                             Self::_istat_helper(&f, statbuf);
                         }
                         Inode::CharDev(f) => {
                             Self::_istat_helper_chr_file(&f, statbuf);
                         }
+                        Inode::Socket(f) => {
+                            Self::_istat_helper_sock(&f, statbuf);
+                        }
                         Inode::Dir(f) => {
                             Self::_istat_helper_dir(&f, statbuf);
                         }
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened");}
                     }
                 }
                 Socket(_) => {
                     return syscall_error(Errno::EOPNOTSUPP, "fstat", "we don't support fstat on sockets yet");
                 }
                 Stream(_) => {self._stat_alt_helper(statbuf, STREAMINODE, &metadata);}
-                Pipe(_) => {self._stat_alt_helper(statbuf, 0xfeef0000, &metadata);}
+                Pipe(pipe_filedesc_obj) => {
+                    //the ends of a fifo are described by the fifo itself
+                    if let Some(inodenum) = pipe_filedesc_obj.inode {
+                        if let Inode::Fifo(f) = metadata.inodetable.get(&inodenum).unwrap() {
+                            statbuf.st_ino = inodenum;
+                            statbuf.st_dev = metadata.dev_id;
+                            Self::_istat_helper(&f, statbuf);
+                        }
+                    } else {
+                        self._stat_alt_helper(statbuf, 0xfeef0000, &metadata);
+                    }
+                }
                 Epoll(_) => {self._stat_alt_helper(statbuf, 0xfeef0000, &metadata);}
             }
             0 //fstat has succeeded!
@@ -493,8 +821,11 @@ impl Cage {
     //------------------------------------STATFS SYSCALL------------------------------------
 
     pub fn statfs_syscall(&self, path: &str, databuf: &mut FSData) -> i32 {
-        let truepath = normpath(convpath(path), self);
         let metadata = FS_METADATA.read().unwrap();
+        let truepath = match self._resolve_path(path, true, Some(&metadata), "statfs") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         //Walk the file tree to get inode from path
         if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
@@ -553,9 +884,54 @@ impl Cage {
     //------------------------------------READ SYSCALL------------------------------------
 
     pub fn read_syscall(&self, fd: i32, buf: *mut u8, count: usize) -> i32 {
-        let fdtable = self.filedescriptortable.read().unwrap();
-        
-        if let Some(wrappedfd) = fdtable.get(&fd) {
+        //reading a pipe, socket, or stdin may block, so we hold neither the fdtable lock nor any lock on the descriptor
+        //meanwhile, letting other threads and cages sharing the descriptor use, change, and close it
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            //a socket is read from as by recv, which lets go of the descriptor itself before waiting
+            if matches!(&*wrappedfd.read().unwrap(), Socket(_)) {
+                return self._recv_helper(&wrappedfd, buf, count, 0, &mut None, "read");
+            }
+
+            //for a pipe or host stdin, what we need is copied out of the descriptor so we can let go of it before waiting
+            let waiton = {
+                let filedesc_enum = wrappedfd.read().unwrap();
+                match &*filedesc_enum {
+                    Stream(stream_filedesc_obj) => {
+                        if stream_filedesc_obj.stream != 0 {
+                            return syscall_error(Errno::EBADF, "read", "specified stream not open for reading");
+                        }
+                        if let Some(buffer) = &stream_filedesc_obj.buffer {
+                            return buffer.read(buf, count) as i32;
+                        }
+                        Some((None, stream_filedesc_obj.flags & O_NONBLOCK != 0))
+                    }
+                    Pipe(pipe_filedesc_obj) => {
+                        if is_wronly(pipe_filedesc_obj.flags) {
+                            return syscall_error(Errno::EBADF, "read", "specified file not open for reading");
+                        }
+                        let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
+                        Some((Some(pipe), pipe_filedesc_obj.flags & O_NONBLOCK != 0))
+                    }
+                    _ => None
+                }
+            };
+
+            if let Some((pipe_option, nonblocking)) = waiton {
+                return match pipe_option {
+                    Some(pipe) => match pipe.read_from_pipe(buf, count, nonblocking) {
+                        Ok(bytesread) => bytesread as i32,
+                        Err(errno) => syscall_error(Errno::from_discriminant(errno), "read", "there is no data available right now, try again later")
+                    },
+                    //reading stdin waits on the host, which happens through the interface
+                    None => match interface::read_stdin(buf, count, nonblocking) {
+                        Ok(bytesread) => bytesread as i32,
+                        Err(errno) => syscall_error(Errno::from_discriminant(errno), "read", "there is no input available right now, try again later")
+                    }
+                };
+            }
+
             let mut filedesc_enum = wrappedfd.write().unwrap();
 
             //delegate to pipe, stream, or socket helper if specified by file descriptor enum type (none of them are implemented yet)
@@ -580,6 +956,9 @@ impl Cage {
                                 //move position forward by the number of bytes we've read
 
                                 normalfile_filedesc_obj.position += bytesread;
+                                drop(fobjtable);
+                                drop(metadata);
+                                self._mark_accessed(normalfile_filedesc_obj.inode);
                                 bytesread as i32
                             } else {
                                0 //0 bytes read, but not an error value that can/should be passed to the user
@@ -593,19 +972,16 @@ impl Cage {
                         Inode::Dir(_) => {
                             syscall_error(Errno::EISDIR, "read", "attempted to read from a directory")
                         }
-                    }
-                }
-                Socket(_) => {syscall_error(Errno::EOPNOTSUPP, "read", "recv not implemented yet")}
-                Stream(_) => {syscall_error(Errno::EOPNOTSUPP, "read", "reading from stdin not implemented yet")}
-                Pipe(pipe_filedesc_obj) => {
-                    if is_wronly(pipe_filedesc_obj.flags) {
-                        return syscall_error(Errno::EBADF, "read", "specified file not open for reading");
-                    }
 
-                    // get the pipe, read from it, and return bytes read
-                    let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
-                    pipe.read_from_pipe(buf, count) as i32
+                        Inode::Socket(_) => {
+                            syscall_error(Errno::ENXIO, "read", "socket files cannot be read from")
+                        }
+
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
+                    }
                 }
+                Socket(_) | Pipe(_) | Stream(_) => {unreachable!("sockets, pipes, and streams are read from above")}
                 Epoll(_) => {syscall_error(Errno::EINVAL, "read", "fd is attached to an object which is unsuitable for reading")}
             }
         } else {
@@ -637,6 +1013,9 @@ impl Cage {
                             let fileobject = fobjtable.get(&normalfile_filedesc_obj.inode).unwrap();
 
                             if let Ok(bytesread) = fileobject.readat(buf, count, offset as usize) {
+                                drop(fobjtable);
+                                drop(metadata);
+                                self._mark_accessed(normalfile_filedesc_obj.inode);
                                 bytesread as i32
                             } else {
                                0 //0 bytes read, but not an error value that can/should be passed to the user
@@ -650,6 +1029,13 @@ impl Cage {
                         Inode::Dir(_) => {
                             syscall_error(Errno::EISDIR, "pread", "attempted to read from a directory")
                         }
+
+                        Inode::Socket(_) => {
+                            syscall_error(Errno::ENXIO, "pread", "socket files cannot be read from")
+                        }
+
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
                     }
                 }
                 Socket(_) => {
@@ -670,6 +1056,13 @@ impl Cage {
         }
     }
 
+    //the access time is set in a short step of its own, so that the metadata is only read locked while reading
+    fn _mark_accessed(&self, inodenum: usize) {
+        if let Some(inodeobj) = FS_METADATA.write().unwrap().inodetable.get_mut(&inodenum) {
+            inodeobj.mark_accessed();
+        }
+    }
+
     fn _read_chr_file(&self, inodeobj: &DeviceInode, buf: *mut u8, count: usize) -> i32 {
         match inodeobj.dev {
             NULLDEVNO => {0} //reading from /dev/null always reads 0 bytes
@@ -683,9 +1076,33 @@ impl Cage {
     //------------------------------------WRITE SYSCALL------------------------------------
 
     pub fn write_syscall(&self, fd: i32, buf: *const u8, count: usize) -> i32 {
-        let fdtable = self.filedescriptortable.read().unwrap();
- 
-        if let Some(wrappedfd) = fdtable.get(&fd) {
+        //writing a pipe or socket may block, so as for reading we hold neither the fdtable lock nor any lock on its descriptor meanwhile
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            {
+                let bindret = self._bind_before_send(&wrappedfd, "write");
+                if bindret != 0 {return bindret;}
+                let filedesc_enum = wrappedfd.read().unwrap();
+                if let Socket(sockfdobj) = &*filedesc_enum {
+                    return self._send_helper(sockfdobj, buf, count, 0, "write");
+                }
+                if let Pipe(pipe_filedesc_obj) = &*filedesc_enum {
+                    if is_rdonly(pipe_filedesc_obj.flags) {
+                        return syscall_error(Errno::EBADF, "write", "specified pipe not open for writing");
+                    }
+                    // get the pipe and let go of the descriptor before writing, which may wait for room
+                    let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
+                    let nonblocking = pipe_filedesc_obj.flags & O_NONBLOCK != 0;
+                    drop(filedesc_enum);
+                    return match pipe.write_to_pipe(buf, count, nonblocking) {
+                        Ok(byteswritten) => byteswritten as i32,
+                        Err(errno) if errno == Errno::EPIPE as i32 => syscall_error(Errno::EPIPE, "write", "the pipe has no readers left"),
+                        Err(errno) => syscall_error(Errno::from_discriminant(errno), "write", "there is no room in the pipe right now, try again later")
+                    };
+                }
+            }
+
             let mut filedesc_enum = wrappedfd.write().unwrap();
 
             //delegate to pipe, stream, or socket helper if specified by file descriptor enum type
@@ -728,6 +1145,9 @@ impl Cage {
                                 //move position forward by the number of bytes we've written
                                 normalfile_filedesc_obj.position = position + byteswritten;
                                 newposition = normalfile_filedesc_obj.position;
+                                let time = interface::timestamp();
+                                normalfile_inode_obj.mtime = time;
+                                normalfile_inode_obj.ctime = time;
                                 if newposition > normalfile_inode_obj.size {
                                     normalfile_inode_obj.size = newposition;
                                     persist_metadata(&metadata);
@@ -746,27 +1166,29 @@ impl Cage {
                         Inode::Dir(_) => {
                             syscall_error(Errno::EISDIR, "write", "attempted to write to a directory")
                         }
+
+                        Inode::Socket(_) => {
+                            syscall_error(Errno::ENXIO, "write", "socket files cannot be written to")
+                        }
+
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
                     }
                 }
-                Socket(_) => {syscall_error(Errno::EOPNOTSUPP, "write", "send not implemented yet")}
+                Socket(_) => {unreachable!("sockets are written to above")}
                 Stream(stream_filedesc_obj) => {
-                    //if it's stdout or stderr, print out and we're done
+                    //if it's stdout or stderr, print out (or capture, if redirected to a buffer) and we're done
                     if stream_filedesc_obj.stream == 1 || stream_filedesc_obj.stream == 2 {
+                        if let Some(buffer) = &stream_filedesc_obj.buffer {
+                            return buffer.write(buf, count) as i32;
+                        }
                         interface::log_from_ptr(buf, count);
                         count as i32
                     } else {
                         return syscall_error(Errno::EBADF, "write", "specified stream not open for writing");
                     }
                 }
-                Pipe(pipe_filedesc_obj) => {
-                    if is_rdonly(pipe_filedesc_obj.flags) {
-                        return syscall_error(Errno::EBADF, "write", "specified pipe not open for writing");
-                    }
-                    // get the pipe, write to it, and return bytes written
-                    let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
-                    pipe.write_to_pipe(buf, count) as i32
-  
-                }
+                Pipe(_) => {unreachable!("pipes are written to above")}
                 Epoll(_) => {syscall_error(Errno::EINVAL, "write", "fd is attached to an object which is unsuitable for writing")}
             }
         } else {
@@ -827,6 +1249,9 @@ impl Cage {
                                   //we still may need to update file size from blank bytes write, so we don't bail out
                             };
 
+                            let time = interface::timestamp();
+                            normalfile_inode_obj.mtime = time;
+                            normalfile_inode_obj.ctime = time;
                             if newposition > filesize {
                                normalfile_inode_obj.size = newposition;
                                persist_metadata(&metadata);
@@ -842,6 +1267,13 @@ impl Cage {
                         Inode::Dir(_) => {
                             syscall_error(Errno::EISDIR, "pwrite", "attempted to write to a directory")
                         }
+
+                        Inode::Socket(_) => {
+                            syscall_error(Errno::ENXIO, "pwrite", "socket files cannot be written to")
+                        }
+
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
                     }
                 }
                 Socket(_) => {
@@ -911,6 +1343,13 @@ impl Cage {
                             0 //for character files, rather than seeking, we transparently do nothing
                         }
 
+                        Inode::Socket(_) => {
+                            syscall_error(Errno::ESPIPE, "lseek", "file descriptor is associated with a socket file, cannot seek")
+                        }
+
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
+
                         Inode::Dir(dir_inode_obj) => {
                             //for directories we seek between entries, and thus our end position is the total number of entries
                             let eventualpos = match whence {
@@ -957,31 +1396,21 @@ impl Cage {
     //------------------------------------ACCESS SYSCALL------------------------------------
 
     pub fn access_syscall(&self, path: &str, amode: u32) -> i32 {
-        let truepath = normpath(convpath(path), self);
+        //like linux, access checks by the real rather than the effective ids
+        let realcreds = self.creds.read().unwrap().as_real();
+
         let metadata = FS_METADATA.read().unwrap();
+        let truepath = match self._resolve_path_as(path, true, &realcreds, Some(&metadata), "access") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         //Walk the file tree to get inode from path
         if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
             let inodeobj = metadata.inodetable.get(&inodenum).unwrap();
 
-            //Get the mode bits if the type of the inode is sane
-            let mode = match inodeobj {
-                Inode::File(f) => {f.mode},
-                Inode::CharDev(f) => {f.mode},
-                Inode::Dir(f) => {f.mode},
-            };
-
-            //We assume that the current user owns the file
-
-            //Construct desired access bits (i.e. 0777) based on the amode parameter
-            let mut newmode: u32 = 0;
-            if amode & X_OK == X_OK {newmode |= S_IXUSR;}
-            if amode & W_OK == W_OK {newmode |= S_IWUSR;}
-            if amode & R_OK == R_OK {newmode |= S_IRUSR;}
-
-            //if the desired access bits are compatible with the actual access bits 
-            //of the file, return a success result, else return a failure result
-            if mode & newmode == newmode {
+            //if the desired access is allowed to the caller return a success result, else return a failure result
+            if inode_permits(inodeobj, &realcreds, amode & (R_OK | W_OK | X_OK)) {
                 0
             } else {
                 syscall_error(Errno::EACCES, "access", "the requested access would be denied to the file")
@@ -994,11 +1423,18 @@ impl Cage {
     //------------------------------------CHDIR SYSCALL------------------------------------
     
     pub fn chdir_syscall(&self, path: &str) -> i32 {
-        let truepath = normpath(convpath(path), self);
         let mut mutmetadata = FS_METADATA.write().unwrap();
+        //the cwd is kept as the path with its links followed, as getcwd would give on linux
+        let truepath = match self._resolve_path(path, true, Some(&mutmetadata), "chdir") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         //Walk the file tree to get inode from path
         if let Some(inodenum) = metawalk(&truepath, Some(&mutmetadata)) {
+            if !self._permits(mutmetadata.inodetable.get(&inodenum).unwrap(), X_OK) {
+                return syscall_error(Errno::EACCES, "chdir", "search permission is denied for the directory");
+            }
             if let Inode::Dir(ref mut dir) = mutmetadata.inodetable.get_mut(&inodenum).unwrap() {
 
                 //increment refcount of new cwd inode to ensure that you can't remove a directory while it is the cwd of a cage
@@ -1066,6 +1502,11 @@ impl Cage {
             return syscall_error(Errno::EBADF, "dup or dup2", "provided file descriptor is out of range");
         }
 
+        //if the file descriptors are equal, return the new one, without taking another reference
+        if newfd == oldfd {
+            return newfd;
+        }
+
         {
             let locked_filedesc = fdtable.get(&oldfd).unwrap();
             let filedesc_enum = locked_filedesc.read().unwrap();
@@ -1087,20 +1528,30 @@ impl Cage {
                         Inode::CharDev(chardev_inode_obj) => {
                             chardev_inode_obj.refcount += 1;
                         },
+                        Inode::Socket(socket_inode_obj) => {
+                            socket_inode_obj.refcount += 1;
+                        },
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes");}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened");}
                     }
                 },
                 Pipe(normalfile_filedesc_obj) => {
                     let pipe = PIPE_TABLE.write().unwrap().get(&normalfile_filedesc_obj.pipe).unwrap().clone();
                     pipe.incr_ref(normalfile_filedesc_obj.flags);
+                    if let Some(inodenum) = normalfile_filedesc_obj.inode {
+                        if let Inode::Fifo(fifo_inode_obj) = mutmetadata.inodetable.get_mut(&inodenum).unwrap() {
+                            fifo_inode_obj.refcount += 1;
+                        }
+                    }
+                },
+                Socket(socket_filedesc_obj) => {
+                    if let Some(sockobjid) = socket_filedesc_obj.socketobjectid {
+                        SOCKET_OBJECT_TABLE.read().unwrap().get(&sockobjid).unwrap().incr_ref();
+                    }
                 },
                 _ => {return syscall_error(Errno::EACCES, "dup or dup2", "can't dup the provided file");},
             }
         }
-        
-        //if the file descriptors are equal, return the new one
-        if newfd == oldfd {
-            return newfd;
-        }
 
         //close the fd in the way of the new fd. If an error is returned from the helper, return the error, else continue to end
         if fdtable.contains_key(&newfd) {
@@ -1109,7 +1560,7 @@ impl Cage {
                 return close_result;
             }
         }    
-        fdtable.insert(newfd, fdtable.get(&oldfd).unwrap().clone());
+        fdtable.insert(newfd, fdtable.get(&oldfd).unwrap().dup());
         return newfd;
     }
 
@@ -1147,26 +1598,40 @@ impl Cage {
                 //if we are a socket, we dont change disk metadata
                 Stream(_) => {},
                 Epoll(_) => {}, //Epoll closing not implemented yet
-                Socket(_) => {
-                    //drop(filedesc_enum);    //to appease Rust ownership, we drop the fdtable borrow before calling cleanup_socket
-                    //drop(locked_filedesc);  
-                    //let retval = Self::_cleanup_socket(self, fd, false, fdtable);
-                    //if retval != 0 {return retval;}
+                Socket(socket_filedesc_obj) => {
+                    if let Some(sockobjid) = socket_filedesc_obj.socketobjectid {
+                        let mut sockobjtable = SOCKET_OBJECT_TABLE.write().unwrap();
+
+                        //dropping the last reference to the socket object closes the host socket
+                        if sockobjtable.get(&sockobjid).unwrap().decr_ref() == 0 {
+                            sockobjtable.remove(&sockobjid);
+                        }
+                    }
                 },
                 Pipe(pipe_filedesc_obj) => {
                     let pipe = PIPE_TABLE.write().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
                
+                    //closing the last write end is what lets readers see end of file, and closing the last
+                    //read end makes writes fail with EPIPE
                     pipe.decr_ref(pipe_filedesc_obj.flags);
 
-                    //Code below needs to reflect addition of pipes
-                    if pipe.get_write_ref() == 0 && pipe_filedesc_obj.flags == O_WRONLY {
-                        // we're closing the last write end, lets set eof
-                        pipe.set_eof();
-                    }
-
                     if pipe.get_write_ref() + pipe.get_read_ref() == 0 {
                         // last reference, lets remove it
                         PIPE_TABLE.write().unwrap().remove(&pipe_filedesc_obj.pipe).unwrap();
+                        if let Some(inodenum) = pipe_filedesc_obj.inode {
+                            FIFO_TABLE.write().unwrap().remove(&inodenum).unwrap();
+                        }
+                    }
+
+                    //a fifo which has been unlinked goes away with its last reference
+                    if let Some(inodenum) = pipe_filedesc_obj.inode {
+                        if let Inode::Fifo(ref mut fifo_inode_obj) = mutmetadata.inodetable.get_mut(&inodenum).unwrap() {
+                            fifo_inode_obj.refcount -= 1;
+                            if fifo_inode_obj.linkcount == 0 && fifo_inode_obj.refcount == 0 {
+                                mutmetadata.inodetable.remove(&inodenum);
+                                persist_metadata(&mutmetadata);
+                            }
+                        }
                     }
 
                 },
@@ -1200,7 +1665,8 @@ impl Cage {
                                 Some(_) => {return syscall_error(Errno::ENOEXEC, "close or dup", "Non-regular file in file object table");},
                                 None => {}
                             }
-                            if dir_inode_obj.linkcount == 2 && dir_inode_obj.refcount == 0 {
+                            //a directory removed or replaced while open has no links left, and goes once the last descriptor does
+                            if dir_inode_obj.linkcount == 0 && dir_inode_obj.refcount == 0 {
                                 //removing the file from the metadata 
                                 mutmetadata.inodetable.remove(&inodenum);
                                 persist_metadata(&mutmetadata);
@@ -1220,6 +1686,17 @@ impl Cage {
                                 persist_metadata(&mutmetadata);
                             } 
                         }
+                        Inode::Socket(ref mut socket_inode_obj) => {
+                            socket_inode_obj.refcount -= 1;
+
+                            if socket_inode_obj.linkcount == 0 && socket_inode_obj.refcount == 0 {
+                                //removing the file from the metadata 
+                                mutmetadata.inodetable.remove(&inodenum);
+                                persist_metadata(&mutmetadata);
+                            } 
+                        }
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes");}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened");}
                     }
                 },
             }
@@ -1233,88 +1710,399 @@ impl Cage {
     //------------------------------------FCNTL SYSCALL------------------------------------
     
     pub fn fcntl_syscall(&self, fd: i32, cmd: i32, arg: i32) -> i32 {
-        let fdtable = self.filedescriptortable.write().unwrap();
+        let wrappedfd = {
+            let mut fdtable = self.filedescriptortable.write().unwrap();
+
+            //close-on-exec belongs to the fdtable entry rather than the descriptor, which dups and forked cages share
+            if let Some(entry) = fdtable.get_mut(&fd) {
+                match (cmd, arg) {
+                    (F_GETFD, ..) => {return if entry.cloexec {FD_CLOEXEC} else {0};}
+                    (F_SETFD, arg) if arg >= 0 => {
+                        entry.cloexec = arg & FD_CLOEXEC != 0;
+                        return 0;
+                    }
+                    //dup'ing here rather than through dup_syscall, which would take the fdtable lock again
+                    (F_DUPFD, arg) if arg >= 0 => {
+                        let nextfd = if let Some(nextfd) = self.get_next_fd(Some(arg), Some(&fdtable)) {nextfd}
+                        else {return syscall_error(Errno::EMFILE, "fcntl", "no available file descriptor number could be found");};
+                        return self._dup2_helper(fd, nextfd, Some(&mut fdtable));
+                    }
+                    _ => {}
+                }
+            }
 
-        if let Some(wrappedfd) = fdtable.get(&fd) {
-            let mut filedesc_enum = wrappedfd.write().unwrap();
+            if let Some(wrappedfd) = fdtable.get(&fd) {
+                wrappedfd.clone()
+            } else {
+                return syscall_error(Errno::EBADF, "fcntl", "Invalid file descriptor");
+            }
+        };
 
-            let flags = match &mut *filedesc_enum {
-                Epoll(obj) => {&mut obj.flags},
-                Pipe(obj) => {&mut obj.flags},
-                Stream(obj) => {&mut obj.flags},
-                Socket(obj) => {&mut obj.flags},
-                File(obj) => {&mut obj.flags},
-            };
-            
-            //matching the tuple
-            match (cmd, arg) {
-                //because the arg parameter is not used in certain commands, it can be anything (..)
-                (F_GETFD, ..) => {
-                    ((*flags & O_CLOEXEC) != 0) as i32
-                }
-                // set the flags but make sure that the flags are valid
-                (F_SETFD, arg) if arg >= 0 => {
-                    *flags |= O_CLOEXEC;
-                    0
-                }
-                (F_GETFL, ..) => {
-                    //for get, we just need to return the flags
-                    *flags
-                }
-                (F_SETFL, arg) if arg >= 0 => {
-                    *flags = arg;
-                    0
-                }
-                (F_DUPFD, arg) if arg >= 0 => {
-                    Self::dup_syscall(self, fd, Some(arg))
+        //the fdtable is let go of before we take the descriptor, so that no one waits on the whole table while we wait for it
+        let mut filedesc_enum = wrappedfd.write().unwrap();
+
+        //the pipe size commands apply to the pipe behind a pipe descriptor rather than the descriptor itself
+        let pipe_option = if let Pipe(obj) = &*filedesc_enum {
+            Some(PIPE_TABLE.read().unwrap().get(&obj.pipe).unwrap().clone())
+        } else {None};
+
+        let flags = match &mut *filedesc_enum {
+            Epoll(obj) => {&mut obj.flags},
+            Pipe(obj) => {&mut obj.flags},
+            Stream(obj) => {&mut obj.flags},
+            Socket(obj) => {&mut obj.flags},
+            File(obj) => {&mut obj.flags},
+        };
+        
+        //matching the tuple
+        match (cmd, arg) {
+            //because the arg parameter is not used in certain commands, it can be anything (..)
+            (F_GETFL, ..) => {
+                //for get, we just need to return the flags
+                *flags
+            }
+            (F_SETFL, arg) if arg >= 0 => {
+                //like linux, only the file status flags can be changed, the access mode stays as it was
+                let changeable = O_APPEND | O_NONBLOCK | O_ASYNC;
+                *flags = (*flags & !changeable) | (arg & changeable);
+                0
+            }
+            //TO DO: implement. this one is saying get the signals
+            (F_GETOWN, ..) => {
+                0 //TO DO: traditional SIGIO behavior
+            }
+            (F_SETOWN, arg) if arg >= 0 => {
+                0 //this would return the PID if positive and the process group if negative,
+                //either way do nothing and return success
+            }
+            (F_GETPIPE_SZ, ..) => {
+                match pipe_option {
+                    Some(pipe) => pipe.get_size() as i32,
+                    None => syscall_error(Errno::EBADF, "fcntl", "fd does not refer to a pipe")
                 }
-                //TO DO: implement. this one is saying get the signals
-                (F_GETOWN, ..) => {
-                    0 //TO DO: traditional SIGIO behavior
+            }
+            (F_SETPIPE_SZ, arg) if arg >= 0 => {
+                let pipe = if let Some(pipe) = pipe_option {pipe} else {
+                    return syscall_error(Errno::EBADF, "fcntl", "fd does not refer to a pipe");
+                };
+                if arg as usize > PIPE_MAX_SIZE {
+                    return syscall_error(Errno::EPERM, "fcntl", "requested pipe size is above the maximum pipe size");
                 }
-                (F_SETOWN, arg) if arg >= 0 => {
-                    0 //this would return the PID if positive and the process group if negative,
-                    //either way do nothing and return success
+
+                //like linux, sizes are rounded up to a power of two, no smaller than a page
+                match pipe.set_size((arg as usize).max(PIPE_MIN_SIZE).next_power_of_two()) {
+                    Ok(newsize) => newsize as i32,
+                    Err(_) => syscall_error(Errno::EBUSY, "fcntl", "pipe holds more data than the requested size")
                 }
-                _ => {syscall_error(Errno::EINVAL, "fcntl", "Arguments provided do not match implemented parameters")}
             }
-        } else {
-            syscall_error(Errno::EBADF, "fcntl", "Invalid file descriptor")
+            _ => {syscall_error(Errno::EINVAL, "fcntl", "Arguments provided do not match implemented parameters")}
         }
     }
 
     //------------------------------------CHMOD SYSCALL------------------------------------
 
     pub fn chmod_syscall(&self, path: &str, mode: u32) -> i32 {
+        self._chmod_path_helper(AT_FDCWD, path, mode, 0, "chmod")
+    }
+
+    //like chmod, with a relative path taken from the directory open as dirfd unless that's AT_FDCWD
+    pub fn fchmodat_syscall(&self, dirfd: i32, path: &str, mode: u32, flags: i32) -> i32 {
+        self._chmod_path_helper(dirfd, path, mode, flags, "fchmodat")
+    }
+
+    fn _chmod_path_helper(&self, dirfd: i32, path: &str, mode: u32, flags: i32, syscallname: &str) -> i32 {
+        if path.len() == 0 {return syscall_error(Errno::ENOENT, syscallname, "given path was null");}
+        if flags & !AT_SYMLINK_NOFOLLOW != 0 {return syscall_error(Errno::EINVAL, syscallname, "flags has an unknown flag set");}
+        let fullpath = match self._at_path(dirfd, path, syscallname) {
+            Ok(fullpath) => fullpath,
+            Err(e) => {return e;}
+        };
 
         let mut metadata = FS_METADATA.write().unwrap();
-        let truepath = normpath(convpath(path), self);
+        let truepath = match self._resolve_path(&fullpath, flags & AT_SYMLINK_NOFOLLOW == 0, Some(&metadata), syscallname) {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         //check if there is a valid path or not there to an inode
         if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
             let thisinode = metadata.inodetable.get_mut(&inodenum).unwrap();
-            if mode & (S_IRWXA|(S_FILETYPEFLAGS as u32)) == mode {
-                match thisinode {
-                    Inode::File(ref mut general_inode) => {
-                        general_inode.mode = (general_inode.mode &!S_IRWXA) | mode
-                    }
-                    Inode::CharDev(ref mut dev_inode) => {
-                        dev_inode.mode = (dev_inode.mode &!S_IRWXA) | mode;
-                    }
-                    Inode::Dir(ref mut dir_inode) => {
-                        dir_inode.mode = (dir_inode.mode &!S_IRWXA) | mode;
-                    }
+            //like linux, the mode of a link itself can't be changed
+            if let Inode::Symlink(_) = thisinode {
+                return syscall_error(Errno::EOPNOTSUPP, syscallname, "the mode of a symbolic link cannot be changed");
+            }
+            let retval = self._chmod_inode(thisinode, mode, syscallname);
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::ENOENT, syscallname, "the provided path does not exist")
+        }
+    }
+
+    pub fn fchmod_syscall(&self, fd: i32, mode: u32) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            //only files and the ends of fifos have inodes to keep a mode in
+            let inodenum = match &*filedesc_enum {
+                File(normalfile_filedesc_obj) => normalfile_filedesc_obj.inode,
+                Pipe(PipeDesc {inode: Some(inodenum), ..}) => *inodenum,
+                _ => {return syscall_error(Errno::EINVAL, "fchmod", "modes are only kept for files in the filesystem");}
+            };
+
+            let mut metadata = FS_METADATA.write().unwrap();
+            let retval = self._chmod_inode(metadata.inodetable.get_mut(&inodenum).unwrap(), mode, "fchmod");
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::EBADF, "fchmod", "invalid file descriptor")
+        }
+    }
+
+    //sets the permission bits of inode, along with its setuid, setgid, and sticky bits
+    fn _chmod_inode(&self, inode: &mut Inode, mode: u32, syscallname: &str) -> i32 {
+        if mode & (S_IALLUGO | S_FILETYPEFLAGS as u32) != mode {
+            //there doesn't seem to be a good syscall error errno for this
+            return syscall_error(Errno::EACCES, syscallname, "provided file mode is not valid");
+        }
+        let mut mode = mode & S_IALLUGO;
+
+        //only the owner of a file, or root, may change its mode
+        let (_, owner, group) = inode.ownership();
+        let creds = self.creds.read().unwrap();
+        if creds.euid != owner && !creds.is_privileged() {
+            return syscall_error(Errno::EPERM, syscallname, "the caller does not own the file");
+        }
+        //as on linux, a file can't be made setgid for a group the caller isn't in, which is quietly dropped rather than an error
+        if !creds.is_privileged() && !creds.in_group(group) {
+            mode &= !S_ISGID;
+        }
+        drop(creds);
+
+        inode.set_permissions(mode);
+        0
+    }
+
+    //------------------------------------CHOWN SYSCALL------------------------------------
+
+    pub fn chown_syscall(&self, path: &str, uid: u32, gid: u32) -> i32 {
+        self._chown_path_helper(path, uid, gid, true, "chown")
+    }
+
+    //like chown, but changes a symbolic link at the end of path rather than what it points to
+    pub fn lchown_syscall(&self, path: &str, uid: u32, gid: u32) -> i32 {
+        self._chown_path_helper(path, uid, gid, false, "lchown")
+    }
+
+    fn _chown_path_helper(&self, path: &str, uid: u32, gid: u32, follow_last: bool, syscallname: &str) -> i32 {
+        let mut metadata = FS_METADATA.write().unwrap();
+        let truepath = match self._resolve_path(path, follow_last, Some(&metadata), syscallname) {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
+        if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
+            let retval = self._chown_inode(metadata.inodetable.get_mut(&inodenum).unwrap(), uid, gid, syscallname);
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::ENOENT, syscallname, "the provided path does not exist")
+        }
+    }
+
+    pub fn fchown_syscall(&self, fd: i32, uid: u32, gid: u32) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            //only files and the ends of fifos have inodes to keep an owner in
+            let inodenum = match &*filedesc_enum {
+                File(normalfile_filedesc_obj) => normalfile_filedesc_obj.inode,
+                Pipe(PipeDesc {inode: Some(inodenum), ..}) => *inodenum,
+                _ => {return syscall_error(Errno::EINVAL, "fchown", "ownership is only kept for files in the filesystem");}
+            };
+
+            let mut metadata = FS_METADATA.write().unwrap();
+            let retval = self._chown_inode(metadata.inodetable.get_mut(&inodenum).unwrap(), uid, gid, "fchown");
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::EBADF, "fchown", "invalid file descriptor")
+        }
+    }
+
+    //sets the owner and group of inode, either of which is left as is when given as NO_ID
+    //root may give a file to anyone, while the owner of a file may only change its group, and only to one they are in
+    fn _chown_inode(&self, inode: &mut Inode, uid: u32, gid: u32, syscallname: &str) -> i32 {
+        let (_, owner, group) = inode.ownership();
+        let creds = self.creds.read().unwrap();
+
+        if !creds.is_privileged() {
+            if uid != NO_ID && (creds.euid != owner || uid != owner) {
+                return syscall_error(Errno::EPERM, syscallname, "only root may change the owner of a file");
+            }
+            if gid != NO_ID && (creds.euid != owner || (gid != group && !creds.in_group(gid))) {
+                return syscall_error(Errno::EPERM, syscallname, "the caller does not own the file or is not in the group");
+            }
+        }
+
+        let newuid = if uid == NO_ID {owner} else {uid};
+        let newgid = if gid == NO_ID {group} else {gid};
+        inode.set_ownership(newuid, newgid);
+
+        //like linux, a file which changes hands stops being setuid, and setgid too if that's not just marking it for locking
+        if uid != NO_ID || gid != NO_ID {
+            let mode = inode.ownership().0;
+            if !is_dir(mode) {
+                let mut cleared = S_ISUID;
+                if mode & S_IXGRP != 0 {cleared |= S_ISGID;}
+                inode.set_permissions(mode & S_IALLUGO & !cleared);
+            }
+        }
+        0
+    }
+
+    //------------------------------------UTIMENSAT SYSCALL------------------------------------
+
+    //sets the access and modification times of the file at path, taken relative to the directory open as dirfd unless that's AT_FDCWD,
+    //each time being seconds and nanoseconds, or having UTIME_NOW or UTIME_OMIT for nanoseconds, with None setting both to now
+    pub fn utimensat_syscall(&self, dirfd: i32, path: &str, times: Option<[(u64, u64); 2]>, flags: i32) -> i32 {
+        if path.len() == 0 {return syscall_error(Errno::ENOENT, "utimensat", "given path was null");}
+        if flags & !AT_SYMLINK_NOFOLLOW != 0 {return syscall_error(Errno::EINVAL, "utimensat", "flags has an unknown flag set");}
+
+        let fullpath = match self._at_path(dirfd, path, "utimensat") {
+            Ok(fullpath) => fullpath,
+            Err(e) => {return e;}
+        };
+
+        let mut metadata = FS_METADATA.write().unwrap();
+        let truepath = match self._resolve_path(&fullpath, flags & AT_SYMLINK_NOFOLLOW == 0, Some(&metadata), "utimensat") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
+        if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
+            let retval = self._utimens_inode(metadata.inodetable.get_mut(&inodenum).unwrap(), times, "utimensat");
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::ENOENT, "utimensat", "the provided path does not exist")
+        }
+    }
+
+    //like utimensat on the file open as fd
+    pub fn futimens_syscall(&self, fd: i32, times: Option<[(u64, u64); 2]>) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            //only files and the ends of fifos have inodes to keep times in
+            let inodenum = match &*filedesc_enum {
+                File(normalfile_filedesc_obj) => normalfile_filedesc_obj.inode,
+                Pipe(PipeDesc {inode: Some(inodenum), ..}) => *inodenum,
+                _ => {return syscall_error(Errno::EINVAL, "futimens", "times are only kept for files in the filesystem");}
+            };
+
+            let mut metadata = FS_METADATA.write().unwrap();
+            let retval = self._utimens_inode(metadata.inodetable.get_mut(&inodenum).unwrap(), times, "futimens");
+            if retval == 0 {persist_metadata(&metadata);}
+            retval
+        } else {
+            syscall_error(Errno::EBADF, "futimens", "invalid file descriptor")
+        }
+    }
+
+    //the older form of utimensat, with times in seconds and microseconds
+    pub fn utimes_syscall(&self, path: &str, times: Option<[(u64, u64); 2]>) -> i32 {
+        let nstimes = match times {
+            Some(times) => {
+                if times.iter().any(|(_, usec)| *usec >= 1_000_000) {
+                    return syscall_error(Errno::EINVAL, "utimes", "a time has an invalid number of microseconds");
                 }
+                Some([(times[0].0, times[0].1 * 1000), (times[1].0, times[1].1 * 1000)])
             }
-            else {
-                //there doesn't seem to be a good syscall error errno for this
-                return syscall_error(Errno::EACCES, "chmod", "provided file mode is not valid");
+            None => None
+        };
+        self.utimensat_syscall(AT_FDCWD, path, nstimes, 0)
+    }
+
+    //setting both times to now only takes being able to write to the file, while setting any other time takes owning it
+    fn _utimens_inode(&self, inode: &mut Inode, times: Option<[(u64, u64); 2]>, syscallname: &str) -> i32 {
+        let times = times.unwrap_or([(0, UTIME_NOW), (0, UTIME_NOW)]);
+        if times.iter().any(|(_, nsec)| *nsec >= 1_000_000_000 && *nsec != UTIME_NOW && *nsec != UTIME_OMIT) {
+            return syscall_error(Errno::EINVAL, syscallname, "a time has an invalid number of nanoseconds");
+        }
+        if times.iter().any(|(sec, nsec)| *nsec != UTIME_NOW && *nsec != UTIME_OMIT && interface::timespec_to_timestamp(*sec, *nsec).is_none()) {
+            return syscall_error(Errno::EINVAL, syscallname, "a time is too large to be kept");
+        }
+        //like linux, leaving both times as they are does nothing at all
+        if times.iter().all(|(_, nsec)| *nsec == UTIME_OMIT) {return 0;}
+
+        {
+            let creds = self.creds.read().unwrap();
+            if creds.euid != inode.ownership().1 && !creds.is_privileged() {
+                if times.iter().any(|(_, nsec)| *nsec != UTIME_NOW) {
+                    return syscall_error(Errno::EPERM, syscallname, "only the owner of a file may set its times to anything but now");
+                }
+                if !inode_permits(inode, &*creds, W_OK) {
+                    return syscall_error(Errno::EACCES, syscallname, "write permission is denied for the file");
+                }
             }
+        }
+
+        let now = interface::timestamp();
+        let totime = |(sec, nsec): (u64, u64)| match nsec {
+            UTIME_NOW => Some(now),
+            UTIME_OMIT => None,
+            _ => interface::timespec_to_timestamp(sec, nsec),
+        };
+        inode.set_times(totime(times[0]), totime(times[1]));
+        0
+    }
+
+    //path as an *at syscall takes it, relative to the directory open as dirfd unless it's absolute or dirfd is AT_FDCWD
+    fn _at_path(&self, dirfd: i32, path: &str, syscallname: &str) -> Result<String, i32> {
+        if dirfd == AT_FDCWD || path.starts_with('/') {return Ok(path.to_string());}
+        let dirpath = self._dirfd_path(dirfd, syscallname)?;
+        Ok(dirpath.join(path).to_str().unwrap().to_string())
+    }
+
+    //the path of the directory open as dirfd, found by going up the ..s of the directory and looking for each in its parent
+    fn _dirfd_path(&self, dirfd: i32, syscallname: &str) -> Result<interface::RustPathBuf, i32> {
+        let fdtable = self.filedescriptortable.read().unwrap();
+        let wrappedfd = match fdtable.get(&dirfd) {
+            Some(wrappedfd) => wrappedfd,
+            None => {return Err(syscall_error(Errno::EBADF, syscallname, "dirfd is not a valid file descriptor"));}
+        };
+        let mut inodenum = if let File(normalfile_filedesc_obj) = &*wrappedfd.read().unwrap() {
+            normalfile_filedesc_obj.inode
         } else {
-            return syscall_error(Errno::ENOENT, "chmod", "the provided path does not exist");
+            return Err(syscall_error(Errno::ENOTDIR, syscallname, "dirfd does not refer to a directory"));
+        };
+
+        let metadata = FS_METADATA.read().unwrap();
+        let mut names = vec![];
+        while inodenum != ROOTDIRECTORYINODE {
+            let parentinodenum = match metadata.inodetable.get(&inodenum) {
+                Some(Inode::Dir(dir)) => *dir.filename_to_inode_dict.get("..").unwrap(),
+                Some(_) => {return Err(syscall_error(Errno::ENOTDIR, syscallname, "dirfd does not refer to a directory"));}
+                None => {return Err(syscall_error(Errno::ENOENT, syscallname, "the directory dirfd refers to has been removed"));}
+            };
+            let parentdir = if let Inode::Dir(dir) = metadata.inodetable.get(&parentinodenum).unwrap() {dir} else {unreachable!();};
+            match parentdir.filename_to_inode_dict.iter().find(|(name, num)| **num == inodenum && *name != "." && *name != "..") {
+                Some((name, _)) => names.push(name.clone()),
+                None => {return Err(syscall_error(Errno::ENOENT, syscallname, "the directory dirfd refers to has been removed"));}
+            }
+            inodenum = parentinodenum;
         }
-        persist_metadata(&metadata);
-        0 //success!
+
+        let mut path = interface::RustPathBuf::from("/");
+        path.extend(names.iter().rev());
+        Ok(path)
     }
 
     //------------------------------------MMAP SYSCALL------------------------------------
@@ -1343,8 +2131,12 @@ impl Cage {
                     //confirm inode type is mappable
                     match inodeobj {
                         Inode::File(normalfile_inode_obj) => {
+                            //any mapping reads the file, so the file needs to be open for reading
+                            if is_wronly(normalfile_filedesc_obj.flags) {
+                                return syscall_error(Errno::EACCES, "mmap", "file descriptor is not open for reading");
+                            }
                             //if we want to write our changes back to the file the file needs to be open for reading and writing
-                            if (flags & MAP_SHARED != 0) && (flags & PROT_WRITE != 0) && (normalfile_filedesc_obj.flags & O_RDWR != 0) {
+                            if (flags & MAP_SHARED != 0) && (prot & PROT_WRITE != 0) && (normalfile_filedesc_obj.flags & O_RDWRFLAGS != O_RDWR) {
                                 return syscall_error(Errno::EACCES, "mmap", "file descriptor is not open RDWR, but MAP_SHARED and PROT_WRITE are set");
                             }
                             let filesize = normalfile_inode_obj.size;
@@ -1366,7 +2158,8 @@ impl Cage {
                             syscall_error(Errno::EOPNOTSUPP, "mmap", "lind currently does not support mapping character files")
                         }
 
-                        Inode::Dir(_) => {syscall_error(Errno::EACCES, "mmap", "the fildes argument refers to a file whose type is not supported by mmap")}
+                        Inode::Dir(_) | Inode::Socket(_) | Inode::Fifo(_) => {syscall_error(Errno::EACCES, "mmap", "the fildes argument refers to a file whose type is not supported by mmap")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
                     }
                 }
                 _ => {syscall_error(Errno::EACCES, "mmap", "the fildes argument refers to a file whose type is not supported by mmap")}
@@ -1436,8 +2229,11 @@ impl Cage {
     pub fn rmdir_syscall(&self, path: &str) -> i32 {
         if path.len() == 0 {return syscall_error(Errno::ENOENT, "rmdir", "Given path is null");}
 
-        let truepath = normpath(convpath(path), self);
         let mut metadata = FS_METADATA.write().unwrap();
+        let truepath = match self._resolve_path(path, false, Some(&metadata), "rmdir") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         // try to get inodenum of input path and its parent
         match metawalkandparent(truepath.as_path(), Some(&metadata)) {
@@ -1456,20 +2252,27 @@ impl Cage {
                         if dir_obj.linkcount > 3 {return syscall_error(Errno::ENOTEMPTY, "rmdir", "Directory is not empty");}
                         if !is_dir(dir_obj.mode) {panic!("This directory does not have its mode set to S_IFDIR");}
 
-                        // check if dir has write permission
-                        if dir_obj.mode as u32 & (S_IWOTH | S_IWGRP | S_IWUSR) == 0 {return syscall_error(Errno::EPERM, "rmdir", "Directory does not have write permission")}
-                        
-                        // remove entry of corresponding inodenum from inodetable
-                        metadata.inodetable.remove(&inodenum).unwrap();
+                        // check if parent dir has write permission
+                        if !self._permits(metadata.inodetable.get(&parent_inodenum).unwrap(), W_OK | X_OK) {
+                            return syscall_error(Errno::EACCES, "rmdir", "Parent directory does not have write permission");
+                        }
+                        if !self._sticky_permits(metadata.inodetable.get(&parent_inodenum).unwrap(), metadata.inodetable.get(&inodenum).unwrap()) {
+                            return syscall_error(Errno::EPERM, "rmdir", "the parent directory is sticky and the caller owns neither it nor the directory");
+                        }
+
+                        // the directory loses its links, but stays in the inodetable until it is no longer open
+                        let inodeobj = metadata.inodetable.get_mut(&inodenum).unwrap();
+                        *inodeobj.linkcount_mut() = 0;
+                        if inodeobj.refcount() == 0 {
+                            metadata.inodetable.remove(&inodenum);
+                        }
                         
                         if let Inode::Dir(parent_dir) = metadata.inodetable.get_mut(&parent_inodenum).unwrap() {
-                            // check if parent dir has write permission
-                            if parent_dir.mode as u32 & (S_IWOTH | S_IWGRP | S_IWUSR) == 0 {return syscall_error(Errno::EPERM, "rmdir", "Parent directory does not have write permission")}
-                            
                             // remove entry of corresponding filename from filename-inode dict
                             parent_dir.filename_to_inode_dict.remove(&truepath.file_name().unwrap().to_str().unwrap().to_string()).unwrap();
                             parent_dir.linkcount -= 1; // decrement linkcount of parent dir
                         }
+                        metadata.inodetable.get_mut(&parent_inodenum).unwrap().mark_modified();
                         persist_metadata(&metadata);
                         0 // success
                     }
@@ -1485,36 +2288,148 @@ impl Cage {
         if oldpath.len() == 0 {return syscall_error(Errno::ENOENT, "rename", "Old path is null");}
         if newpath.len() == 0 {return syscall_error(Errno::ENOENT, "rename", "New path is null");}
 
-        let true_oldpath = normpath(convpath(oldpath), self);
-        let true_newpath = normpath(convpath(newpath), self);
         let mut metadata = FS_METADATA.write().unwrap();
+        //links are renamed themselves, not what they point to
+        let true_oldpath = match self._resolve_path(oldpath, false, Some(&metadata), "rename") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+        let true_newpath = match self._resolve_path(newpath, false, Some(&metadata), "rename") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
 
         // try to get inodenum of old path and its parent
-        match metawalkandparent(true_oldpath.as_path(), Some(&metadata)) {
+        let (inodenum, parent_inodenum) = match metawalkandparent(true_oldpath.as_path(), Some(&metadata)) {
             (None, ..) => {
-                syscall_error(Errno::EEXIST, "rename", "Old path does not exist")
+                return syscall_error(Errno::ENOENT, "rename", "Old path does not exist");
             }
             (Some(_), None) => {
-                syscall_error(Errno::EBUSY, "rename", "Cannot rename root directory")
+                return syscall_error(Errno::EBUSY, "rename", "Cannot rename root directory");
             }
-            (Some(inodenum), Some(parent_inodenum)) => {
-                // make sure file is not moved to another dir 
-                // get inodenum for parent of new path
-                let (_, new_par_inodenum) = metawalkandparent(true_newpath.as_path(), Some(&metadata));
-                // check if old and new paths share parent
-                if new_par_inodenum != Some(parent_inodenum) {
-                    return syscall_error(Errno::EOPNOTSUPP, "rename", "Cannot move file to another directory");
+            (Some(inodenum), Some(parent_inodenum)) => (inodenum, parent_inodenum)
+        };
+
+        // get inodenum of whatever is already at the new path, and of the directory it's to go in
+        let (target_inodenum, new_par_inodenum) = match metawalkandparent(true_newpath.as_path(), Some(&metadata)) {
+            (None, None) => {
+                return syscall_error(Errno::ENOENT, "rename", "A directory component in new path does not exist");
+            }
+            (Some(_), None) => {
+                return syscall_error(Errno::EBUSY, "rename", "Cannot replace root directory");
+            }
+            (target_inodenum, Some(new_par_inodenum)) => (target_inodenum, new_par_inodenum)
+        };
+
+        // renaming something to another name for itself does nothing, as with linux
+        if target_inodenum == Some(inodenum) {return 0;}
+
+        // entries are removed from the old parent and added to the new one, which both must allow
+        for dirinodenum in [parent_inodenum, new_par_inodenum].iter() {
+            if !self._permits(metadata.inodetable.get(dirinodenum).unwrap(), W_OK | X_OK) {
+                return syscall_error(Errno::EACCES, "rename", "write permission is denied for a parent directory");
+            }
+        }
+
+        // in sticky directories, only what the caller owns (or the directory is theirs) can be moved away or replaced
+        if !self._sticky_permits(metadata.inodetable.get(&parent_inodenum).unwrap(), metadata.inodetable.get(&inodenum).unwrap()) {
+            return syscall_error(Errno::EPERM, "rename", "the old parent directory is sticky and the caller owns neither it nor the file");
+        }
+        if let Some(target_inodenum) = target_inodenum {
+            if !self._sticky_permits(metadata.inodetable.get(&new_par_inodenum).unwrap(), metadata.inodetable.get(&target_inodenum).unwrap()) {
+                return syscall_error(Errno::EPERM, "rename", "the new parent directory is sticky and the caller owns neither it nor the file being replaced");
+            }
+        }
+
+        let isdir = if let Inode::Dir(_) = metadata.inodetable.get(&inodenum).unwrap() {true} else {false};
+
+        // a directory moved to another parent has its .. rewritten, so must itself be writable
+        if isdir && new_par_inodenum != parent_inodenum && !self._permits(metadata.inodetable.get(&inodenum).unwrap(), W_OK) {
+            return syscall_error(Errno::EACCES, "rename", "write permission is denied for the directory being moved");
+        }
+
+        // a directory can't be moved into itself or anything under it, which we check by walking up from the new parent
+        if isdir {
+            let mut ancestor = new_par_inodenum;
+            loop {
+                if ancestor == inodenum {
+                    return syscall_error(Errno::EINVAL, "rename", "Cannot move a directory into its own subdirectory");
                 }
-                
-                if let Inode::Dir(parent_dir) = metadata.inodetable.get_mut(&parent_inodenum).unwrap() {
-                    // add pair of new path and its inodenum to filename-inode dict
-                    parent_dir.filename_to_inode_dict.insert(true_newpath.file_name().unwrap().to_str().unwrap().to_string(), inodenum);
+                if ancestor == ROOTDIRECTORYINODE {break;}
+                ancestor = if let Inode::Dir(dir) = metadata.inodetable.get(&ancestor).unwrap() {
+                    *dir.filename_to_inode_dict.get("..").unwrap()
+                } else {unreachable!();};
+            }
+        }
 
-                    // remove entry of old path from filename-inode dict
-                    parent_dir.filename_to_inode_dict.remove(&true_oldpath.file_name().unwrap().to_str().unwrap().to_string());
+        // an existing target is replaced only by something of its own kind, and only if empty when a directory
+        if let Some(target_inodenum) = target_inodenum {
+            match metadata.inodetable.get(&target_inodenum).unwrap() {
+                Inode::Dir(target_dir) => {
+                    if !isdir {return syscall_error(Errno::EISDIR, "rename", "New path is a directory but old path is not");}
+                    if target_dir.filename_to_inode_dict.len() > 2 {return syscall_error(Errno::ENOTEMPTY, "rename", "New path is a directory which is not empty");}
                 }
-                persist_metadata(&metadata);
-                0 // success
+                _ => {
+                    if isdir {return syscall_error(Errno::ENOTDIR, "rename", "Old path is a directory but new path is not");}
+                }
+            }
+        }
+
+        let oldname = true_oldpath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
+        let newname = true_newpath.file_name().unwrap().to_str().unwrap().to_string();
+
+        // everything below happens under the metadata lock, so the target is replaced in one step
+        if let Some(target_inodenum) = target_inodenum {
+            metadata.inodetable.get_mut(&target_inodenum).unwrap().mark_changed();
+            Self::_rename_drop_target(&mut metadata, target_inodenum);
+            if let Inode::Dir(new_parent_dir) = metadata.inodetable.get_mut(&new_par_inodenum).unwrap() {
+                new_parent_dir.filename_to_inode_dict.remove(&newname);
+                new_parent_dir.linkcount -= 1;
+            }
+        }
+
+        if let Inode::Dir(parent_dir) = metadata.inodetable.get_mut(&parent_inodenum).unwrap() {
+            // remove entry of old path from filename-inode dict
+            parent_dir.filename_to_inode_dict.remove(&oldname);
+            parent_dir.linkcount -= 1;
+        }
+        if let Inode::Dir(new_parent_dir) = metadata.inodetable.get_mut(&new_par_inodenum).unwrap() {
+            // add pair of new path and its inodenum to filename-inode dict
+            new_parent_dir.filename_to_inode_dict.insert(newname, inodenum);
+            new_parent_dir.linkcount += 1;
+        }
+
+        // a moved directory's .. now refers to its new parent
+        if let Inode::Dir(dir) = metadata.inodetable.get_mut(&inodenum).unwrap() {
+            dir.filename_to_inode_dict.insert("..".to_string(), new_par_inodenum);
+        }
+
+        // as on linux, both parents are modified and the renamed inode itself is changed
+        metadata.inodetable.get_mut(&parent_inodenum).unwrap().mark_modified();
+        metadata.inodetable.get_mut(&new_par_inodenum).unwrap().mark_modified();
+        metadata.inodetable.get_mut(&inodenum).unwrap().mark_changed();
+
+        persist_metadata(&metadata);
+        0 // success
+    }
+
+    // drops the link to the target a rename replaces, removing it if nothing else links to it or has it open, as unlink and rmdir would
+    fn _rename_drop_target(metadata: &mut FilesystemMetadata, target_inodenum: usize) {
+        let inodeobj = metadata.inodetable.get_mut(&target_inodenum).unwrap();
+        let has_fobj = matches!(inodeobj, Inode::File(_));
+        //an empty directory has no links left once its entry is gone, . and .. going with it
+        if let Inode::Dir(_) = inodeobj {
+            *inodeobj.linkcount_mut() = 0;
+        } else {
+            *inodeobj.linkcount_mut() -= 1;
+        }
+        let (currefcount, curlinkcount) = (inodeobj.refcount(), *inodeobj.linkcount_mut());
+
+        if curlinkcount == 0 && currefcount == 0 {
+            metadata.inodetable.remove(&target_inodenum);
+            if has_fobj {
+                let sysfilename = format!("{}{}", FILEDATAPREFIX, target_inodenum);
+                interface::removefile(sysfilename).unwrap();
             }
         }
     }
@@ -1532,38 +2447,26 @@ impl Cage {
             match &*filedesc_enum {
                 // only proceed when fd references a regular file
                 File(normalfile_filedesc_obj) => {
+                    if is_rdonly(normalfile_filedesc_obj.flags) {
+                        return syscall_error(Errno::EINVAL, "ftruncate", "fd is not open for writing");
+                    }
                     let inodenum = normalfile_filedesc_obj.inode;
                     let inodeobj = mutmetadata.inodetable.get_mut(&inodenum).unwrap();
 
                     match inodeobj {
                         // only proceed when inode matches with a file
                         Inode::File(ref mut normalfile_inode_obj) => {
-                            // get file object table with write lock
-                            let mut fobjtable = FILEOBJECTTABLE.write().unwrap();
-                            
-                            let mut fileobject = fobjtable.get_mut(&inodenum).unwrap();
-                            let filesize = normalfile_inode_obj.size as isize;
-                            
-                            // if length is greater than original filesize,
-                            // file is extented with null bytes
-                            if filesize < length {
-                                let blankbytecount = length - filesize;
-                                if let Ok(byteswritten) = fileobject.zerofill_at(filesize as usize, blankbytecount as usize) {
-                                    if byteswritten != blankbytecount as usize {
-                                        panic!("zerofill_at() has failed");
-                                    }
-                                } else {
-                                    panic!("zerofill_at() has failed");
-                                }
-                            } else { // if length is smaller than original filesize,
-                                     // extra data are cut off
-                                fileobject.shrink(length as usize);
-                            } 
+                            self._truncate_file(inodenum, normalfile_inode_obj, length);
                             persist_metadata(&mutmetadata);
                         }
                         Inode::CharDev(_) => {
                             return syscall_error(Errno::EISDIR, "ftruncate", "The named file is a character driver");
                         }
+                        Inode::Socket(_) => {
+                            return syscall_error(Errno::EINVAL, "ftruncate", "The named file is a socket");
+                        }
+                        Inode::Fifo(_) => {unreachable!("fifos are opened as pipes")}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened")}
                         Inode::Dir(_) => {
                             return syscall_error(Errno::EISDIR, "ftruncate", "The named file is a directory");
                         }
@@ -1579,14 +2482,95 @@ impl Cage {
         }
     }
 
+    //sets the size of a regular file, cutting off its end or extending it with null bytes
+    fn _truncate_file(&self, inodenum: usize, normalfile_inode_obj: &mut GenericInode, length: isize) {
+        // get file object table with write lock
+        let mut fobjtable = FILEOBJECTTABLE.write().unwrap();
+
+        //a file which no cage has open has no file object, so one is opened just for this
+        let openedhere = !fobjtable.contains_key(&inodenum);
+        if openedhere {
+            let sysfilename = format!("{}{}", FILEDATAPREFIX, inodenum);
+            fobjtable.insert(inodenum, interface::openfile(sysfilename, true).unwrap());
+        }
+
+        let fileobject = fobjtable.get_mut(&inodenum).unwrap();
+        let filesize = normalfile_inode_obj.size as isize;
+
+        // if length is greater than original filesize,
+        // file is extented with null bytes
+        if filesize < length {
+            let blankbytecount = length - filesize;
+            if let Ok(byteswritten) = fileobject.zerofill_at(filesize as usize, blankbytecount as usize) {
+                if byteswritten != blankbytecount as usize {
+                    panic!("zerofill_at() has failed");
+                }
+            } else {
+                panic!("zerofill_at() has failed");
+            }
+        } else { // if length is smaller than original filesize,
+                 // extra data are cut off
+            fileobject.shrink(length as usize);
+        }
+
+        if openedhere {
+            fobjtable.remove(&inodenum).unwrap().close().unwrap();
+        }
+
+        normalfile_inode_obj.size = length as usize;
+        let time = interface::timestamp();
+        normalfile_inode_obj.mtime = time;
+        normalfile_inode_obj.ctime = time;
+    }
+
     //------------------TRUNCATE SYSCALL------------------
     pub fn truncate_syscall(&self, path: &str, length: isize) -> i32 {
-        self.ftruncate_syscall(self.open_syscall(path, O_RDWR, S_IRWXA), length)
+        if path.len() == 0 {return syscall_error(Errno::ENOENT, "truncate", "given path was null");}
+
+        //the file is truncated in place rather than opened, which for a fifo could block until it had a reader
+        let mut mutmetadata = FS_METADATA.write().unwrap();
+        let truepath = match self._resolve_path(path, true, Some(&mutmetadata), "truncate") {
+            Ok(truepath) => truepath,
+            Err(e) => {return e;}
+        };
+
+        if let Some(inodenum) = metawalk(truepath.as_path(), Some(&mutmetadata)) {
+            let inodeobj = mutmetadata.inodetable.get_mut(&inodenum).unwrap();
+            match inodeobj {
+                Inode::File(_) => {}
+                Inode::Dir(_) => {
+                    return syscall_error(Errno::EISDIR, "truncate", "The named file is a directory");
+                }
+                _ => {
+                    return syscall_error(Errno::EINVAL, "truncate", "The named file is not a regular file");
+                }
+            }
+            if !self._permits(inodeobj, W_OK) {
+                return syscall_error(Errno::EACCES, "truncate", "write permission is denied for the file");
+            }
+
+            if let Inode::File(ref mut normalfile_inode_obj) = inodeobj {
+                self._truncate_file(inodenum, normalfile_inode_obj, length);
+            }
+            persist_metadata(&mutmetadata);
+            0
+        } else {
+            syscall_error(Errno::ENOENT, "truncate", "the provided path does not exist")
+        }
     }
 
     //------------------PIPE SYSCALL------------------
 
     pub fn pipe_syscall(&self, pipefd: &mut PipeArray) -> i32 {
+        self.pipe2_syscall(pipefd, 0)
+    }
+
+    pub fn pipe2_syscall(&self, pipefd: &mut PipeArray, flags: i32) -> i32 {
+
+        //only close-on-exec and nonblocking may be asked for, and they apply to both ends
+        if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+            return syscall_error(Errno::EINVAL, "pipe2", "invalid flags were specified");
+        }
 
         let mut fdtable = self.filedescriptortable.write().unwrap();
 
@@ -1600,35 +2584,212 @@ impl Cage {
 
         let mut pipetable = PIPE_TABLE.write().unwrap();
 
+        // get an fd for each end of the pipe before touching the fdtable, so a failure leaves nothing behind
+        let readfd = self.get_next_fd(None, Some(&fdtable));
+        let writefd = readfd.and_then(|readfd| self.get_next_fd(Some(readfd + 1), Some(&fdtable)));
+        let (readfd, writefd) = if let (Some(readfd), Some(writefd)) = (readfd, writefd) {
+            (readfd, writefd)
+        } else {
+            return syscall_error(Errno::EMFILE, "pipe", "no available file descriptor number could be found");
+        };
+
         pipetable.insert(pipenumber, interface::RustRfc::new(interface::new_pipe(PIPE_CAPACITY)));
+
+        // set flags to RD_ONLY and WR_ONLY plus whatever was asked for, and fill in pipefds
+        for (thisfd, accmode) in [(readfd, O_RDONLY), (writefd, O_WRONLY)] {
+            let newfd = Pipe(PipeDesc {pipe: pipenumber, inode: None, flags: accmode | (flags & O_NONBLOCK), advlock: interface::AdvisoryLock::new()});
+            fdtable.insert(thisfd, FdEntry::new(newfd, flags & O_CLOEXEC != 0));
+        }
+        pipefd.readfd = readfd;
+        pipefd.writefd = writefd;
+
+      0 // success
+    }  
         
-        // get an fd for each end of the pipe and set flags to RD_ONLY and WR_ONLY
-        // append each to pipefds list
+    //------------------SPLICE, TEE, AND SENDFILE SYSCALLS------------------
+    //these move data between pipes and files inside lind, copying it through a buffer of our own rather than the caller's
 
-        let flags = [O_RDONLY, O_WRONLY];
-        for flag in flags {
+    pub fn splice_syscall(&self, fd_in: i32, off_in: Option<&mut i64>, fd_out: i32, off_out: Option<&mut i64>, len: usize, flags: u32) -> i32 {
+        if flags & !(SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT) != 0 {
+            return syscall_error(Errno::EINVAL, "splice", "invalid flags were specified");
+        }
 
-            let thisfd = if let Some(fd) = self.get_next_fd(None, Some(&fdtable)) {
-                fd
-            } else {
-                pipetable.remove(&pipenumber).unwrap();
-                return syscall_error(Errno::ENFILE, "pipe", "no available file descriptor number could be found");
-            };
+        let pipe_in = match self._splice_pipe(fd_in, false, "splice") {Ok(p) => p, Err(e) => {return e;}};
+        let pipe_out = match self._splice_pipe(fd_out, true, "splice") {Ok(p) => p, Err(e) => {return e;}};
+        if (pipe_in.is_some() && off_in.is_some()) || (pipe_out.is_some() && off_out.is_some()) {
+            return syscall_error(Errno::ESPIPE, "splice", "an offset was given for a pipe");
+        }
+
+        match (pipe_in, pipe_out) {
+            (Some((pipe_in, pipeno_in, flags_in)), Some((pipe_out, pipeno_out, flags_out))) => {
+                if pipeno_in == pipeno_out {
+                    return syscall_error(Errno::EINVAL, "splice", "input and output refer to the same pipe");
+                }
+                Self::_pipe_to_pipe(&pipe_in, flags_in, &pipe_out, flags_out, len, flags, true, "splice")
+            }
+
+            (Some((pipe_in, _, flags_in)), None) => {
+                let position = match self._splice_position(fd_out, &off_out, true, "splice") {Ok(p) => p, Err(e) => {return e;}};
+                if len == 0 {return 0;}
+
+                //only what makes it into the file is taken out of the pipe, and no other reader can get to it in between
+                let nonblocking = flags & SPLICE_F_NONBLOCK != 0 || flags_in & O_NONBLOCK != 0;
+                let moved = pipe_in.read_with(len, nonblocking, |bytes| {
+                    let byteswritten = self.pwrite_syscall(fd_out, bytes.as_ptr(), bytes.len(), position as isize);
+                    if byteswritten < 0 {Err(byteswritten)} else {Ok(byteswritten as usize)}
+                });
+                match moved {
+                    Ok(byteswritten) => {
+                        self._splice_advance(fd_out, off_out, byteswritten);
+                        byteswritten as i32
+                    }
+                    //pwrite has already given its error
+                    Err(e) if e < 0 => e,
+                    Err(errno) => syscall_error(Errno::from_discriminant(errno), "splice", "there is no data available right now, try again later")
+                }
+            }
+
+            (None, Some((pipe_out, _, flags_out))) => {
+                let position = match self._splice_position(fd_in, &off_in, false, "splice") {Ok(p) => p, Err(e) => {return e;}};
+                if len == 0 {return 0;}
 
-            let newfd = Pipe(PipeDesc {pipe: pipenumber, flags: flag, advlock: interface::AdvisoryLock::new()});
-            let wrappedfd = interface::RustRfc::new(interface::RustLock::new(newfd));
-            fdtable.insert(thisfd, wrappedfd);
+                //never read more than the pipe could take, or if we won't wait for room, more than it can take right now
+                let nonblocking = flags & SPLICE_F_NONBLOCK != 0 || flags_out & O_NONBLOCK != 0;
+                let room = if nonblocking {pipe_out.room()} else {pipe_out.get_size()};
+                if room == 0 {
+                    return syscall_error(Errno::EAGAIN, "splice", "there is no room in the pipe right now, try again later");
+                }
 
-            match flag {
-                O_RDONLY => {pipefd.readfd = thisfd;},
-                O_WRONLY => {pipefd.writefd = thisfd;},
-                _ => panic!("How did you get here."),
+                let mut buf = vec![0u8; interface::rust_min(len, room)];
+                let bytesread = self.pread_syscall(fd_in, buf.as_mut_ptr(), buf.len(), position as isize);
+                if bytesread <= 0 {return bytesread;}
+
+                match pipe_out.write_to_pipe(buf.as_ptr(), bytesread as usize, nonblocking) {
+                    Ok(byteswritten) => {
+                        self._splice_advance(fd_in, off_in, byteswritten);
+                        byteswritten as i32
+                    }
+                    Err(errno) if errno == Errno::EPIPE as i32 => syscall_error(Errno::EPIPE, "splice", "the pipe has no readers left"),
+                    Err(errno) => syscall_error(Errno::from_discriminant(errno), "splice", "there is no room in the pipe right now, try again later")
+                }
             }
+
+            (None, None) => syscall_error(Errno::EINVAL, "splice", "neither descriptor refers to a pipe")
         }
+    }
+
+    pub fn tee_syscall(&self, fd_in: i32, fd_out: i32, len: usize, flags: u32) -> i32 {
+        if flags & !(SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT) != 0 {
+            return syscall_error(Errno::EINVAL, "tee", "invalid flags were specified");
+        }
+
+        let pipe_in = match self._splice_pipe(fd_in, false, "tee") {Ok(p) => p, Err(e) => {return e;}};
+        let pipe_out = match self._splice_pipe(fd_out, true, "tee") {Ok(p) => p, Err(e) => {return e;}};
+
+        if let (Some((pipe_in, pipeno_in, flags_in)), Some((pipe_out, pipeno_out, flags_out))) = (pipe_in, pipe_out) {
+            if pipeno_in == pipeno_out {
+                return syscall_error(Errno::EINVAL, "tee", "input and output refer to the same pipe");
+            }
+            Self::_pipe_to_pipe(&pipe_in, flags_in, &pipe_out, flags_out, len, flags, false, "tee")
+        } else {
+            syscall_error(Errno::EINVAL, "tee", "both descriptors must refer to pipes")
+        }
+    }
+
+    pub fn sendfile_syscall(&self, out_fd: i32, in_fd: i32, offset: Option<&mut i64>, count: usize) -> i32 {
+        let position = match self._splice_position(in_fd, &offset, false, "sendfile") {Ok(p) => p, Err(e) => {return e;}};
+
+        //the output can be anything we can write to, so we hand it the file a pipe's worth at a time
+        let mut buf = vec![0u8; interface::rust_min(count, PIPE_CAPACITY)];
+        let mut totalsent = 0;
+        while totalsent < count {
+            let chunk = interface::rust_min(count - totalsent, buf.len());
+            let bytesread = self.pread_syscall(in_fd, buf.as_mut_ptr(), chunk, (position + totalsent) as isize);
+            if bytesread <= 0 {
+                if totalsent == 0 {return bytesread;}
+                break;
+            }
+
+            let byteswritten = self.write_syscall(out_fd, buf.as_ptr(), bytesread as usize);
+            if byteswritten < 0 {
+                if totalsent == 0 {return byteswritten;}
+                break;
+            }
+            totalsent += byteswritten as usize;
+
+            //a short read means the end of the file, and a short write that the output can't take more right now
+            if (bytesread as usize) < chunk || byteswritten < bytesread {break;}
+        }
+
+        self._splice_advance(in_fd, offset, totalsent);
+        totalsent as i32
+    }
+
+    //the pipe behind fd along with its number and the descriptor's flags, or None if fd isn't a pipe
+    //fails if fd is a pipe end which can't be used in the direction asked for
+    fn _splice_pipe(&self, fd: i32, forwriting: bool, syscallname: &str) -> Result<Option<(interface::RustRfc<interface::EmulatedPipe>, i32, i32)>, i32> {
+        let wrappedfd = match self.filedescriptortable.read().unwrap().get(&fd) {
+            Some(wrappedfd) => wrappedfd.clone(),
+            None => {return Err(syscall_error(Errno::EBADF, syscallname, "invalid file descriptor"));}
+        };
+        let filedesc_enum = wrappedfd.read().unwrap();
+
+        if let Pipe(pipe_filedesc_obj) = &*filedesc_enum {
+            if (forwriting && is_rdonly(pipe_filedesc_obj.flags)) || (!forwriting && is_wronly(pipe_filedesc_obj.flags)) {
+                return Err(syscall_error(Errno::EBADF, syscallname, "pipe is not open in the direction required"));
+            }
+            let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
+            Ok(Some((pipe, pipe_filedesc_obj.pipe, pipe_filedesc_obj.flags)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    //the position to read or write the file at fd from, which is offset if given and otherwise the file's own position
+    fn _splice_position(&self, fd: i32, offset: &Option<&mut i64>, forwriting: bool, syscallname: &str) -> Result<usize, i32> {
+        let wrappedfd = match self.filedescriptortable.read().unwrap().get(&fd) {
+            Some(wrappedfd) => wrappedfd.clone(),
+            None => {return Err(syscall_error(Errno::EBADF, syscallname, "invalid file descriptor"));}
+        };
+        let filedesc_enum = wrappedfd.read().unwrap();
+
+        if let File(normalfile_filedesc_obj) = &*filedesc_enum {
+            if (forwriting && is_rdonly(normalfile_filedesc_obj.flags)) || (!forwriting && is_wronly(normalfile_filedesc_obj.flags)) {
+                return Err(syscall_error(Errno::EBADF, syscallname, "file is not open in the direction required"));
+            }
+            match offset {
+                Some(off) if **off < 0 => Err(syscall_error(Errno::EINVAL, syscallname, "offset is negative")),
+                Some(off) => Ok(**off as usize),
+                None => Ok(normalfile_filedesc_obj.position)
+            }
+        } else {
+            Err(syscall_error(Errno::EINVAL, syscallname, "descriptor refers to something other than a pipe or file"))
+        }
+    }
+
+    //moves offset if given and otherwise the position of the file at fd forward by count bytes
+    fn _splice_advance(&self, fd: i32, offset: Option<&mut i64>, count: usize) {
+        if let Some(off) = offset {
+            *off += count as i64;
+        } else if let Some(wrappedfd) = self.filedescriptortable.read().unwrap().get(&fd) {
+            if let File(ref mut normalfile_filedesc_obj) = &mut *wrappedfd.write().unwrap() {
+                normalfile_filedesc_obj.position += count;
+            }
+        }
+    }
+
+    //moves (if consume) or copies up to len bytes from one pipe to another, with what the output can't take left in the input
+    fn _pipe_to_pipe(pipe_in: &interface::EmulatedPipe, flags_in: i32, pipe_out: &interface::EmulatedPipe, flags_out: i32, len: usize, flags: u32, consume: bool, syscallname: &str) -> i32 {
+        let nonblocking_in = flags & SPLICE_F_NONBLOCK != 0 || flags_in & O_NONBLOCK != 0;
+        let nonblocking_out = flags & SPLICE_F_NONBLOCK != 0 || flags_out & O_NONBLOCK != 0;
+
+        match pipe_in.splice_to(pipe_out, len, consume, nonblocking_in, nonblocking_out) {
+            Ok(moved) => moved as i32,
+            Err(errno) if errno == Errno::EPIPE as i32 => syscall_error(Errno::EPIPE, syscallname, "the pipe has no readers left"),
+            Err(errno) => syscall_error(Errno::from_discriminant(errno), syscallname, "there is no data or no room in the pipes right now, try again later")
+        }
+    }
 
-      0 // success
-    }  
-        
     //------------------GETDENTS SYSCALL------------------
 
     pub fn getdents_syscall(&self, fd: i32, dirp: *mut u8, bufsize: u32)-> i32 {
@@ -1691,6 +2852,8 @@ impl Cage {
                             }
                             // update file position
                             normalfile_filedesc_obj.position = interface::rust_min(position + count, dir_inode_obj.filename_to_inode_dict.len());
+                            drop(metadata);
+                            self._mark_accessed(normalfile_filedesc_obj.inode);
 
                             interface::pack_dirents(vec, dirp);
                             bufcount as i32 // return the number of bytes written
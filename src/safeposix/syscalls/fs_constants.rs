@@ -17,6 +17,9 @@ pub const ROOTDIRECTORYINODE: usize = 1;
 pub const STREAMINODE: usize = 2;
 
 pub const PIPE_CAPACITY: usize = 65536;
+//bounds on what F_SETPIPE_SZ can resize a pipe to, the maximum being like linux's default /proc/sys/fs/pipe-max-size
+pub const PIPE_MIN_SIZE: usize = 4096;
+pub const PIPE_MAX_SIZE: usize = 1048576;
 
 pub const F_OK: u32 = 0;
 pub const X_OK: u32 = 1;
@@ -38,8 +41,18 @@ pub const O_NONBLOCK: i32 = 0o4000;
 pub const O_SYNC: i32 = 0o10000;
 // O_FSYNC=O_SYNC
 pub const O_ASYNC: i32 = 0o20000;
+pub const O_NOFOLLOW: i32 = 0o400000;
 pub const O_CLOEXEC: i32 = 0o2000000;
 
+//how many symbolic links a path may go through before it's taken to loop, like linux
+pub const MAXSYMLINKS: usize = 40;
+
+pub const AT_FDCWD: i32 = -100;
+pub const AT_SYMLINK_NOFOLLOW: i32 = 0x100;
+//nanoseconds values which have utimensat set a time to now, or leave it as it is
+pub const UTIME_NOW: u64 = (1 << 30) - 1;
+pub const UTIME_OMIT: u64 = (1 << 30) - 2;
+
 pub const DEFAULTTIME: u64 = 1323630836;
 
 //Standard flag combinations
@@ -56,6 +69,13 @@ pub const S_IRWXO: u32 = 0o007;
 pub const S_IROTH: u32 = 0o004;
 pub const S_IWOTH: u32 = 0o002;
 pub const S_IXOTH: u32 = 0o001;
+pub const S_ISUID: u32 = 0o4000;
+pub const S_ISGID: u32 = 0o2000;
+pub const S_ISVTX: u32 = 0o1000; //sticky
+pub const S_IALLUGO: u32 = 0o7777; //the bits chmod can set
+
+//what the character devices under /dev are made with, readable and writable by everyone
+pub const DEVICE_MODE: u32 = 0o666;
 
 //Commands for FCNTL
 pub const F_DUPFD: i32= 0;
@@ -76,6 +96,17 @@ pub const F_GETSIG: i32 = 11;
 pub const F_SETLEASE: i32 = 1024;
 pub const F_GETLEASE: i32 = 1025;
 pub const F_NOTIFY: i32 = 1026;
+pub const F_SETPIPE_SZ: i32 = 1031;
+pub const F_GETPIPE_SZ: i32 = 1032;
+
+//Flags for F_GETFD and F_SETFD
+pub const FD_CLOEXEC: i32 = 1;
+
+//Flags for splice and tee
+pub const SPLICE_F_MOVE: u32 = 1;
+pub const SPLICE_F_NONBLOCK: u32 = 2;
+pub const SPLICE_F_MORE: u32 = 4;
+pub const SPLICE_F_GIFT: u32 = 8;
 
 //File types for open/stat etc.
 pub const S_IFBLK: i32 = 0o60000;
@@ -1,5 +1,1685 @@
+#![allow(dead_code)]
 // Network related system calls
 
 use crate::interface;
+use crate::safeposix::cage::{*, FileDescriptor::*};
+use crate::safeposix::filesystem::*;
 
 use super::net_constants::*;
+use super::fs_constants::*;
+
+//what accept got, either a connection from within lind or one from the host
+enum AcceptedConnection {
+    Emulated(interface::PendingConnection),
+    Host(interface::Socket, interface::GenSockaddr),
+}
+
+impl Cage {
+
+    //------------------------------------SOCKET SYSCALL------------------------------------
+
+    pub fn socket_syscall(&self, domain: i32, socktype: i32, protocol: i32) -> i32 {
+        let real_socktype = socktype & SOCK_TYPEMASK;
+
+        if socktype & !(SOCK_TYPEMASK | SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+            return syscall_error(Errno::EINVAL, "socket", "unknown flags were passed in the socket type");
+        }
+
+        match domain {
+            AF_UNIX | AF_INET | AF_INET6 => {}
+            _ => {return syscall_error(Errno::EAFNOSUPPORT, "socket", "the socket domain is not supported");}
+        }
+
+        match real_socktype {
+            SOCK_STREAM => {
+                if protocol != IPPROTO_IP && (domain == AF_UNIX || protocol != IPPROTO_TCP) {
+                    return syscall_error(Errno::EPROTONOSUPPORT, "socket", "the protocol is not supported for stream sockets");
+                }
+            }
+            SOCK_DGRAM => {
+                if protocol != IPPROTO_IP && (domain == AF_UNIX || protocol != IPPROTO_UDP) {
+                    return syscall_error(Errno::EPROTONOSUPPORT, "socket", "the protocol is not supported for datagram sockets");
+                }
+            }
+            _ => {return syscall_error(Errno::ESOCKTNOSUPPORT, "socket", "the socket type is not supported");}
+        }
+
+        let flags = if socktype & SOCK_NONBLOCK != 0 {O_NONBLOCK} else {0};
+        let cloexec = socktype & SOCK_CLOEXEC != 0;
+
+        let mut fdtable = self.filedescriptortable.write().unwrap();
+
+        let thisfd = if let Some(fd) = self.get_next_fd(None, Some(&fdtable)) {
+            fd
+        } else {
+            return syscall_error(Errno::ENFILE, "socket", "no available file descriptor number could be found");
+        };
+
+        //unix sockets are always emulated, while inet sockets are backed by a host socket for their whole lifetime
+        let socketobjectid = if domain == AF_UNIX {
+            None
+        } else {
+            let sockobj = match interface::Socket::new(domain, real_socktype, protocol) {
+                Ok(sockobj) => sockobj,
+                Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "socket", "the host could not create the socket");}
+            };
+            if let Some(id) = add_socket_object(sockobj) {
+                Some(id)
+            } else {
+                return syscall_error(Errno::ENFILE, "socket", "no available socket object id could be found");
+            }
+        };
+
+        let newfd = Socket(SocketDesc {
+            mode: S_IFSOCK | 0o666, domain: domain, socktype: real_socktype, protocol: protocol,
+            options: 0, tcpoptions: 0, linger: 0, sndbuf: 0, rcvbuf: 0, state: ConnState::NOTCONNECTED, shutdown: interface::RustAtomicI32::new(0), flags: flags,
+            localaddr: None, remoteaddr: None, socketobjectid: socketobjectid,
+            connection: None, binding: None,
+            advlock: interface::AdvisoryLock::new()
+        });
+        fdtable.insert(thisfd, FdEntry::new(newfd, cloexec));
+
+        thisfd //socket returns the new file descriptor
+    }
+
+    //------------------------------------SOCKETPAIR SYSCALL------------------------------------
+
+    pub fn socketpair_syscall(&self, domain: i32, socktype: i32, protocol: i32, sv: &mut SockPair) -> i32 {
+        let real_socktype = socktype & SOCK_TYPEMASK;
+
+        if socktype & !(SOCK_TYPEMASK | SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+            return syscall_error(Errno::EINVAL, "socketpair", "unknown flags were passed in the socket type");
+        }
+
+        match domain {
+            AF_UNIX => {}
+            AF_INET | AF_INET6 => {return syscall_error(Errno::EOPNOTSUPP, "socketpair", "socket pairs can only be made in the unix domain");}
+            _ => {return syscall_error(Errno::EAFNOSUPPORT, "socketpair", "the socket domain is not supported");}
+        }
+
+        if real_socktype != SOCK_STREAM && real_socktype != SOCK_DGRAM {
+            return syscall_error(Errno::ESOCKTNOSUPPORT, "socketpair", "the socket type is not supported");
+        }
+        if protocol != IPPROTO_IP {
+            return syscall_error(Errno::EPROTONOSUPPORT, "socketpair", "the protocol is not supported for unix sockets");
+        }
+
+        let flags = if socktype & SOCK_NONBLOCK != 0 {O_NONBLOCK} else {0};
+        let cloexec = socktype & SOCK_CLOEXEC != 0;
+
+        //the two ends are connected to each other from the start, and neither has a name
+        let (end1, end2) = if real_socktype == SOCK_STREAM {
+            interface::EmulatedConnection::new_pair(EMULATEDBUFFERSIZE)
+        } else {
+            interface::EmulatedConnection::new_datagram_pair(EMULATEDBUFFERSIZE)
+        };
+        let unnamed = interface::GenSockaddr::new_unix("");
+
+        let mut fdtable = self.filedescriptortable.write().unwrap();
+        let mut fds = [0; 2];
+
+        for (index, connection) in vec![end1, end2].into_iter().enumerate() {
+            let thisfd = if let Some(fd) = self.get_next_fd(None, Some(&fdtable)) {
+                fd
+            } else {
+                if index == 1 {fdtable.remove(&fds[0]);}
+                return syscall_error(Errno::ENFILE, "socketpair", "no available file descriptor number could be found");
+            };
+
+            let newfd = Socket(SocketDesc {
+                mode: S_IFSOCK | 0o666, domain: domain, socktype: real_socktype, protocol: protocol,
+                options: 0, tcpoptions: 0, linger: 0, sndbuf: 0, rcvbuf: 0, state: ConnState::CONNECTED, shutdown: interface::RustAtomicI32::new(0), flags: flags,
+                localaddr: Some(unnamed), remoteaddr: Some(unnamed), socketobjectid: None,
+                connection: Some(connection), binding: None,
+                advlock: interface::AdvisoryLock::new()
+            });
+            fdtable.insert(thisfd, FdEntry::new(newfd, cloexec));
+            fds[index] = thisfd;
+        }
+
+        sv.sock1 = fds[0];
+        sv.sock2 = fds[1];
+        0 //socketpair has succeeded
+    }
+
+    //gets the host socket backing a socket descriptor
+    fn _socket_object(sockfdobj: &SocketDesc) -> interface::RustRfc<interface::Socket> {
+        let sockobjid = sockfdobj.socketobjectid.unwrap();
+        SOCKET_OBJECT_TABLE.read().unwrap().get(&sockobjid).unwrap().clone()
+    }
+
+    fn _emulated_listener(sockfdobj: &SocketDesc) -> Option<interface::RustRfc<interface::EmulatedListener>> {
+        match &sockfdobj.binding {
+            Some(interface::EmulatedBinding {bound: interface::BoundSocket::Stream(listener), ..}) => Some(listener.clone()),
+            _ => None
+        }
+    }
+
+    fn _datagram_queue(sockfdobj: &SocketDesc) -> Option<interface::RustRfc<interface::DatagramQueue>> {
+        match &sockfdobj.binding {
+            Some(interface::EmulatedBinding {bound: interface::BoundSocket::Datagram(queue), ..}) => Some(queue.clone()),
+            _ => None
+        }
+    }
+
+    //a listening socket bound to anything but loopback must also accept connections coming from outside lind
+    fn _host_listening(sockfdobj: &SocketDesc) -> bool {
+        sockfdobj.socketobjectid.is_some() && match sockfdobj.localaddr {
+            Some(localaddr) => !localaddr.is_loopback(),
+            None => false
+        }
+    }
+
+    //finds the lind socket, if any, that a connection or datagram sent to an inet address would reach without leaving lind
+    fn _lookup_local(socktype: i32, addr: &interface::GenSockaddr) -> Option<interface::BoundSocket> {
+        if !(addr.is_loopback() || addr.is_unspecified()) {return None;}
+
+        let mut loopback = interface::GenSockaddr::new_loopback(addr.get_family()).unwrap();
+        loopback.set_port(addr.port());
+        let mut wildcard = interface::GenSockaddr::new_unspecified(addr.get_family()).unwrap();
+        wildcard.set_port(addr.port());
+
+        //a socket bound to exactly the address wins over one bound to every address
+        for candidate in [*addr, loopback, wildcard].iter() {
+            if let Some(bound) = interface::EmulatedBinding::lookup(socktype, interface::BindKey::inet(candidate)) {
+                return Some(bound);
+            }
+        }
+        None
+    }
+
+    //finds the socket bound to the socket file at a unix address
+    fn _resolve_unix(&self, addr: &interface::GenSockaddr, socktype: i32, callname: &str) -> Result<interface::BoundSocket, i32> {
+        let path = addr.path();
+        if path.len() == 0 {
+            return Err(syscall_error(Errno::EINVAL, callname, "unnamed and abstract unix socket addresses are not supported"));
+        }
+        let metadata = FS_METADATA.read().unwrap();
+        let truepath = self._resolve_path(path, true, Some(&metadata), callname)?;
+
+        if let Some(inodenum) = metawalk(truepath.as_path(), Some(&metadata)) {
+            //like linux, connecting to a socket file takes write permission on it
+            if !self._permits(metadata.inodetable.get(&inodenum).unwrap(), W_OK) {
+                return Err(syscall_error(Errno::EACCES, callname, "write permission is denied for the socket file"));
+            }
+            if let Inode::Socket(_) = metadata.inodetable.get(&inodenum).unwrap() {
+                interface::EmulatedBinding::lookup(socktype, interface::BindKey::Unix(inodenum)).ok_or_else(||
+                    syscall_error(Errno::ECONNREFUSED, callname, "no socket of the right type is bound to the socket file"))
+            } else {
+                Err(syscall_error(Errno::ECONNREFUSED, callname, "the path does not refer to a socket file"))
+            }
+        } else {
+            Err(syscall_error(Errno::ENOENT, callname, "the socket file does not exist"))
+        }
+    }
+
+    //the address a datagram from this socket appears to come from
+    fn _source_addr(sockfdobj: &SocketDesc) -> interface::GenSockaddr {
+        match sockfdobj.localaddr {
+            //a socket bound to every address sends from loopback when talking within lind
+            Some(localaddr) if localaddr.is_unspecified() => {
+                let mut loopback = interface::GenSockaddr::new_loopback(localaddr.get_family()).unwrap();
+                loopback.set_port(localaddr.port());
+                loopback
+            }
+            Some(localaddr) => localaddr,
+            None => interface::GenSockaddr::new_unspecified(sockfdobj.domain as u16).unwrap()
+        }
+    }
+
+    fn _fill_addr(addr: &mut Option<&mut interface::GenSockaddr>, srcaddr: interface::GenSockaddr) {
+        if let Some(addrref) = addr {
+            **addrref = srcaddr;
+        }
+    }
+
+    //------------------------------------BIND SYSCALL------------------------------------
+
+    pub fn bind_syscall(&self, fd: i32, localaddr: &interface::GenSockaddr) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let mut filedesc_enum = wrappedfd.write().unwrap();
+
+            if let Socket(ref mut sockfdobj) = &mut *filedesc_enum {
+                self._bind_helper(sockfdobj, localaddr, "bind")
+            } else {
+                syscall_error(Errno::ENOTSOCK, "bind", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "bind", "invalid file descriptor")
+        }
+    }
+
+    fn _bind_helper(&self, sockfdobj: &mut SocketDesc, localaddr: &interface::GenSockaddr, callname: &str) -> i32 {
+        if localaddr.get_family() as i32 != sockfdobj.domain {
+            return syscall_error(Errno::EAFNOSUPPORT, callname, "the address family does not match the domain of the socket");
+        }
+
+        if sockfdobj.localaddr.is_some() {
+            return syscall_error(Errno::EINVAL, callname, "the socket is already bound to an address");
+        }
+
+        if sockfdobj.domain == AF_UNIX {
+            return self._bind_unix(sockfdobj, localaddr, callname);
+        }
+
+        let sockobj = Self::_socket_object(sockfdobj);
+        if let Err(errno) = sockobj.bind(localaddr) {
+            return syscall_error(Errno::from_discriminant(errno), callname, "the host could not bind the socket");
+        }
+
+        //ask the host which address we actually got, as binding to port 0 picks an ephemeral port
+        Self::_record_host_binding(sockfdobj, &sockobj, callname)
+    }
+
+    //creates the socket file that other unix sockets find this one by
+    fn _bind_unix(&self, sockfdobj: &mut SocketDesc, localaddr: &interface::GenSockaddr, callname: &str) -> i32 {
+        let path = localaddr.path();
+        if path.len() == 0 {
+            return syscall_error(Errno::EINVAL, callname, "unnamed and abstract unix socket addresses are not supported");
+        }
+        let inodenum = {
+            let mut mutmetadata = FS_METADATA.write().unwrap();
+            //like linux, binding to a link fails rather than follows it
+            let truepath = match self._resolve_path(path, false, Some(&mutmetadata), callname) {
+                Ok(truepath) => truepath,
+                Err(e) => {return e;}
+            };
+
+            match metawalkandparent(truepath.as_path(), Some(&mutmetadata)) {
+                (None, None) => {
+                    return syscall_error(Errno::ENOENT, callname, "a directory component in the socket path does not exist");
+                }
+
+                (Some(_), ..) => {
+                    return syscall_error(Errno::EADDRINUSE, callname, "a file already exists at the socket path");
+                }
+
+                (None, Some(pardirinode)) => {
+                    if !self._permits(mutmetadata.inodetable.get(&pardirinode).unwrap(), W_OK | X_OK) {
+                        return syscall_error(Errno::EACCES, callname, "write permission is denied for the directory the socket file would be in");
+                    }
+                    let filename = truepath.file_name().unwrap().to_str().unwrap().to_string(); //for now we assume this is sane, but maybe this should be checked later
+
+                    let time = interface::timestamp(); //We do a real timestamp now
+                    let newgid = self._new_inode_gid(mutmetadata.inodetable.get(&pardirinode).unwrap());
+                    let newinode = Inode::Socket(SocketInode {
+                        size: 0, uid: self.geteuid_syscall() as u32, gid: newgid,
+                        mode: S_IFSOCK as u32 | self._apply_umask(S_IRWXA), linkcount: 1, refcount: 0,
+                        atime: time, ctime: time, mtime: time
+                    });
+
+                    let newinodenum = mutmetadata.nextinode;
+                    mutmetadata.nextinode += 1;
+                    if let Inode::Dir(parentdir) = mutmetadata.inodetable.get_mut(&pardirinode).unwrap() {
+                        parentdir.filename_to_inode_dict.insert(filename, newinodenum);
+                        parentdir.linkcount += 1;
+                    } //insert a reference to the file in the parent directory
+                    mutmetadata.inodetable.get_mut(&pardirinode).unwrap().mark_modified();
+                    mutmetadata.inodetable.insert(newinodenum, newinode);
+
+                    persist_metadata(&mutmetadata);
+                    newinodenum
+                }
+            }
+        };
+
+        let registerret = Self::_register_binding(sockfdobj, interface::BindKey::Unix(inodenum), callname);
+        if registerret != 0 {return registerret;}
+
+        sockfdobj.localaddr = Some(*localaddr);
+        0 //bind has succeeded
+    }
+
+    //makes a bound socket reachable by other lind sockets
+    fn _register_binding(sockfdobj: &mut SocketDesc, key: interface::BindKey, callname: &str) -> i32 {
+        let bound = if sockfdobj.socktype == SOCK_STREAM {
+            interface::BoundSocket::Stream(interface::RustRfc::new(interface::EmulatedListener::new()))
+        } else {
+            interface::BoundSocket::Datagram(interface::RustRfc::new(interface::DatagramQueue::new(EMULATEDBUFFERSIZE)))
+        };
+
+        let reuseport = sockfdobj.options & (1 << SO_REUSEPORT) != 0;
+        match interface::EmulatedBinding::register(sockfdobj.socktype, key, bound, reuseport) {
+            Ok(binding) => {
+                sockfdobj.binding = Some(binding);
+                Self::_apply_buffer_sizes(sockfdobj);
+                0
+            }
+            Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the address is already in use by another lind socket")
+        }
+    }
+
+    //records the address the host has bound a socket to, registering it if other lind sockets can reach it through loopback
+    fn _record_host_binding(sockfdobj: &mut SocketDesc, sockobj: &interface::Socket, callname: &str) -> i32 {
+        let boundaddr = match sockobj.getsockname() {
+            Ok(boundaddr) => boundaddr,
+            Err(errno) => {return syscall_error(Errno::from_discriminant(errno), callname, "the host could not report the bound address");}
+        };
+
+        if sockfdobj.binding.is_none() && (boundaddr.is_loopback() || boundaddr.is_unspecified()) {
+            let registerret = Self::_register_binding(sockfdobj, interface::BindKey::inet(&boundaddr), callname);
+            if registerret != 0 {return registerret;}
+        }
+
+        sockfdobj.localaddr = Some(boundaddr);
+        0
+    }
+
+    //binds a socket that is being used without an explicit bind to the unspecified address and an ephemeral port
+    fn _implicit_bind(&self, sockfdobj: &mut SocketDesc, callname: &str) -> i32 {
+        //unbound unix sockets just stay unnamed
+        if sockfdobj.localaddr.is_some() || sockfdobj.domain == AF_UNIX {return 0;}
+
+        let unspecified = if let Some(addr) = interface::GenSockaddr::new_unspecified(sockfdobj.domain as u16) {
+            addr
+        } else {
+            return syscall_error(Errno::EAFNOSUPPORT, callname, "the socket domain is not supported");
+        };
+        self._bind_helper(sockfdobj, &unspecified, callname)
+    }
+
+    //like linux, sending on an unbound inet datagram socket binds it first; that needs the descriptor exclusively,
+    //so it is a step of its own, leaving the send itself, which may block, to be done under a shared lock
+    pub fn _bind_before_send(&self, wrappedfd: &interface::RustLock<FileDescriptor>, callname: &str) -> i32 {
+        let needsbind = match &*wrappedfd.read().unwrap() {
+            Socket(sockfdobj) => sockfdobj.socktype == SOCK_DGRAM && sockfdobj.domain != AF_UNIX && sockfdobj.localaddr.is_none(),
+            _ => false
+        };
+        if !needsbind {return 0;}
+
+        if let Socket(ref mut sockfdobj) = &mut *wrappedfd.write().unwrap() {
+            return self._implicit_bind(sockfdobj, callname);
+        }
+        0
+    }
+
+    //------------------------------------LISTEN SYSCALL------------------------------------
+
+    pub fn listen_syscall(&self, fd: i32, backlog: i32) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let mut filedesc_enum = wrappedfd.write().unwrap();
+
+            if let Socket(ref mut sockfdobj) = &mut *filedesc_enum {
+                if sockfdobj.socktype != SOCK_STREAM {
+                    return syscall_error(Errno::EOPNOTSUPP, "listen", "the socket is not of a type that supports listen");
+                }
+
+                match sockfdobj.state {
+                    ConnState::CONNECTED | ConnState::CONNECTING => {
+                        return syscall_error(Errno::EINVAL, "listen", "the socket is already connected");
+                    }
+                    //listening again only changes the backlog, which the host takes care of
+                    ConnState::LISTEN | ConnState::NOTCONNECTED => {}
+                }
+
+                if sockfdobj.domain == AF_UNIX && sockfdobj.localaddr.is_none() {
+                    return syscall_error(Errno::EINVAL, "listen", "unix sockets must be bound before they can listen");
+                }
+
+                //listening on an unbound inet socket binds it to an ephemeral port
+                let bindret = self._implicit_bind(sockfdobj, "listen");
+                if bindret != 0 {return bindret;}
+
+                if let Some(listener) = Self::_emulated_listener(sockfdobj) {
+                    listener.listen();
+                }
+
+                //connections from within lind go through the emulated listener, the host only has to
+                //listen if connections can come from outside lind
+                if Self::_host_listening(sockfdobj) {
+                    let sockobj = Self::_socket_object(sockfdobj);
+                    if let Err(errno) = sockobj.listen(backlog) {
+                        return syscall_error(Errno::from_discriminant(errno), "listen", "the host could not listen on the socket");
+                    }
+                }
+                sockfdobj.state = ConnState::LISTEN;
+                0 //listen has succeeded
+            } else {
+                syscall_error(Errno::ENOTSOCK, "listen", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "listen", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------CONNECT SYSCALL------------------------------------
+
+    pub fn connect_syscall(&self, fd: i32, remoteaddr: &interface::GenSockaddr) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let mut filedesc_enum = wrappedfd.write().unwrap();
+
+            if let Socket(ref mut sockfdobj) = &mut *filedesc_enum {
+                if remoteaddr.get_family() as i32 != sockfdobj.domain {
+                    return syscall_error(Errno::EAFNOSUPPORT, "connect", "the address family does not match the domain of the socket");
+                }
+
+                let connectret = if sockfdobj.socktype == SOCK_STREAM {
+                    self._connect_stream(sockfdobj, remoteaddr)
+                } else {
+                    self._connect_datagram(sockfdobj, remoteaddr)
+                };
+                if connectret != 0 {return connectret;}
+
+                sockfdobj.remoteaddr = Some(*remoteaddr);
+                sockfdobj.state = ConnState::CONNECTED;
+                0 //connect has succeeded
+            } else {
+                syscall_error(Errno::ENOTSOCK, "connect", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "connect", "invalid file descriptor")
+        }
+    }
+
+    fn _connect_stream(&self, sockfdobj: &mut SocketDesc, remoteaddr: &interface::GenSockaddr) -> i32 {
+        let nonblocking = sockfdobj.flags & O_NONBLOCK != 0;
+
+        //the host knows how far an earlier nonblocking connect has got, so we just ask it again
+        if sockfdobj.state == ConnState::CONNECTING {
+            return match Self::_socket_object(sockfdobj).connect(remoteaddr, true) {
+                Ok(()) => {
+                    sockfdobj.state = ConnState::CONNECTED;
+                    0
+                }
+                Err(errno) if errno == Errno::EALREADY as i32 => {
+                    syscall_error(Errno::EALREADY, "connect", "the connection is still being made")
+                }
+                Err(errno) if errno == Errno::EISCONN as i32 => {
+                    sockfdobj.state = ConnState::CONNECTED;
+                    syscall_error(Errno::EISCONN, "connect", "the socket is already connected")
+                }
+                Err(errno) => {
+                    sockfdobj.state = ConnState::NOTCONNECTED;
+                    sockfdobj.remoteaddr = None;
+                    syscall_error(Errno::from_discriminant(errno), "connect", "the host could not connect the socket")
+                }
+            };
+        }
+
+        if sockfdobj.state != ConnState::NOTCONNECTED {
+            return syscall_error(Errno::EISCONN, "connect", "the socket is already connected or listening");
+        }
+
+        let bound = if sockfdobj.domain == AF_UNIX {
+            match self._resolve_unix(remoteaddr, SOCK_STREAM, "connect") {
+                Ok(bound) => Some(bound),
+                Err(e) => {return e;}
+            }
+        } else {
+            Self::_lookup_local(SOCK_STREAM, remoteaddr)
+        };
+
+        if let Some(interface::BoundSocket::Stream(listener)) = bound {
+            //inet sockets connecting within lind still get an ephemeral port, so that they have an address like any other
+            if sockfdobj.domain != AF_UNIX && sockfdobj.localaddr.is_none() {
+                let loopback = interface::GenSockaddr::new_loopback(sockfdobj.domain as u16).unwrap();
+                let bindret = self._bind_helper(sockfdobj, &loopback, "connect");
+                if bindret != 0 {return bindret;}
+            }
+
+            let (clientend, serverend) = interface::EmulatedConnection::new_pair(EMULATEDBUFFERSIZE);
+            let pending = interface::PendingConnection {connection: serverend, remoteaddr: Self::_source_addr(sockfdobj)};
+            if let Err(errno) = listener.connect(pending) {
+                return syscall_error(Errno::from_discriminant(errno), "connect", "the socket bound to the address is not listening");
+            }
+            sockfdobj.connection = Some(clientend);
+            Self::_apply_buffer_sizes(sockfdobj);
+        } else {
+            let sockobj = Self::_socket_object(sockfdobj);
+            let inprogress = match sockobj.connect(remoteaddr, nonblocking) {
+                Ok(()) => false,
+                Err(errno) if errno == Errno::EINPROGRESS as i32 => true,
+                Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "connect", "the host could not connect the socket");}
+            };
+
+            //connecting implicitly binds the socket on the host, so we record where
+            let recordret = Self::_record_host_binding(sockfdobj, &sockobj, "connect");
+            if recordret != 0 {return recordret;}
+
+            //the connect carries on in the background, poll reports when it is done and SO_ERROR how it went
+            if inprogress {
+                sockfdobj.state = ConnState::CONNECTING;
+                sockfdobj.remoteaddr = Some(*remoteaddr);
+                return syscall_error(Errno::EINPROGRESS, "connect", "the connection can't be made right away");
+            }
+        }
+        0
+    }
+
+    //whether a nonblocking connect on the host has finished successfully
+    fn _host_connect_finished(sockfdobj: &SocketDesc) -> bool {
+        Self::_socket_object(sockfdobj).poll(POLLOUT, 0) & (POLLOUT | POLLERR | POLLHUP) == POLLOUT
+    }
+
+    fn _is_nonblocking(sockfdobj: &SocketDesc, flags: i32) -> bool {
+        flags & MSG_DONTWAIT != 0 || sockfdobj.flags & O_NONBLOCK != 0
+    }
+
+    //datagram sockets may be connected again to change their default peer
+    fn _connect_datagram(&self, sockfdobj: &mut SocketDesc, remoteaddr: &interface::GenSockaddr) -> i32 {
+        if sockfdobj.domain == AF_UNIX {
+            if let Err(e) = self._resolve_unix(remoteaddr, SOCK_DGRAM, "connect") {
+                return e;
+            }
+        } else if Self::_lookup_local(SOCK_DGRAM, remoteaddr).is_some() {
+            //the peer needs somewhere to send replies to
+            let bindret = self._implicit_bind(sockfdobj, "connect");
+            if bindret != 0 {return bindret;}
+        } else {
+            //connecting a udp socket only sets its peer, so it never has to wait
+            let sockobj = Self::_socket_object(sockfdobj);
+            if let Err(errno) = sockobj.connect(remoteaddr, false) {
+                return syscall_error(Errno::from_discriminant(errno), "connect", "the host could not connect the socket");
+            }
+            if sockfdobj.localaddr.is_none() {
+                let recordret = Self::_record_host_binding(sockfdobj, &sockobj, "connect");
+                if recordret != 0 {return recordret;}
+            }
+        }
+        0
+    }
+
+    //------------------------------------ACCEPT SYSCALL------------------------------------
+
+    pub fn accept_syscall(&self, fd: i32, addr: &mut interface::GenSockaddr) -> i32 {
+        //we don't hold the fdtable lock while waiting for a connection, but we keep the descriptor alive
+        let wrappedfd = if let Some(wrappedfd) = self.filedescriptortable.read().unwrap().get(&fd) {
+            wrappedfd.clone()
+        } else {
+            return syscall_error(Errno::EBADF, "accept", "invalid file descriptor");
+        };
+
+        //we copy out what we wait on and let go of the descriptor, so that calls needing it exclusively don't wait on us
+        let (listener, hostsock, nonblocking, listenaddr, domain, socktype, protocol, inherited) = {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                if sockfdobj.socktype != SOCK_STREAM {
+                    return syscall_error(Errno::EOPNOTSUPP, "accept", "the socket is not of a type that supports accept");
+                }
+                if sockfdobj.state != ConnState::LISTEN {
+                    return syscall_error(Errno::EINVAL, "accept", "the socket is not listening for connections");
+                }
+
+                let nonblocking = sockfdobj.flags & O_NONBLOCK != 0;
+                let listener = Self::_emulated_listener(sockfdobj);
+                let hostsock = if Self::_host_listening(sockfdobj) {Some(Self::_socket_object(sockfdobj))} else {None};
+                if listener.is_none() && hostsock.is_none() {
+                    return syscall_error(Errno::EINVAL, "accept", "the socket is not listening for connections");
+                }
+
+                //like on linux, the new connection starts out with the listening socket's options
+                let inherited = (sockfdobj.options, sockfdobj.tcpoptions, sockfdobj.linger, sockfdobj.sndbuf, sockfdobj.rcvbuf);
+                (listener, hostsock, nonblocking, sockfdobj.localaddr.unwrap(), sockfdobj.domain, sockfdobj.socktype, sockfdobj.protocol, inherited)
+            } else {
+                return syscall_error(Errno::ENOTSOCK, "accept", "file descriptor refers to something other than a socket");
+            }
+        };
+
+        let accepted = loop {
+            let generation = interface::readiness_generation();
+
+            //we only block in the emulated listener or the host's accept when there is nothing else to wait on
+            if let Some(ref listener) = listener {
+                let timeout = if nonblocking || hostsock.is_some() {Some(interface::RustDuration::from_millis(0))} else {None};
+                if let Ok(pending) = listener.accept(timeout) {
+                    break AcceptedConnection::Emulated(pending);
+                }
+            }
+
+            if let Some(ref sockobj) = hostsock {
+                let hostnonblocking = nonblocking || listener.is_some();
+                match sockobj.accept(hostnonblocking) {
+                    Ok((newsockobj, remoteaddr)) => {break AcceptedConnection::Host(newsockobj, remoteaddr);}
+                    Err(errno) if errno == Errno::EAGAIN as i32 && hostnonblocking => {}
+                    Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "accept", "the host could not accept a connection");}
+                }
+            }
+
+            if nonblocking {
+                return syscall_error(Errno::EAGAIN, "accept", "there are no connections waiting to be accepted");
+            }
+
+            //connections can come both from within lind and from the host, so we wait until either might have one
+            hostsock.as_ref().unwrap().wait_with_readiness(POLLIN, generation);
+        };
+
+        let (options, tcpoptions, linger, sndbuf, rcvbuf) = inherited;
+        let (newsockdesc, remoteaddr) = match accepted {
+            AcceptedConnection::Emulated(pending) => {
+                //a connection made within lind was made over loopback, even if we are bound to every address
+                let localaddr = if listenaddr.is_unspecified() {
+                    let mut loopback = interface::GenSockaddr::new_loopback(listenaddr.get_family()).unwrap();
+                    loopback.set_port(listenaddr.port());
+                    loopback
+                } else {
+                    listenaddr
+                };
+
+                (SocketDesc {
+                    mode: S_IFSOCK | 0o666, domain: domain, socktype: socktype, protocol: protocol,
+                    options: options, tcpoptions: tcpoptions, linger: linger, sndbuf: sndbuf, rcvbuf: rcvbuf,
+                    state: ConnState::CONNECTED, shutdown: interface::RustAtomicI32::new(0), flags: 0,
+                    localaddr: Some(localaddr), remoteaddr: Some(pending.remoteaddr), socketobjectid: None,
+                    connection: Some(pending.connection), binding: None,
+                    advlock: interface::AdvisoryLock::new()
+                }, pending.remoteaddr)
+            }
+            AcceptedConnection::Host(newsockobj, remoteaddr) => {
+                let localaddr = match newsockobj.getsockname() {
+                    Ok(localaddr) => localaddr,
+                    Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "accept", "the host could not report the bound address");}
+                };
+
+                let sockobjid = if let Some(id) = add_socket_object(newsockobj) {
+                    id
+                } else {
+                    return syscall_error(Errno::ENFILE, "accept", "no available socket object id could be found");
+                };
+
+                (SocketDesc {
+                    mode: S_IFSOCK | 0o666, domain: domain, socktype: socktype, protocol: protocol,
+                    options: options, tcpoptions: tcpoptions, linger: linger, sndbuf: sndbuf, rcvbuf: rcvbuf,
+                    state: ConnState::CONNECTED, shutdown: interface::RustAtomicI32::new(0), flags: 0,
+                    localaddr: Some(localaddr), remoteaddr: Some(remoteaddr), socketobjectid: Some(sockobjid),
+                    connection: None, binding: None,
+                    advlock: interface::AdvisoryLock::new()
+                }, remoteaddr)
+            }
+        };
+
+        let mut fdtable = self.filedescriptortable.write().unwrap();
+
+        //if we fail here the new socket is dropped along with its descriptor, closing the connection
+        let thisfd = if let Some(newfd) = self.get_next_fd(None, Some(&fdtable)) {
+            newfd
+        } else {
+            if let Some(sockobjid) = newsockdesc.socketobjectid {
+                SOCKET_OBJECT_TABLE.write().unwrap().remove(&sockobjid);
+            }
+            return syscall_error(Errno::ENFILE, "accept", "no available file descriptor number could be found");
+        };
+
+        Self::_apply_buffer_sizes(&newsockdesc);
+        fdtable.insert(thisfd, FdEntry::new(Socket(newsockdesc), false));
+
+        *addr = remoteaddr;
+        thisfd //accept returns the file descriptor of the new connection
+    }
+
+    //------------------------------------SEND SYSCALL------------------------------------
+
+    pub fn send_syscall(&self, fd: i32, buf: *const u8, buflen: usize, flags: i32) -> i32 {
+        //sending may block, so as for receiving we hold neither the fdtable lock nor an exclusive lock on the descriptor
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            let bindret = self._bind_before_send(&wrappedfd, "send");
+            if bindret != 0 {return bindret;}
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                self._send_helper(sockfdobj, buf, buflen, flags, "send")
+            } else {
+                syscall_error(Errno::ENOTSOCK, "send", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "send", "invalid file descriptor")
+        }
+    }
+
+    //shared by send and write on a socket, which must have been through _bind_before_send
+    pub fn _send_helper(&self, sockfdobj: &SocketDesc, buf: *const u8, buflen: usize, flags: i32, callname: &str) -> i32 {
+        if sockfdobj.shutdown.load(interface::RustAtomicOrdering::SeqCst) & SEND_SHUTDOWN != 0 {
+            return syscall_error(Errno::EPIPE, callname, "the socket has been shut down for writing");
+        }
+
+        let nonblocking = Self::_is_nonblocking(sockfdobj, flags);
+
+        if let Some(connection) = &sockfdobj.connection {
+            return match connection.send(buf, buflen, nonblocking) {
+                Ok(bytessent) => bytessent as i32,
+                Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the connection could not be written to"),
+            };
+        }
+
+        //a host connect still in progress is waited on by the host itself
+        if sockfdobj.state != ConnState::CONNECTED && sockfdobj.state != ConnState::CONNECTING {
+            if sockfdobj.socktype == SOCK_DGRAM {
+                return syscall_error(Errno::EDESTADDRREQ, callname, "the socket has no peer address set");
+            }
+            return syscall_error(Errno::ENOTCONN, callname, "the socket is not connected");
+        }
+
+        if sockfdobj.socktype == SOCK_DGRAM {
+            let remoteaddr = sockfdobj.remoteaddr.unwrap();
+            return self._sendto_datagram(sockfdobj, buf, buflen, flags, &remoteaddr, callname);
+        }
+
+        let sockobj = Self::_socket_object(sockfdobj);
+        match sockobj.send(buf, buflen, if nonblocking {flags | MSG_DONTWAIT} else {flags}) {
+            Ok(bytessent) => bytessent as i32,
+            Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the host could not send the data"),
+        }
+    }
+
+    //------------------------------------SENDTO SYSCALL------------------------------------
+
+    pub fn sendto_syscall(&self, fd: i32, buf: *const u8, buflen: usize, flags: i32, dest_addr: &interface::GenSockaddr) -> i32 {
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            let bindret = self._bind_before_send(&wrappedfd, "sendto");
+            if bindret != 0 {return bindret;}
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                //stream sockets can only send to the peer they are connected to, so the address is ignored
+                if sockfdobj.socktype == SOCK_STREAM {
+                    return self._send_helper(sockfdobj, buf, buflen, flags, "sendto");
+                }
+
+                if dest_addr.get_family() as i32 != sockfdobj.domain {
+                    return syscall_error(Errno::EAFNOSUPPORT, "sendto", "the address family does not match the domain of the socket");
+                }
+
+                self._sendto_datagram(sockfdobj, buf, buflen, flags, dest_addr, "sendto")
+            } else {
+                syscall_error(Errno::ENOTSOCK, "sendto", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "sendto", "invalid file descriptor")
+        }
+    }
+
+    fn _sendto_datagram(&self, sockfdobj: &SocketDesc, buf: *const u8, buflen: usize, flags: i32, dest_addr: &interface::GenSockaddr, callname: &str) -> i32 {
+        if sockfdobj.shutdown.load(interface::RustAtomicOrdering::SeqCst) & SEND_SHUTDOWN != 0 {
+            return syscall_error(Errno::EPIPE, callname, "the socket has been shut down for writing");
+        }
+
+        let (queue, maxpayload) = match sockfdobj.domain {
+            AF_UNIX => {
+                match self._resolve_unix(dest_addr, SOCK_DGRAM, callname) {
+                    Ok(interface::BoundSocket::Datagram(queue)) => (Some(queue), EMULATEDBUFFERSIZE),
+                    Ok(interface::BoundSocket::Stream(_)) => {return syscall_error(Errno::EPROTOTYPE, callname, "the socket file belongs to a stream socket");}
+                    Err(e) => {return e;}
+                }
+            }
+            domain => {
+                let queue = match Self::_lookup_local(SOCK_DGRAM, dest_addr) {
+                    Some(interface::BoundSocket::Datagram(queue)) => Some(queue),
+                    _ => None
+                };
+                (queue, if domain == AF_INET6 {UDPMAXPAYLOAD_V6} else {UDPMAXPAYLOAD_V4})
+            }
+        };
+
+        if let Some(queue) = queue {
+            if buflen > maxpayload {
+                return syscall_error(Errno::EMSGSIZE, callname, "the datagram is too large to send");
+            }
+
+            return match queue.deliver(buf, buflen, Self::_source_addr(sockfdobj), Self::_is_nonblocking(sockfdobj, flags)) {
+                Ok(bytessent) => bytessent as i32,
+                Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the datagram could not be delivered"),
+            };
+        }
+
+        let sockobj = Self::_socket_object(sockfdobj);
+        let hostflags = if Self::_is_nonblocking(sockfdobj, flags) {flags | MSG_DONTWAIT} else {flags};
+        match sockobj.sendto(buf, buflen, hostflags, Some(dest_addr)) {
+            Ok(bytessent) => bytessent as i32,
+            Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the host could not send the data"),
+        }
+    }
+
+    //------------------------------------RECV SYSCALL------------------------------------
+
+    pub fn recv_syscall(&self, fd: i32, buf: *mut u8, buflen: usize, flags: i32) -> i32 {
+        //receiving may block, so we hold neither the fdtable lock nor the descriptor meanwhile
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            self._recv_helper(&wrappedfd, buf, buflen, flags, &mut None, "recv")
+        } else {
+            syscall_error(Errno::EBADF, "recv", "invalid file descriptor")
+        }
+    }
+
+    //shared by recv, recvfrom and read on a socket, filling in the sender's address if one is asked for
+    //what we wait on is copied out first, so that calls needing the descriptor exclusively don't wait on us
+    pub fn _recv_helper(&self, wrappedfd: &interface::RustLock<FileDescriptor>, buf: *mut u8, buflen: usize, flags: i32, addr: &mut Option<&mut interface::GenSockaddr>, callname: &str) -> i32 {
+        let peek = flags & MSG_PEEK != 0;
+
+        let (receiver, queue, hostsock, remoteaddr, nonblocking) = {
+            let filedesc_enum = wrappedfd.read().unwrap();
+            let sockfdobj = if let Socket(sockfdobj) = &*filedesc_enum {
+                sockfdobj
+            } else {
+                return syscall_error(Errno::ENOTSOCK, callname, "file descriptor refers to something other than a socket");
+            };
+
+            let receiver = sockfdobj.connection.as_ref().map(|connection| connection.receiver());
+            if receiver.is_none() && sockfdobj.socktype == SOCK_STREAM && sockfdobj.state != ConnState::CONNECTED && sockfdobj.state != ConnState::CONNECTING {
+                return syscall_error(Errno::ENOTCONN, callname, "the socket is not connected");
+            }
+
+            let hostsock = if sockfdobj.socketobjectid.is_some() {Some(Self::_socket_object(sockfdobj))} else {None};
+            (receiver, Self::_datagram_queue(sockfdobj), hostsock, sockfdobj.remoteaddr, Self::_is_nonblocking(sockfdobj, flags))
+        };
+
+        if let Some(receiver) = receiver {
+            return match receiver.read(buf, buflen, peek, nonblocking) {
+                Ok(bytesreceived) => {
+                    Self::_fill_addr(addr, remoteaddr.unwrap());
+                    bytesreceived as i32
+                }
+                Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the connection could not be read from"),
+            };
+        }
+
+        if let Some(queue) = queue {
+            //a bound inet socket may also be sent to from outside lind, and the socket may be shut down while
+            //we wait, so we check on each of them and then wait until any of them might have changed
+            loop {
+                let generation = interface::readiness_generation();
+
+                if let Ok((bytesreceived, srcaddr)) = queue.receive(buf, buflen, peek, Some(interface::RustDuration::from_millis(0))) {
+                    Self::_fill_addr(addr, srcaddr);
+                    return bytesreceived as i32;
+                }
+
+                if let Some(ref sockobj) = hostsock {
+                    match sockobj.recvfrom(buf, buflen, flags | MSG_DONTWAIT) {
+                        Ok((bytesreceived, srcaddr)) => {
+                            if let Some(srcaddr) = srcaddr {Self::_fill_addr(addr, srcaddr);}
+                            return bytesreceived as i32;
+                        }
+                        Err(errno) if errno == Errno::EAGAIN as i32 => {}
+                        Err(errno) => {return syscall_error(Errno::from_discriminant(errno), callname, "the host could not receive data");}
+                    }
+                }
+
+                //once shut down for reading, there is nothing more to wait for
+                let shutdown = match &*wrappedfd.read().unwrap() {
+                    Socket(sockfdobj) => sockfdobj.shutdown.load(interface::RustAtomicOrdering::SeqCst),
+                    _ => 0
+                };
+                if shutdown & RCV_SHUTDOWN != 0 {
+                    return 0;
+                }
+
+                if nonblocking {
+                    return syscall_error(Errno::EAGAIN, callname, "there is no data available right now, try again later");
+                }
+
+                match hostsock {
+                    Some(ref sockobj) => sockobj.wait_with_readiness(POLLIN, generation),
+                    None => interface::wait_for_readiness(generation, None)
+                }
+            }
+        }
+
+        let sockobj = if let Some(sockobj) = hostsock {
+            sockobj
+        } else {
+            return syscall_error(Errno::EINVAL, callname, "the socket is not bound to an address");
+        };
+
+        match sockobj.recvfrom(buf, buflen, if nonblocking {flags | MSG_DONTWAIT} else {flags}) {
+            Ok((bytesreceived, srcaddr)) => {
+                //a connected stream socket's host may not report the sender, but it is always the peer
+                if let Some(srcaddr) = srcaddr.or(remoteaddr) {
+                    Self::_fill_addr(addr, srcaddr);
+                }
+                bytesreceived as i32
+            }
+            Err(errno) => syscall_error(Errno::from_discriminant(errno), callname, "the host could not receive data"),
+        }
+    }
+
+    //------------------------------------RECVFROM SYSCALL------------------------------------
+
+    pub fn recvfrom_syscall(&self, fd: i32, buf: *mut u8, buflen: usize, flags: i32, addr: &mut Option<&mut interface::GenSockaddr>) -> i32 {
+        //receiving may block, so we hold neither the fdtable lock nor the descriptor meanwhile
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            self._recv_helper(&wrappedfd, buf, buflen, flags, addr, "recvfrom")
+        } else {
+            syscall_error(Errno::EBADF, "recvfrom", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------GETSOCKNAME SYSCALL------------------------------------
+
+    pub fn getsockname_syscall(&self, fd: i32, addr: &mut interface::GenSockaddr) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                //an unbound socket reports the unspecified address of its family
+                *addr = match sockfdobj.localaddr {
+                    Some(localaddr) => localaddr,
+                    None => interface::GenSockaddr::new_unspecified(sockfdobj.domain as u16).unwrap()
+                };
+                0 //getsockname has succeeded
+            } else {
+                syscall_error(Errno::ENOTSOCK, "getsockname", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "getsockname", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------GETPEERNAME SYSCALL------------------------------------
+
+    pub fn getpeername_syscall(&self, fd: i32, addr: &mut interface::GenSockaddr) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                if sockfdobj.state == ConnState::CONNECTING && !Self::_host_connect_finished(sockfdobj) {
+                    return syscall_error(Errno::ENOTCONN, "getpeername", "the socket is not connected yet");
+                }
+                if let Some(remoteaddr) = sockfdobj.remoteaddr {
+                    *addr = remoteaddr;
+                    0 //getpeername has succeeded
+                } else {
+                    syscall_error(Errno::ENOTCONN, "getpeername", "the socket is not connected")
+                }
+            } else {
+                syscall_error(Errno::ENOTSOCK, "getpeername", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "getpeername", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------SELECT SYSCALL------------------------------------
+
+    pub fn select_syscall(&self, nfds: i32, mut readfds: Option<&mut interface::FdSet>, mut writefds: Option<&mut interface::FdSet>,
+                          mut exceptfds: Option<&mut interface::FdSet>, mut timeout: Option<&mut interface::TimeVal>) -> i32 {
+        if nfds < 0 || nfds > interface::FD_SET_MAX_FD {
+            return syscall_error(Errno::EINVAL, "select", "the number of file descriptors is negative or too large");
+        }
+
+        //no timeout waits for as long as it takes
+        let total = match &timeout {
+            Some(timeval) => {
+                if timeval.tv_sec < 0 || timeval.tv_usec < 0 || timeval.tv_usec >= 1000000 {
+                    return syscall_error(Errno::EINVAL, "select", "the timeout is invalid");
+                }
+                Some(interface::RustDuration::new(timeval.tv_sec as u64, timeval.tv_usec as u32 * 1000))
+            }
+            None => None
+        };
+
+        //we only report on the descriptors that were asked about, so we need to keep the original sets
+        let emptyset = interface::FdSet::new();
+        let origreadfds = readfds.as_ref().map_or(emptyset, |set| **set);
+        let origwritefds = writefds.as_ref().map_or(emptyset, |set| **set);
+        let origexceptfds = exceptfds.as_ref().map_or(emptyset, |set| **set);
+
+        let starttime = interface::starttimer();
+
+        loop {
+            //anything that changes while we check wakes us right away
+            let generation = interface::readiness_generation();
+            let mut needsrecheck = false;
+            let mut readycount = 0;
+            let mut newreadfds = interface::FdSet::new();
+            let mut newwritefds = interface::FdSet::new();
+            let mut newexceptfds = interface::FdSet::new();
+
+            for fd in 0..nfds {
+                let (wantread, wantwrite, wantexcept) = (origreadfds.is_set(fd), origwritefds.is_set(fd), origexceptfds.is_set(fd));
+                if !(wantread || wantwrite || wantexcept) {continue;}
+
+                let revents = self._poll_fd(fd, &mut needsrecheck, &mut 0);
+                if revents & POLLNVAL != 0 {
+                    return syscall_error(Errno::EBADF, "select", "a file descriptor in one of the sets is not open");
+                }
+
+                //end of file and errors count as readable, since reading won't block
+                if wantread && revents & (POLLIN | POLLHUP | POLLERR) != 0 {
+                    newreadfds.set(fd);
+                    readycount += 1;
+                }
+                if wantwrite && revents & (POLLOUT | POLLERR) != 0 {
+                    newwritefds.set(fd);
+                    readycount += 1;
+                }
+                if wantexcept && revents & POLLPRI != 0 {
+                    newexceptfds.set(fd);
+                    readycount += 1;
+                }
+            }
+
+            let remaining = total.map(|total| total.checked_sub(interface::readtimer(starttime)).unwrap_or(interface::RustDuration::ZERO));
+            let timedout = remaining == Some(interface::RustDuration::ZERO);
+
+            if readycount > 0 || timedout {
+                //like linux, we rewrite the sets to the ready descriptors and the timeout to what is left of it
+                if let Some(set) = readfds.as_mut() {**set = newreadfds;}
+                if let Some(set) = writefds.as_mut() {**set = newwritefds;}
+                if let Some(set) = exceptfds.as_mut() {**set = newexceptfds;}
+                if let (Some(timeval), Some(remaining)) = (timeout.as_mut(), remaining) {
+                    timeval.tv_sec = remaining.as_secs() as i64;
+                    timeval.tv_usec = remaining.subsec_micros() as i64;
+                }
+                return readycount;
+            }
+
+            self._wait_for_readiness(generation, remaining, needsrecheck);
+        }
+    }
+
+    //------------------------------------POLL SYSCALL------------------------------------
+
+    pub fn poll_syscall(&self, fds: &mut [PollStruct], timeout: i32) -> i32 {
+        if fds.len() > MAXFD as usize {
+            return syscall_error(Errno::EINVAL, "poll", "more file descriptors were passed than can be open");
+        }
+
+        let starttime = interface::starttimer();
+
+        loop {
+            //anything that changes while we check wakes us right away
+            let generation = interface::readiness_generation();
+            let mut needsrecheck = false;
+            let mut readycount = 0;
+
+            for pollfd in fds.iter_mut() {
+                pollfd.revents = 0;
+                if pollfd.fd < 0 {continue;} //negative fds are ignored, as a way to leave entries out
+
+                //errors and hangups are reported whether they were asked for or not
+                let revents = self._poll_fd(pollfd.fd, &mut needsrecheck, &mut 0);
+                pollfd.revents = revents & (pollfd.events | POLLERR | POLLHUP | POLLNVAL);
+                if pollfd.revents != 0 {readycount += 1;}
+            }
+
+            if readycount > 0 || timeout == 0 {return readycount;}
+
+            //a negative timeout waits for as long as it takes
+            let remaining = if timeout < 0 {None} else {
+                let elapsed = interface::readtimer(starttime);
+                let total = interface::RustDuration::from_millis(timeout as u64);
+                if elapsed >= total {return 0;}
+                Some(total - elapsed)
+            };
+            self._wait_for_readiness(generation, remaining, needsrecheck);
+        }
+    }
+
+    //------------------------------------EPOLL SYSCALLS------------------------------------
+
+    pub fn epoll_create_syscall(&self, size: i32) -> i32 {
+        //the size is only a hint, but it has to be positive
+        if size <= 0 {
+            return syscall_error(Errno::EINVAL, "epoll_create", "the size must be greater than zero");
+        }
+
+        let mut fdtable = self.filedescriptortable.write().unwrap();
+
+        let thisfd = if let Some(fd) = self.get_next_fd(None, Some(&fdtable)) {
+            fd
+        } else {
+            return syscall_error(Errno::ENFILE, "epoll_create", "no available file descriptor number could be found");
+        };
+
+        let newfd = Epoll(EpollDesc {
+            mode: (S_IRUSR | S_IWUSR) as i32, registered_fds: interface::RustHashMap::new(), registered_descs: interface::RustHashMap::new(),
+            lastready: interface::RustHashMap::new(),
+            advlock: interface::AdvisoryLock::new(), errno: 0, flags: 0
+        });
+        fdtable.insert(thisfd, FdEntry::new(newfd, false));
+
+        thisfd
+    }
+
+    pub fn epoll_ctl_syscall(&self, epfd: i32, op: i32, fd: i32, event: &EpollEvent) -> i32 {
+        let (wrappedepfd, wrappedfd) = {
+            let fdtable = self.filedescriptortable.read().unwrap();
+            match (fdtable.get(&epfd), fdtable.get(&fd)) {
+                (Some(wrappedepfd), Some(wrappedfd)) => (wrappedepfd.clone(), wrappedfd.clone()),
+                _ => {return syscall_error(Errno::EBADF, "epoll_ctl", "invalid file descriptor");}
+            }
+        };
+
+        if epfd == fd {
+            return syscall_error(Errno::EINVAL, "epoll_ctl", "an epoll instance can't watch itself");
+        }
+
+        //regular files are always ready, so like linux we refuse to watch them
+        match &*wrappedfd.read().unwrap() {
+            File(_) => {return syscall_error(Errno::EPERM, "epoll_ctl", "regular files and directories can't be watched");}
+            Epoll(_) => {return syscall_error(Errno::EINVAL, "epoll_ctl", "watching another epoll instance is not supported");}
+            _ => {}
+        }
+
+        let mut epollfd_enum = wrappedepfd.write().unwrap();
+        let epollfdobj = if let Epoll(epollfdobj) = &mut *epollfd_enum {
+            epollfdobj
+        } else {
+            return syscall_error(Errno::EINVAL, "epoll_ctl", "the file descriptor is not an epoll instance");
+        };
+
+        //a registration left behind by a descriptor which has since been closed doesn't count
+        let registered = match epollfdobj.registered_descs.get(&fd) {
+            Some(desc) => wrappedfd.refers_to(desc),
+            None => false
+        };
+
+        match op {
+            EPOLL_CTL_ADD => {
+                if registered {
+                    return syscall_error(Errno::EEXIST, "epoll_ctl", "the file descriptor is already registered");
+                }
+                epollfdobj.registered_fds.insert(fd, EpollEvent {events: event.events, fd: event.fd});
+                epollfdobj.registered_descs.insert(fd, wrappedfd.downgrade());
+            }
+            EPOLL_CTL_MOD => {
+                if !registered {
+                    return syscall_error(Errno::ENOENT, "epoll_ctl", "the file descriptor is not registered");
+                }
+                epollfdobj.registered_fds.insert(fd, EpollEvent {events: event.events, fd: event.fd});
+            }
+            EPOLL_CTL_DEL => {
+                if !registered {
+                    return syscall_error(Errno::ENOENT, "epoll_ctl", "the file descriptor is not registered");
+                }
+                epollfdobj.registered_fds.remove(&fd);
+                epollfdobj.registered_descs.remove(&fd);
+            }
+            _ => {return syscall_error(Errno::EINVAL, "epoll_ctl", "the operation is not supported");}
+        }
+
+        //changing a registration rearms it, so an edge triggered fd which is already ready gets reported
+        epollfdobj.lastready.remove(&fd);
+        0
+    }
+
+    pub fn epoll_wait_syscall(&self, epfd: i32, events: &mut [EpollEvent], maxevents: i32, timeout: i32) -> i32 {
+        let wrappedepfd = if let Some(wrappedepfd) = self.filedescriptortable.read().unwrap().get(&epfd) {
+            wrappedepfd.clone()
+        } else {
+            return syscall_error(Errno::EBADF, "epoll_wait", "invalid file descriptor");
+        };
+
+        if maxevents <= 0 {
+            return syscall_error(Errno::EINVAL, "epoll_wait", "maxevents must be greater than zero");
+        }
+        if events.len() < maxevents as usize {
+            return syscall_error(Errno::EINVAL, "epoll_wait", "the events buffer can't hold maxevents events");
+        }
+
+        let starttime = interface::starttimer();
+
+        loop {
+            //anything that changes while we check wakes us right away
+            let generation = interface::readiness_generation();
+            let mut needsrecheck = false;
+            let mut readycount = 0;
+
+            {
+                let mut epollfd_enum = wrappedepfd.write().unwrap();
+                let epollfdobj = if let Epoll(epollfdobj) = &mut *epollfd_enum {
+                    epollfdobj
+                } else {
+                    return syscall_error(Errno::EINVAL, "epoll_wait", "the file descriptor is not an epoll instance");
+                };
+
+                let mut closedfds = vec![];
+                for (fd, event) in epollfdobj.registered_fds.iter_mut() {
+                    if readycount == maxevents {break;}
+
+                    //we aren't told about closes, so forget descriptors once they are gone, even if another
+                    //descriptor has been given their fd number since
+                    let current = self.filedescriptortable.read().unwrap().get(fd).cloned();
+                    let wrappedfd = match current {
+                        Some(wrappedfd) if wrappedfd.refers_to(&epollfdobj.registered_descs[fd]) => wrappedfd,
+                        _ => {
+                            closedfds.push(*fd);
+                            continue;
+                        }
+                    };
+
+                    let mut arrivals = 0;
+                    let revents = Self::_poll_descriptor(&wrappedfd, &mut needsrecheck, &mut arrivals);
+
+                    //errors and hangups are reported whether they were asked for or not
+                    let mut ready = revents as u32 & (event.events | EPOLLERR | EPOLLHUP);
+                    //a disabled oneshot registration reports nothing until it is modified
+                    if event.events & EPOLLONESHOT != 0 && event.events & !(EPOLLONESHOT | EPOLLET) == 0 {ready = 0;}
+
+                    //edge triggered registrations only report events which weren't ready the last time we looked,
+                    //except that new data for a descriptor which was already readable is reported again
+                    if event.events & EPOLLET != 0 {
+                        if let Some((lastready, lastarrivals)) = epollfdobj.lastready.insert(*fd, (ready, arrivals)) {
+                            let rearmed = if lastarrivals != arrivals {EPOLLIN} else {0};
+                            ready &= !(lastready & !rearmed);
+                        }
+                    }
+                    if ready == 0 {continue;}
+
+                    events[readycount as usize] = EpollEvent {events: ready, fd: event.fd};
+                    readycount += 1;
+
+                    if event.events & EPOLLONESHOT != 0 {
+                        event.events &= EPOLLONESHOT | EPOLLET;
+                    }
+                }
+
+                for fd in closedfds {
+                    epollfdobj.registered_fds.remove(&fd);
+                    epollfdobj.registered_descs.remove(&fd);
+                    epollfdobj.lastready.remove(&fd);
+                }
+            }
+
+            if readycount > 0 || timeout == 0 {return readycount;}
+
+            //a negative timeout waits for as long as it takes
+            let remaining = if timeout < 0 {None} else {
+                let elapsed = interface::readtimer(starttime);
+                let total = interface::RustDuration::from_millis(timeout as u64);
+                if elapsed >= total {return 0;}
+                Some(total - elapsed)
+            };
+            self._wait_for_readiness(generation, remaining, needsrecheck);
+        }
+    }
+
+    //blocks until something might have become ready, checking back regularly on what we aren't woken for
+    pub fn _wait_for_readiness(&self, generation: u64, remaining: Option<interface::RustDuration>, needsrecheck: bool) {
+        let wait = if needsrecheck {
+            let interval = interface::RustDuration::from_millis(HOSTPOLLINTERVAL_MS);
+            Some(remaining.map_or(interval, |remaining| interface::rust_min(remaining, interval)))
+        } else {
+            remaining
+        };
+        interface::wait_for_readiness(generation, wait);
+    }
+
+    //the poll events a file descriptor is ready for, setting needsrecheck if we won't be woken when that changes,
+    //as host sockets don't tell us when they become ready, and arrivals to how many times something has come in
+    //for it, where we can tell
+    pub fn _poll_fd(&self, fd: i32, needsrecheck: &mut bool, arrivals: &mut u64) -> i16 {
+        let wrappedfd = if let Some(wrappedfd) = self.filedescriptortable.read().unwrap().get(&fd) {
+            wrappedfd.clone()
+        } else {
+            return POLLNVAL;
+        };
+        Self::_poll_descriptor(&wrappedfd, needsrecheck, arrivals)
+    }
+
+    fn _poll_descriptor(wrappedfd: &interface::RustLock<FileDescriptor>, needsrecheck: &mut bool, arrivals: &mut u64) -> i16 {
+        //someone blocked in a call holding the descriptor exclusively means it isn't ready yet
+        let filedesc_enum = if let Ok(filedesc_enum) = wrappedfd.try_read() {
+            filedesc_enum
+        } else {
+            *needsrecheck = true;
+            return 0;
+        };
+
+        match &*filedesc_enum {
+            //regular files never block
+            File(_) => POLLIN | POLLOUT,
+            //in-memory buffers never block
+            Stream(StreamDesc {stream, buffer: Some(_), ..}) => {
+                if *stream == 0 {POLLIN} else {POLLOUT}
+            }
+            Stream(stream_filedesc_obj) => {
+                if stream_filedesc_obj.stream == 0 {
+                    let (readable, eof, stdinarrivals) = interface::stdin_poll_state();
+                    *arrivals = stdinarrivals;
+                    let mut revents = 0;
+                    if readable {revents |= POLLIN;}
+                    if eof {revents |= POLLHUP;}
+                    revents
+                } else {POLLOUT}
+            }
+            Pipe(pipe_filedesc_obj) => {
+                let pipe = PIPE_TABLE.read().unwrap().get(&pipe_filedesc_obj.pipe).unwrap().clone();
+                let mut revents = 0;
+                if is_wronly(pipe_filedesc_obj.flags) {
+                    if pipe.writable() {revents |= POLLOUT;}
+                    if pipe.get_read_ref() == 0 {revents |= POLLERR;}
+                } else {
+                    *arrivals = pipe.arrivals();
+                    if pipe.readable() {revents |= POLLIN;}
+                    if pipe.get_write_ref() == 0 {revents |= POLLHUP;}
+                }
+                revents
+            }
+            Socket(sockfdobj) => Self::_poll_socket(sockfdobj, needsrecheck, arrivals),
+            Epoll(_) => 0,
+        }
+    }
+
+    fn _poll_socket(sockfdobj: &SocketDesc, needsrecheck: &mut bool, arrivals: &mut u64) -> i16 {
+        let mut revents = 0;
+
+        if let Some(connection) = &sockfdobj.connection {
+            let (readable, writable, hungup) = connection.poll_state();
+            *arrivals = connection.arrivals();
+            if readable {revents |= POLLIN;}
+            if writable {revents |= POLLOUT;}
+            if hungup {revents |= POLLHUP;}
+        } else if sockfdobj.state == ConnState::LISTEN {
+            if let Some(listener) = Self::_emulated_listener(sockfdobj) {
+                *arrivals = listener.arrivals();
+                if listener.has_pending() {revents |= POLLIN;}
+            }
+            if Self::_host_listening(sockfdobj) {
+                *needsrecheck = true;
+                revents |= Self::_socket_object(sockfdobj).poll(POLLIN, 0) & POLLIN;
+            }
+        } else if sockfdobj.socktype == SOCK_DGRAM {
+            //a datagram can go to any queue, so we can't tell whether sending would wait for room in it
+            revents |= POLLOUT;
+            if let Some(queue) = Self::_datagram_queue(sockfdobj) {
+                *arrivals = queue.arrivals();
+                if queue.has_datagrams() {revents |= POLLIN;}
+            }
+            if sockfdobj.socketobjectid.is_some() {
+                *needsrecheck = true;
+                revents |= Self::_socket_object(sockfdobj).poll(POLLIN | POLLOUT, 0);
+            }
+        } else if sockfdobj.socketobjectid.is_some() {
+            *needsrecheck = true;
+            revents |= Self::_socket_object(sockfdobj).poll(POLLIN | POLLPRI | POLLOUT, 0);
+        } else {
+            //an unconnected unix stream socket, like on linux
+            revents |= POLLOUT | POLLHUP;
+        }
+
+        let shutdown = sockfdobj.shutdown.load(interface::RustAtomicOrdering::SeqCst);
+        if shutdown & RCV_SHUTDOWN != 0 {revents |= POLLIN;}
+        if shutdown == RCV_SHUTDOWN | SEND_SHUTDOWN {revents |= POLLHUP;}
+        revents
+    }
+
+    //------------------------------------SHUTDOWN SYSCALL------------------------------------
+
+    pub fn shutdown_syscall(&self, fd: i32, how: i32) -> i32 {
+        //shutting down is how one thread wakes another blocked in recv on the same socket, so we must not
+        //wait for an exclusive lock on the descriptor
+        let wrappedfd_option = self.filedescriptortable.read().unwrap().get(&fd).cloned();
+
+        if let Some(wrappedfd) = wrappedfd_option {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                let shutbits = match how {
+                    SHUT_RD => RCV_SHUTDOWN,
+                    SHUT_WR => SEND_SHUTDOWN,
+                    SHUT_RDWR => RCV_SHUTDOWN | SEND_SHUTDOWN,
+                    _ => {return syscall_error(Errno::EINVAL, "shutdown", "the how argument is not valid");}
+                };
+
+                //a nonblocking connect which has since finished counts, even if connect hasn't been called again to see it
+                let connected = match sockfdobj.state {
+                    ConnState::CONNECTED => true,
+                    ConnState::CONNECTING => Self::_host_connect_finished(sockfdobj),
+                    _ => false
+                };
+                if !connected {
+                    return syscall_error(Errno::ENOTCONN, "shutdown", "the socket is not connected");
+                }
+
+                if let Some(connection) = &sockfdobj.connection {
+                    if shutbits & RCV_SHUTDOWN != 0 {connection.shutdown_read();}
+                    if shutbits & SEND_SHUTDOWN != 0 {connection.shutdown_write();}
+                } else if sockfdobj.socketobjectid.is_some() {
+                    //a datagram socket whose peer is within lind isn't connected on the host, which is fine
+                    match Self::_socket_object(sockfdobj).shutdown(how) {
+                        Ok(()) => {}
+                        Err(errno) if errno == Errno::ENOTCONN as i32 && sockfdobj.socktype == SOCK_DGRAM => {}
+                        Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "shutdown", "the host could not shut down the socket");}
+                    }
+                }
+
+                sockfdobj.shutdown.fetch_or(shutbits, interface::RustAtomicOrdering::SeqCst);
+                interface::notify_readiness();
+                0 //shutdown has succeeded
+            } else {
+                syscall_error(Errno::ENOTSOCK, "shutdown", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "shutdown", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------SETSOCKOPT SYSCALL------------------------------------
+
+    pub fn setsockopt_syscall(&self, fd: i32, level: i32, optname: i32, optval: *const u8, optlen: u32) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let mut filedesc_enum = wrappedfd.write().unwrap();
+
+            if let Socket(ref mut sockfdobj) = &mut *filedesc_enum {
+                match level {
+                    SOL_SOCKET => {
+                        match optname {
+                            SO_TYPE | SO_ERROR => {
+                                syscall_error(Errno::ENOPROTOOPT, "setsockopt", "the option can only be read")
+                            }
+
+                            SO_LINGER => {
+                                if (optlen as usize) < interface::rust_size_of::<interface::Linger>() {
+                                    return syscall_error(Errno::EINVAL, "setsockopt", "the option value is too short");
+                                }
+                                let linger = unsafe{(optval as *const interface::Linger).read_unaligned()};
+
+                                let mut rawlinger = linger.l_onoff.to_ne_bytes().to_vec();
+                                rawlinger.extend_from_slice(&linger.l_linger.to_ne_bytes());
+                                let hostret = Self::_host_setsockopt(sockfdobj, level, optname, &rawlinger);
+                                if hostret != 0 {return hostret;}
+
+                                if linger.l_onoff != 0 {
+                                    sockfdobj.options |= 1 << SO_LINGER;
+                                    sockfdobj.linger = linger.l_linger;
+                                } else {
+                                    sockfdobj.options &= !(1 << SO_LINGER);
+                                    sockfdobj.linger = 0;
+                                }
+                                0 //setsockopt has succeeded
+                            }
+
+                            SO_REUSEADDR | SO_REUSEPORT | SO_KEEPALIVE | SO_SNDBUF | SO_RCVBUF => {
+                                if (optlen as usize) < interface::rust_size_of::<i32>() {
+                                    return syscall_error(Errno::EINVAL, "setsockopt", "the option value is too short");
+                                }
+                                let value = unsafe{(optval as *const i32).read_unaligned()};
+
+                                let hostret = Self::_host_setsockopt(sockfdobj, level, optname, &value.to_ne_bytes());
+                                if hostret != 0 {return hostret;}
+
+                                match optname {
+                                    SO_SNDBUF => {sockfdobj.sndbuf = Self::_buffer_size(value, SNDBUF_MIN);}
+                                    SO_RCVBUF => {sockfdobj.rcvbuf = Self::_buffer_size(value, RCVBUF_MIN);}
+                                    _ => {
+                                        if value != 0 {
+                                            sockfdobj.options |= 1 << optname;
+                                        } else {
+                                            sockfdobj.options &= !(1 << optname);
+                                        }
+                                    }
+                                }
+                                Self::_apply_buffer_sizes(sockfdobj);
+                                0 //setsockopt has succeeded
+                            }
+
+                            _ => syscall_error(Errno::ENOPROTOOPT, "setsockopt", "the socket option is not supported")
+                        }
+                    }
+
+                    IPPROTO_TCP => {
+                        let tcpret = Self::_check_tcp_level(sockfdobj, "setsockopt");
+                        if tcpret != 0 {return tcpret;}
+
+                        match optname {
+                            TCP_NODELAY => {
+                                if (optlen as usize) < interface::rust_size_of::<i32>() {
+                                    return syscall_error(Errno::EINVAL, "setsockopt", "the option value is too short");
+                                }
+                                let value = unsafe{(optval as *const i32).read_unaligned()};
+
+                                let hostret = Self::_host_setsockopt(sockfdobj, level, optname, &value.to_ne_bytes());
+                                if hostret != 0 {return hostret;}
+
+                                //emulated connections never delay sending, so this only matters to the host
+                                if value != 0 {
+                                    sockfdobj.tcpoptions |= 1 << optname;
+                                } else {
+                                    sockfdobj.tcpoptions &= !(1 << optname);
+                                }
+                                0 //setsockopt has succeeded
+                            }
+                            _ => syscall_error(Errno::ENOPROTOOPT, "setsockopt", "the tcp option is not supported")
+                        }
+                    }
+
+                    _ => syscall_error(Errno::ENOPROTOOPT, "setsockopt", "the option level is not supported")
+                }
+            } else {
+                syscall_error(Errno::ENOTSOCK, "setsockopt", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "setsockopt", "invalid file descriptor")
+        }
+    }
+
+    //------------------------------------GETSOCKOPT SYSCALL------------------------------------
+
+    pub fn getsockopt_syscall(&self, fd: i32, level: i32, optname: i32, optval: *mut u8, optlen: &mut u32) -> i32 {
+        let fdtable = self.filedescriptortable.read().unwrap();
+
+        if let Some(wrappedfd) = fdtable.get(&fd) {
+            let filedesc_enum = wrappedfd.read().unwrap();
+
+            if let Socket(sockfdobj) = &*filedesc_enum {
+                let value = match level {
+                    SOL_SOCKET => {
+                        match optname {
+                            SO_TYPE => sockfdobj.socktype,
+
+                            //only the host reports errors that happen in the background, reading them clears them there
+                            SO_ERROR => {
+                                if sockfdobj.socketobjectid.is_some() {
+                                    Self::_socket_object(sockfdobj).getsockopt_int(SOL_SOCKET, SO_ERROR).unwrap_or(0)
+                                } else {
+                                    0
+                                }
+                            }
+
+                            SO_LINGER => {
+                                let onoff = if sockfdobj.options & (1 << SO_LINGER) != 0 {1} else {0};
+                                let mut rawlinger = (onoff as i32).to_ne_bytes().to_vec();
+                                rawlinger.extend_from_slice(&sockfdobj.linger.to_ne_bytes());
+                                Self::_copy_out_opt(optval, optlen, &rawlinger);
+                                return 0; //getsockopt has succeeded
+                            }
+
+                            SO_SNDBUF | SO_RCVBUF => {
+                                let stored = if optname == SO_SNDBUF {sockfdobj.sndbuf} else {sockfdobj.rcvbuf};
+                                if stored != 0 {
+                                    stored
+                                } else if sockfdobj.socketobjectid.is_some() {
+                                    match Self::_socket_object(sockfdobj).getsockopt_int(level, optname) {
+                                        Ok(hostvalue) => hostvalue,
+                                        Err(errno) => {return syscall_error(Errno::from_discriminant(errno), "getsockopt", "the host could not report the option");}
+                                    }
+                                } else {
+                                    EMULATEDBUFFERSIZE as i32
+                                }
+                            }
+
+                            SO_REUSEADDR | SO_REUSEPORT | SO_KEEPALIVE => (sockfdobj.options >> optname) & 1,
+
+                            _ => {return syscall_error(Errno::ENOPROTOOPT, "getsockopt", "the socket option is not supported");}
+                        }
+                    }
+
+                    IPPROTO_TCP => {
+                        let tcpret = Self::_check_tcp_level(sockfdobj, "getsockopt");
+                        if tcpret != 0 {return tcpret;}
+
+                        match optname {
+                            TCP_NODELAY => (sockfdobj.tcpoptions >> optname) & 1,
+                            _ => {return syscall_error(Errno::ENOPROTOOPT, "getsockopt", "the tcp option is not supported");}
+                        }
+                    }
+
+                    _ => {return syscall_error(Errno::ENOPROTOOPT, "getsockopt", "the option level is not supported");}
+                };
+
+                Self::_copy_out_opt(optval, optlen, &value.to_ne_bytes());
+                0 //getsockopt has succeeded
+            } else {
+                syscall_error(Errno::ENOTSOCK, "getsockopt", "file descriptor refers to something other than a socket")
+            }
+        } else {
+            syscall_error(Errno::EBADF, "getsockopt", "invalid file descriptor")
+        }
+    }
+
+    //tcp options only make sense for inet stream sockets
+    fn _check_tcp_level(sockfdobj: &SocketDesc, callname: &str) -> i32 {
+        if sockfdobj.domain == AF_UNIX {
+            return syscall_error(Errno::EOPNOTSUPP, callname, "unix sockets have no tcp options");
+        }
+        if sockfdobj.socktype != SOCK_STREAM {
+            return syscall_error(Errno::ENOPROTOOPT, callname, "only stream sockets have tcp options");
+        }
+        0
+    }
+
+    //passes an option on to the host socket, if there is one
+    fn _host_setsockopt(sockfdobj: &SocketDesc, level: i32, optname: i32, optval: &[u8]) -> i32 {
+        if sockfdobj.socketobjectid.is_some() {
+            if let Err(errno) = Self::_socket_object(sockfdobj).setsockopt(level, optname, optval) {
+                return syscall_error(Errno::from_discriminant(errno), "setsockopt", "the host could not set the option");
+            }
+        }
+        0
+    }
+
+    //the size linux would actually use for a buffer when asked for the given one
+    fn _buffer_size(requested: i32, minimum: i32) -> i32 {
+        let capped = interface::rust_min(requested as u32, SOCKBUF_MAX as u32) as i32;
+        interface::rust_max(capped * 2, minimum)
+    }
+
+    //resizes the in-memory buffers of an emulated socket to match its options
+    fn _apply_buffer_sizes(sockfdobj: &SocketDesc) {
+        if let Some(connection) = &sockfdobj.connection {
+            if sockfdobj.sndbuf != 0 {connection.set_send_capacity(sockfdobj.sndbuf as usize);}
+            if sockfdobj.rcvbuf != 0 {connection.set_recv_capacity(sockfdobj.rcvbuf as usize);}
+        }
+        if let Some(queue) = Self::_datagram_queue(sockfdobj) {
+            if sockfdobj.rcvbuf != 0 {queue.set_capacity(sockfdobj.rcvbuf as usize);}
+        }
+    }
+
+    //copies an option's value out, truncated to the space given
+    fn _copy_out_opt(optval: *mut u8, optlen: &mut u32, value: &[u8]) {
+        let len = interface::rust_min(*optlen as usize, value.len());
+        unsafe{std::ptr::copy_nonoverlapping(value.as_ptr(), optval, len);}
+        *optlen = len as u32;
+    }
+}
@@ -5,11 +5,91 @@ use crate::interface;
 // Define constants using static or const
 // Imported into net_calls file
 
-#[derive(Debug)]
-#[repr(C)]
-pub struct EpollEvent {
-    pub events: u32,
-    pub fd: i32 
-    //in native this is a union which could be one of a number of things
-    //however, we only support EPOLL_CTL subcommands which take the fd
-}
+pub const STARTINGSOCKETOBJ: i32 = 0;
+pub const MAXSOCKETOBJ: i32 = 1024;
+
+//Address families
+pub const AF_UNSPEC: i32 = 0;
+pub const AF_UNIX: i32 = 1;
+pub const AF_INET: i32 = 2;
+pub const AF_INET6: i32 = 10;
+
+//Socket types, the high bits of the type argument to socket may also carry flags
+pub const SOCK_STREAM: i32 = 1;
+pub const SOCK_DGRAM: i32 = 2;
+pub const SOCK_TYPEMASK: i32 = 0o17;
+pub const SOCK_NONBLOCK: i32 = 0o4000;
+pub const SOCK_CLOEXEC: i32 = 0o2000000;
+
+//Protocols
+pub const IPPROTO_IP: i32 = 0;
+pub const IPPROTO_TCP: i32 = 6;
+pub const IPPROTO_UDP: i32 = 17;
+
+//Flags for send and recv, and their to/from variants
+pub const MSG_OOB: i32 = 1;
+pub const MSG_PEEK: i32 = 2;
+pub const MSG_DONTWAIT: i32 = 0x40;
+pub const MSG_WAITALL: i32 = 0x100;
+pub const MSG_NOSIGNAL: i32 = 0x4000;
+
+//How to shut a socket down
+pub const SHUT_RD: i32 = 0;
+pub const SHUT_WR: i32 = 1;
+pub const SHUT_RDWR: i32 = 2;
+
+//Directions which have been shut down, as bits in SocketDesc.shutdown
+pub const RCV_SHUTDOWN: i32 = 1;
+pub const SEND_SHUTDOWN: i32 = 2;
+
+//Events for poll
+pub const POLLIN: i16 = 0x1;
+pub const POLLPRI: i16 = 0x2;
+pub const POLLOUT: i16 = 0x4;
+pub const POLLERR: i16 = 0x8;
+pub const POLLHUP: i16 = 0x10;
+pub const POLLNVAL: i16 = 0x20;
+
+//Events for epoll, the readiness bits are the same as poll's
+pub const EPOLLIN: u32 = 0x1;
+pub const EPOLLPRI: u32 = 0x2;
+pub const EPOLLOUT: u32 = 0x4;
+pub const EPOLLERR: u32 = 0x8;
+pub const EPOLLHUP: u32 = 0x10;
+pub const EPOLLRDHUP: u32 = 0x2000;
+pub const EPOLLONESHOT: u32 = 1 << 30;
+pub const EPOLLET: u32 = 1 << 31;
+
+//Operations for epoll_ctl
+pub const EPOLL_CTL_ADD: i32 = 1;
+pub const EPOLL_CTL_DEL: i32 = 2;
+pub const EPOLL_CTL_MOD: i32 = 3;
+
+//Socket option levels
+pub const SOL_SOCKET: i32 = 1;
+
+//Options at the SOL_SOCKET level, the boolean ones are stored as bits in SocketDesc.options
+pub const SO_REUSEADDR: i32 = 2;
+pub const SO_TYPE: i32 = 3;
+pub const SO_ERROR: i32 = 4;
+pub const SO_SNDBUF: i32 = 7;
+pub const SO_RCVBUF: i32 = 8;
+pub const SO_KEEPALIVE: i32 = 9;
+pub const SO_LINGER: i32 = 13;
+pub const SO_REUSEPORT: i32 = 15;
+
+//Options at the IPPROTO_TCP level, stored as bits in SocketDesc.tcpoptions
+pub const TCP_NODELAY: i32 = 1;
+
+//Like linux, we double the buffer sizes asked for to leave room for bookkeeping, within these bounds
+pub const SOCKBUF_MAX: i32 = 212992;
+pub const SNDBUF_MIN: i32 = 4608;
+pub const RCVBUF_MIN: i32 = 2304;
+
+//Sizes for sockets emulated within lind
+pub const EMULATEDBUFFERSIZE: usize = 212992; //matches the default socket buffer size on linux
+pub const UDPMAXPAYLOAD_V4: usize = 65507;
+pub const UDPMAXPAYLOAD_V6: usize = 65527;
+
+//how long to wait on an emulated socket before checking whether its host socket has anything for us
+pub const HOSTPOLLINTERVAL_MS: u64 = 10;
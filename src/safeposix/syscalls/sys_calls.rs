@@ -2,7 +2,7 @@
 
 // System related system calls
 use crate::interface;
-use crate::safeposix::cage::{Arg, CAGE_TABLE, PIPE_TABLE, Cage, Errno, FileDescriptor::*, FSData, Rlimit, StatData};
+use crate::safeposix::cage::{Arg, CAGE_TABLE, PIPE_TABLE, SOCKET_OBJECT_TABLE, Cage, Errno, FileDescriptor::*, FSData, Rlimit, StatData, syscall_error};
 use crate::safeposix::filesystem::{FS_METADATA, Inode, metawalk, decref_dir};
 
 use super::sys_constants::*;
@@ -20,8 +20,12 @@ impl Cage {
             for (key, value) in self.filedescriptortable.read().unwrap().iter() {
                 let fd = value.read().unwrap();
 
-                //only file inodes have real inode objects currently
-                let inodenum_option = if let File(f) = &*fd {Some(f.inode)} else {None};
+                //only files and the ends of fifos have real inode objects currently
+                let inodenum_option = match &*fd {
+                    File(f) => Some(f.inode),
+                    Pipe(p) => p.inode,
+                    _ => None
+                };
 
                 if let Some(inodenum) = inodenum_option {
                     //increment the reference count on the inode
@@ -29,7 +33,10 @@ impl Cage {
                     match inode {
                         Inode::File(f) => {f.refcount += 1;}
                         Inode::CharDev(f) => {f.refcount += 1;}
+                        Inode::Socket(f) => {f.refcount += 1;}
                         Inode::Dir(f) => {f.refcount += 1;}
+                        Inode::Fifo(f) => {f.refcount += 1;}
+                        Inode::Symlink(_) => {unreachable!("symbolic links are never opened");}
                     }
                 }
 
@@ -38,6 +45,12 @@ impl Cage {
                     pipe.incr_ref(f.flags)
                 }
 
+                if let Socket(f) = &*fd {
+                    if let Some(sockobjid) = f.socketobjectid {
+                        SOCKET_OBJECT_TABLE.read().unwrap().get(&sockobjid).unwrap().incr_ref();
+                    }
+                }
+
                 newfdtable.insert(*key, value.clone()); //clone (increment) the reference counter, and add to hashmap
 
             }
@@ -50,7 +63,9 @@ impl Cage {
         }
         let cageobj = Cage {
             cageid: child_cageid, cwd: interface::RustLock::new(self.cwd.read().unwrap().clone()), parent: self.cageid,
-            filedescriptortable: interface::RustLock::new(newfdtable)
+            filedescriptortable: interface::RustLock::new(newfdtable),
+            creds: interface::RustLock::new(self.creds.read().unwrap().clone()),
+            umask: interface::RustAtomicU32::new(self.umask.load(interface::RustAtomicOrdering::Relaxed))
         };
         mutcagetable.insert(child_cageid, interface::RustRfc::new(cageobj));
         0
@@ -59,15 +74,22 @@ impl Cage {
     pub fn exec_syscall(&self, child_cageid: u64) -> i32 {
         {CAGE_TABLE.write().unwrap().remove(&self.cageid).unwrap();}
 
-        self.filedescriptortable.write().unwrap().retain(|&_, v| !match &*v.read().unwrap() {
-            File(_f) => true,//f.flags & CLOEXEC,
-            Stream(_s) => true,//s.flags & CLOEXEC,
-            Socket(_s) => true,//s.flags & CLOEXEC,
-            Pipe(_p) => true,//p.flags & CLOEXEC
-            Epoll(_p) => true,//p.flags & CLOEXEC
-        });
+        //descriptors marked close-on-exec are closed properly, so that pipe and socket references are given up,
+        //while everything else carries over into the new cage
+        {
+            let mut fdtable = self.filedescriptortable.write().unwrap();
+            let cloexecfds = fdtable.iter().filter(|(_, entry)| entry.cloexec).map(|(fd, _)| *fd).collect::<Vec<i32>>();
+            for fd in cloexecfds {
+                self._close_helper(fd, Some(&mut *fdtable));
+            }
+        }
+
+        //as on linux, the saved ids become the effective ones across an exec
+        let mut newcreds = self.creds.read().unwrap().clone();
+        newcreds.suid = newcreds.euid;
+        newcreds.sgid = newcreds.egid;
 
-        let newcage = Cage {cageid: child_cageid, cwd: interface::RustLock::new(self.cwd.read().unwrap().clone()), parent: self.parent, filedescriptortable: interface::RustLock::new(self.filedescriptortable.read().unwrap().clone())};
+        let newcage = Cage {cageid: child_cageid, cwd: interface::RustLock::new(self.cwd.read().unwrap().clone()), parent: self.parent, filedescriptortable: interface::RustLock::new(self.filedescriptortable.read().unwrap().clone()), creds: interface::RustLock::new(newcreds), umask: interface::RustAtomicU32::new(self.umask.load(interface::RustAtomicOrdering::Relaxed))};
         //wasteful clone of fdtable, but mutability constraints exist
 
         {CAGE_TABLE.write().unwrap().insert(child_cageid, interface::RustRfc::new(newcage))};
@@ -110,17 +132,92 @@ impl Cage {
     }
 
     pub fn getgid_syscall(&self) -> i32 {
-        DEFAULT_GID as i32 //Lind is only run in one group so a default value is returned
+        self.creds.read().unwrap().rgid as i32
     }
     pub fn getegid_syscall(&self) -> i32 {
-        DEFAULT_GID as i32 //Lind is only run in one group so a default value is returned
+        self.creds.read().unwrap().egid as i32
     }
 
     pub fn getuid_syscall(&self) -> i32 {
-        DEFAULT_UID as i32 //Lind is only run as one user so a default value is returned
+        self.creds.read().unwrap().ruid as i32
     }
     pub fn geteuid_syscall(&self) -> i32 {
-        DEFAULT_UID as i32 //Lind is only run as one user so a default value is returned
+        self.creds.read().unwrap().euid as i32
+    }
+
+    //root sets all of its uids, while anyone else may only switch its effective uid to its real or saved one
+    pub fn setuid_syscall(&self, uid: u32) -> i32 {
+        if uid == NO_ID {return syscall_error(Errno::EINVAL, "setuid", "uid is not valid");}
+        let mut creds = self.creds.write().unwrap();
+        if creds.is_privileged() {
+            creds.ruid = uid;
+            creds.suid = uid;
+        } else if uid != creds.ruid && uid != creds.suid {
+            return syscall_error(Errno::EPERM, "setuid", "the caller is not privileged and uid is not its real or saved uid");
+        }
+        creds.euid = uid;
+        0
+    }
+
+    //as setuid, but for the gids, with privilege still going by the effective uid
+    pub fn setgid_syscall(&self, gid: u32) -> i32 {
+        if gid == NO_ID {return syscall_error(Errno::EINVAL, "setgid", "gid is not valid");}
+        let mut creds = self.creds.write().unwrap();
+        if creds.is_privileged() {
+            creds.rgid = gid;
+            creds.sgid = gid;
+        } else if gid != creds.rgid && gid != creds.sgid {
+            return syscall_error(Errno::EPERM, "setgid", "the caller is not privileged and gid is not its real or saved gid");
+        }
+        creds.egid = gid;
+        0
+    }
+
+    //sets each of the real, effective, and saved uids not given as NO_ID,
+    //which for anyone but root must be one of the uids they already have
+    pub fn setresuid_syscall(&self, ruid: u32, euid: u32, suid: u32) -> i32 {
+        let mut creds = self.creds.write().unwrap();
+        let current = [creds.ruid, creds.euid, creds.suid];
+        if !creds.is_privileged() && [ruid, euid, suid].iter().any(|id| *id != NO_ID && !current.contains(id)) {
+            return syscall_error(Errno::EPERM, "setresuid", "the caller is not privileged and a uid is not one of its own");
+        }
+        if ruid != NO_ID {creds.ruid = ruid;}
+        if euid != NO_ID {creds.euid = euid;}
+        if suid != NO_ID {creds.suid = suid;}
+        0
+    }
+
+    pub fn setresgid_syscall(&self, rgid: u32, egid: u32, sgid: u32) -> i32 {
+        let mut creds = self.creds.write().unwrap();
+        let current = [creds.rgid, creds.egid, creds.sgid];
+        if !creds.is_privileged() && [rgid, egid, sgid].iter().any(|id| *id != NO_ID && !current.contains(id)) {
+            return syscall_error(Errno::EPERM, "setresgid", "the caller is not privileged and a gid is not one of its own");
+        }
+        if rgid != NO_ID {creds.rgid = rgid;}
+        if egid != NO_ID {creds.egid = egid;}
+        if sgid != NO_ID {creds.sgid = sgid;}
+        0
+    }
+
+    //copies the supplementary groups into list, or with an empty list just gives how many there are
+    pub fn getgroups_syscall(&self, list: &mut [u32]) -> i32 {
+        let creds = self.creds.read().unwrap();
+        if list.len() == 0 {return creds.groups.len() as i32;}
+        if list.len() < creds.groups.len() {
+            return syscall_error(Errno::EINVAL, "getgroups", "list is too small to hold all of the supplementary groups");
+        }
+        list[..creds.groups.len()].copy_from_slice(&creds.groups);
+        creds.groups.len() as i32
+    }
+
+    pub fn setgroups_syscall(&self, groups: &[u32]) -> i32 {
+        if groups.len() > NGROUPS_MAX {return syscall_error(Errno::EINVAL, "setgroups", "too many groups were given");}
+        let mut creds = self.creds.write().unwrap();
+        if !creds.is_privileged() {
+            return syscall_error(Errno::EPERM, "setgroups", "only root may set the supplementary groups");
+        }
+        creds.groups = groups.to_vec();
+        0
     }
 
     pub fn getrlimit(&self, res_type: u64, rlimit: &mut Rlimit) -> i32 {
@@ -12,6 +12,10 @@ use crate::interface;
 
 pub const DEFAULT_UID : u32 = 1000;
 pub const DEFAULT_GID : u32 = 1000;
+pub const ROOT_UID : u32 = 0;
+pub const NO_ID : u32 = u32::MAX; //-1, for an id which is to be left as it is
+pub const NGROUPS_MAX : usize = 65536;
+pub const DEFAULT_UMASK : u32 = 0o022; //like linux, new files aren't writable by group or others unless a cage sets its own umask
 
 
 // RESOURCE LIMITS
@@ -8,9 +8,12 @@ mod fs_tests {
 
     #[test]
     pub fn test_fs() {
+        let _guard = lock_tests();
+
         ut_lind_fs_simple(); // has to go first, else the data files created screw with link count test
 
         ut_lind_fs_chmod();
+        ut_lind_fs_fchmod();
         ut_lind_fs_dir_chdir();
         ut_lind_fs_dir_mode();
         ut_lind_fs_dir_multiple();
@@ -21,6 +24,9 @@ mod fs_tests {
         ut_lind_fs_file_lseek_past_end();
         ut_lind_fs_fstat_complex();
         ut_lind_fs_getuid();
+        ut_lind_fs_setuid();
+        ut_lind_fs_chown();
+        ut_lind_fs_umask();
         ut_lind_fs_load_fs();
         ut_lind_fs_mknod();
         ut_lind_fs_multiple_open();
@@ -34,6 +40,10 @@ mod fs_tests {
         ut_lind_fs_ftruncate();
         ut_lind_fs_truncate();
         ut_lind_fs_getdents();
+        ut_lind_fs_symlink();
+        ut_lind_fs_permissions();
+        ut_lind_fs_timestamps();
+        ut_lind_fs_metadata_upgrade();
 
         persistencetest();
         rdwrtest();
@@ -193,7 +203,7 @@ mod fs_tests {
 
         let fd = cage.open_syscall(filepath, flags, S_IRWXA);
         assert_eq!(cage.stat_syscall(filepath, &mut statdata), 0);
-        assert_eq!(statdata.st_mode, S_IRWXA | S_IFREG as u32);
+        assert_eq!(statdata.st_mode, (S_IRWXA & !DEFAULT_UMASK) | S_IFREG as u32);
 
         assert_eq!(cage.chmod_syscall(filepath, S_IRUSR | S_IRGRP), 0);
         assert_eq!(cage.stat_syscall(filepath, &mut statdata), 0);
@@ -210,6 +220,95 @@ mod fs_tests {
 
 
 
+    pub fn ut_lind_fs_fchmod() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut statdata = StatData::default();
+        let mut before = StatData::default();
+
+        //fchmod changes the file open as fd, setuid bit and all, which is a status change
+        let fd = cage.open_syscall("/fchmodfile", O_CREAT | O_EXCL | O_RDWR, S_IRWXU);
+        assert!(fd >= 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut before), 0);
+        interface::sleep_ms(interface::RustDuration::from_millis(5));
+        assert_eq!(cage.fchmod_syscall(fd, S_IRUSR | S_IWUSR | S_IXUSR | S_ISUID), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRUSR | S_IWUSR | S_IXUSR | S_ISUID | S_IFREG as u32);
+        assert!(statdata.st_ctim > before.st_ctim);
+        assert_eq!(statdata.st_mtim, before.st_mtim);
+        assert_eq!(cage.fchmod_syscall(fd + 100, S_IRWXU), -(Errno::EBADF as i32));
+
+        //fchmodat takes paths relative to a directory fd, and won't change the mode of a link itself
+        assert_eq!(cage.mkdir_syscall("/fchmoddir", S_IRWXA), 0);
+        let dirfd = cage.open_syscall("/fchmoddir", O_RDONLY, 0);
+        assert!(dirfd >= 0);
+        assert_eq!(cage.symlink_syscall("/fchmodfile", "/fchmoddir/link"), 0);
+        assert_eq!(cage.fchmodat_syscall(dirfd, "link", S_IRWXU | S_IRGRP, 0), 0);
+        assert_eq!(cage.stat_syscall("/fchmodfile", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRWXU | S_IRGRP | S_IFREG as u32);
+        assert_eq!(cage.fchmodat_syscall(dirfd, "link", S_IRWXU, AT_SYMLINK_NOFOLLOW), -(Errno::EOPNOTSUPP as i32));
+        assert_eq!(cage.fchmodat_syscall(dirfd, "link", S_IRWXU, 0x1), -(Errno::EINVAL as i32));
+        assert_eq!(cage.fchmodat_syscall(AT_FDCWD, "/fchmoddir", S_IRWXA | S_ISVTX, 0), 0);
+        assert_eq!(cage.stat_syscall("/fchmoddir", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRWXA | S_ISVTX | S_IFDIR as u32);
+        assert_eq!(cage.close_syscall(dirfd), 0);
+
+        //only the owner may change a file's mode
+        assert_eq!(cage.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        *cage2.creds.write().unwrap() = Credentials::new(2000, 2000);
+        assert_eq!(cage2.fchmod_syscall(fd, S_IRWXA), -(Errno::EPERM as i32));
+        assert_eq!(cage2.chmod_syscall("/fchmodfile", S_IRWXA), -(Errno::EPERM as i32));
+
+        //in the now sticky directory, others can't remove or replace what they don't own, but can their own
+        let fd2 = cage.open_syscall("/fchmoddir/mine", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage.close_syscall(fd2), 0);
+        assert_eq!(cage2.unlink_syscall("/fchmoddir/mine"), -(Errno::EPERM as i32));
+        assert_eq!(cage2.rename_syscall("/fchmoddir/mine", "/fchmoddir/moved"), -(Errno::EPERM as i32));
+        let fd2 = cage2.open_syscall("/fchmoddir/theirs", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage2.close_syscall(fd2), 0);
+        assert_eq!(cage2.rename_syscall("/fchmoddir/theirs", "/fchmoddir/mine"), -(Errno::EPERM as i32));
+        assert_eq!(cage2.unlink_syscall("/fchmoddir/theirs"), 0);
+
+        //new files in a setgid directory take its group, and new directories its setgid bit too
+        assert_eq!(cage.chmod_syscall("/fchmoddir", S_IRWXA | S_ISGID), 0);
+        let fd2 = cage2.open_syscall("/fchmoddir/grouped", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage2.fstat_syscall(fd2, &mut statdata), 0);
+        assert_eq!((statdata.st_uid, statdata.st_gid), (2000, DEFAULT_GID));
+        assert_eq!(cage2.close_syscall(fd2), 0);
+        assert_eq!(cage2.mkdir_syscall("/fchmoddir/subdir", S_IRWXA), 0);
+        assert_eq!(cage2.stat_syscall("/fchmoddir/subdir", &mut statdata), 0);
+        assert_eq!(statdata.st_gid, DEFAULT_GID);
+        assert_eq!(statdata.st_mode, (S_IRWXA & !DEFAULT_UMASK) | S_ISGID | S_IFDIR as u32);
+
+        //a chown drops the setuid bit, and setgid can't be set for a group the owner isn't in
+        *cage2.creds.write().unwrap() = Credentials::new(ROOT_UID, ROOT_UID);
+        assert_eq!(cage.fchmod_syscall(fd, S_IRWXU | S_ISUID), 0);
+        assert_eq!(cage2.chown_syscall("/fchmodfile", NO_ID, 3000), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRWXU | S_IFREG as u32);
+        assert_eq!(cage.fchmod_syscall(fd, S_IRWXU | S_ISGID), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRWXU | S_IFREG as u32);
+        assert_eq!(cage2.fchmod_syscall(fd, S_IRWXU | S_ISGID), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IRWXU | S_ISGID | S_IFREG as u32);
+        assert_eq!(cage2.exit_syscall(), 0);
+
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.rmdir_syscall("/fchmoddir/subdir"), 0);
+        assert_eq!(cage.unlink_syscall("/fchmoddir/grouped"), 0);
+        assert_eq!(cage.unlink_syscall("/fchmoddir/mine"), 0);
+        assert_eq!(cage.unlink_syscall("/fchmoddir/link"), 0);
+        assert_eq!(cage.rmdir_syscall("/fchmoddir"), 0);
+        assert_eq!(cage.unlink_syscall("/fchmodfile"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+
+
     pub fn ut_lind_fs_dir_chdir() {
         lindrustinit();
         let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
@@ -227,6 +326,9 @@ mod fs_tests {
         assert_eq!(cage.chdir_syscall(".."), 0);
 
         assert_eq!(cage.access_syscall("subdir1", F_OK), 0);
+        //subdir3 was made with no permissions, so it can't be searched until they are added
+        assert_eq!(cage.chdir_syscall("/subdir1/subdir2/subdir3"), -(Errno::EACCES as i32));
+        assert_eq!(cage.chmod_syscall("/subdir1/subdir2/subdir3", S_IRWXA), 0);
         assert_eq!(cage.chdir_syscall("/subdir1/subdir2/subdir3"), 0);
         assert_eq!(cage.access_syscall("../../../subdir1", F_OK), 0);
 
@@ -247,7 +349,7 @@ mod fs_tests {
 
         assert_eq!(cage.mkdir_syscall(filepath1, S_IRWXA), 0);
         assert_eq!(cage.stat_syscall(filepath1, &mut statdata), 0);
-        assert_eq!(statdata.st_mode, S_IRWXA | S_IFDIR as u32);
+        assert_eq!(statdata.st_mode, (S_IRWXA & !DEFAULT_UMASK) | S_IFDIR as u32);
         
         assert_eq!(cage.mkdir_syscall(filepath2, 0), 0);
         assert_eq!(cage.stat_syscall(filepath2, &mut statdata), 0);
@@ -271,7 +373,7 @@ mod fs_tests {
 
         //ensure that the file is a dir with all of the correct bits on for nodes
         assert_eq!(cage.stat_syscall("/subdirMultiple1/subdirMultiple2", &mut statdata), 0);
-        assert_eq!(statdata.st_mode, S_IRWXA | S_IFDIR as u32);
+        assert_eq!(statdata.st_mode, (S_IRWXA & !DEFAULT_UMASK) | S_IFDIR as u32);
 
         assert_eq!(cage.stat_syscall("/subdirMultiple1/subdirMultiple2/subdirMultiple3", &mut statdata), 0);
         assert_eq!(statdata.st_mode, S_IFDIR as u32);
@@ -520,6 +622,181 @@ mod fs_tests {
 
 
 
+    pub fn ut_lind_fs_setuid() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //an unprivileged cage can only "change" to the ids it already has
+        assert_eq!(cage.setuid_syscall(DEFAULT_UID), 0);
+        assert_eq!(cage.setuid_syscall(ROOT_UID), -(Errno::EPERM as i32));
+        assert_eq!(cage.setgid_syscall(2000), -(Errno::EPERM as i32));
+        assert_eq!(cage.setresuid_syscall(NO_ID, 2000, NO_ID), -(Errno::EPERM as i32));
+        assert_eq!(cage.setgroups_syscall(&[2000]), -(Errno::EPERM as i32));
+        assert_eq!(cage.getgroups_syscall(&mut []), 0);
+
+        //a cage started as root may take on any ids
+        assert_eq!(cage.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        *cage2.creds.write().unwrap() = Credentials::new(ROOT_UID, ROOT_UID);
+        assert_eq!(cage2.setgroups_syscall(&[3000, 4000]), 0);
+        let mut groups = [0u32; 2];
+        assert_eq!(cage2.getgroups_syscall(&mut groups[..1]), -(Errno::EINVAL as i32));
+        assert_eq!(cage2.getgroups_syscall(&mut groups), 2);
+        assert_eq!(groups, [3000, 4000]);
+        assert_eq!(cage2.setresgid_syscall(2000, 2000, NO_ID), 0);
+        assert_eq!(cage2.getgid_syscall(), 2000);
+        assert_eq!(cage2.getegid_syscall(), 2000);
+
+        //keeping root as the saved uid lets the effective uid go back to it
+        assert_eq!(cage2.setresuid_syscall(2000, 2000, NO_ID), 0);
+        assert_eq!(cage2.getuid_syscall(), 2000);
+        assert_eq!(cage2.geteuid_syscall(), 2000);
+        assert_eq!(cage2.setgroups_syscall(&[]), -(Errno::EPERM as i32));
+        assert_eq!(cage2.setuid_syscall(ROOT_UID), 0);
+        assert_eq!(cage2.geteuid_syscall(), 0);
+        assert_eq!(cage2.getuid_syscall(), 2000);
+
+        //the credentials carry over to a forked child
+        assert_eq!(cage2.setuid_syscall(2000), 0);
+        assert_eq!(cage2.fork_syscall(3), 0);
+        let cage3 = {CAGE_TABLE.read().unwrap().get(&3).unwrap().clone()};
+        assert_eq!(cage3.getuid_syscall(), 2000);
+        assert_eq!(cage3.geteuid_syscall(), 2000);
+        assert_eq!(cage3.getegid_syscall(), 2000);
+        assert_eq!(cage3.getgroups_syscall(&mut groups), 2);
+
+        //and across an exec, where the saved ids become the effective ones
+        assert_eq!(cage3.setresuid_syscall(5000, 5000, 5000), -(Errno::EPERM as i32));
+        assert_eq!(cage2.exit_syscall(), 0);
+        assert_eq!(cage3.exec_syscall(4), 0);
+        let cage4 = {CAGE_TABLE.read().unwrap().get(&4).unwrap().clone()};
+        assert_eq!(cage4.geteuid_syscall(), 2000);
+        assert_eq!(cage4.setuid_syscall(ROOT_UID), -(Errno::EPERM as i32));
+        assert_eq!(cage4.exit_syscall(), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_umask() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut statdata = StatData::default();
+
+        //the umask's bits are cleared from whatever mode new files ask for
+        assert_eq!(cage.umask_syscall(S_IWGRP | S_IWOTH), DEFAULT_UMASK as i32);
+        let fd = cage.open_syscall("/umaskfile", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert!(fd >= 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFREG as u32 | 0o755);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.mkdir_syscall("/umaskdir", S_IRWXA), 0);
+        assert_eq!(cage.stat_syscall("/umaskdir", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFDIR as u32 | 0o755);
+        assert_eq!(cage.mkfifo_syscall("/umaskfifo", 0o666), 0);
+        assert_eq!(cage.stat_syscall("/umaskfifo", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFIFO as u32 | 0o644);
+
+        //setuid, setgid, and sticky bits can be asked for too, though mkdir ignores the first two
+        let fd = cage.open_syscall("/umasksetuid", O_CREAT | O_EXCL | O_WRONLY, S_ISUID | S_IRWXA);
+        assert!(fd >= 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFREG as u32 | S_ISUID | 0o755);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.mkdir_syscall("/umasksticky", S_ISUID | S_ISGID | S_ISVTX | S_IRWXA), 0);
+        assert_eq!(cage.stat_syscall("/umasksticky", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFDIR as u32 | S_ISVTX | 0o755);
+        assert_eq!(cage.mkfifo_syscall("/umasksetgid", S_ISGID | 0o666), 0);
+        assert_eq!(cage.stat_syscall("/umasksetgid", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFIFO as u32 | S_ISGID | 0o644);
+
+        //but not from modes set explicitly
+        assert_eq!(cage.chmod_syscall("/umaskfile", S_IRWXA), 0);
+        assert_eq!(cage.stat_syscall("/umaskfile", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFREG as u32 | S_IRWXA);
+
+        //a forked cage keeps the umask of its parent, and only permission bits are kept in it
+        assert_eq!(cage.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(cage2.umask_syscall(0o7777), (S_IWGRP | S_IWOTH) as i32);
+        assert_eq!(cage2.mkdir_syscall("/umaskdir/sub", S_IRWXA), 0);
+        assert_eq!(cage2.stat_syscall("/umaskdir/sub", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFDIR as u32);
+        assert_eq!(cage2.umask_syscall(0), S_IRWXA as i32);
+        assert_eq!(cage2.exit_syscall(), 0);
+        assert_eq!(cage.umask_syscall(0), (S_IWGRP | S_IWOTH) as i32);
+
+        assert_eq!(cage.rmdir_syscall("/umaskdir/sub"), 0);
+        assert_eq!(cage.rmdir_syscall("/umaskdir"), 0);
+        assert_eq!(cage.unlink_syscall("/umaskfifo"), 0);
+        assert_eq!(cage.unlink_syscall("/umaskfile"), 0);
+        assert_eq!(cage.unlink_syscall("/umasksetuid"), 0);
+        assert_eq!(cage.rmdir_syscall("/umasksticky"), 0);
+        assert_eq!(cage.unlink_syscall("/umasksetgid"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_chown() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut statdata = StatData::default();
+
+        //files belong to whoever created them
+        let fd = cage.open_syscall("/chownfile", O_CREAT | O_EXCL | O_WRONLY, S_IRUSR | S_IWUSR | S_IRGRP);
+        assert!(fd >= 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_uid, DEFAULT_UID);
+        assert_eq!(statdata.st_gid, DEFAULT_GID);
+
+        //its owner can't give it away, or put it in a group they aren't in
+        assert_eq!(cage.chown_syscall("/chownfile", 2000, NO_ID), -(Errno::EPERM as i32));
+        assert_eq!(cage.chown_syscall("/chownfile", NO_ID, 3000), -(Errno::EPERM as i32));
+        assert_eq!(cage.chown_syscall("/chownfile", DEFAULT_UID, DEFAULT_GID), 0);
+        assert_eq!(cage.fchown_syscall(fd, NO_ID, NO_ID), 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //root can though
+        assert_eq!(cage.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        *cage2.creds.write().unwrap() = Credentials::new(ROOT_UID, ROOT_UID);
+        assert_eq!(cage2.chown_syscall("/chownfile", 2000, 3000), 0);
+        assert_eq!(cage2.stat_syscall("/chownfile", &mut statdata), 0);
+        assert_eq!(statdata.st_uid, 2000);
+        assert_eq!(statdata.st_gid, 3000);
+
+        //lchown changes a link rather than what it points to
+        assert_eq!(cage2.symlink_syscall("/chownfile", "/chownlink"), 0);
+        assert_eq!(cage2.lchown_syscall("/chownlink", 4000, NO_ID), 0);
+        assert_eq!(cage2.lstat_syscall("/chownlink", &mut statdata), 0);
+        assert_eq!(statdata.st_uid, 4000);
+        assert_eq!(cage2.stat_syscall("/chownlink", &mut statdata), 0);
+        assert_eq!(statdata.st_uid, 2000);
+
+        //the old owner is now an other, and so can't read it
+        assert_eq!(cage.open_syscall("/chownfile", O_RDONLY, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.chown_syscall("/chownfile", NO_ID, DEFAULT_GID), -(Errno::EPERM as i32));
+
+        //while a member of its group gets the group's read permission
+        assert_eq!(cage2.setgroups_syscall(&[3000]), 0);
+        assert_eq!(cage2.setresuid_syscall(5000, 5000, 5000), 0);
+        let fd = cage2.open_syscall("/chownfile", O_RDONLY, 0);
+        assert!(fd >= 0);
+        assert_eq!(cage2.fchown_syscall(fd, NO_ID, 3000), -(Errno::EPERM as i32));
+        assert_eq!(cage2.close_syscall(fd), 0);
+        assert_eq!(cage2.open_syscall("/chownfile", O_WRONLY, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage2.exit_syscall(), 0);
+
+        assert_eq!(cage.unlink_syscall("/chownlink"), 0);
+        assert_eq!(cage.unlink_syscall("/chownfile"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+
+
     pub fn ut_lind_fs_load_fs() {
         lindrustinit();
         let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
@@ -633,7 +910,7 @@ mod fs_tests {
 
         //now we are going to mknod /dev/null with create, read and write flags and permissions
         //and then makr sure that it exists
-        assert_eq!(cage.mknod_syscall(path, S_IFCHR as u32, dev), 0);
+        assert_eq!(cage.mknod_syscall(path, S_IRWXA | S_IFCHR as u32, dev), 0);
         let fd = cage.open_syscall(path, O_RDWR, S_IRWXA);
 
         //checking the metadata of the file:
@@ -657,7 +934,7 @@ mod fs_tests {
         let path2 = "/random";
 
         //making the node and then making sure that it exists
-        assert_eq!(cage.mknod_syscall(path2, S_IFCHR as u32, dev2), 0);
+        assert_eq!(cage.mknod_syscall(path2, S_IRWXA | S_IFCHR as u32, dev2), 0);
         let fd2 = cage.open_syscall(path2, O_RDWR, S_IRWXA);
         
         let mut buf2 = sizecbuf(4);
@@ -761,7 +1038,7 @@ mod fs_tests {
 
         let mut statdata = StatData::default();
         assert_eq!(cage.stat_syscall(path, &mut statdata), 0);
-        assert_eq!(statdata.st_mode, S_IRWXA | S_IFREG as u32);
+        assert_eq!(statdata.st_mode, (S_IRWXA & !DEFAULT_UMASK) | S_IFREG as u32);
 
         //make a file without permissions and check that it is a reg file without permissions
         let path2 = "/fooFileMode2";
@@ -801,6 +1078,91 @@ mod fs_tests {
         assert_eq!(cage.mkdir_syscall(old_path, S_IRWXA), 0);
         assert_eq!(cage.rename_syscall(old_path, "/test_dir_renamed"), 0);
 
+        let mut statdata = StatData::default();
+        let mut buf = sizecbuf(10);
+        assert_eq!(cage.rename_syscall("/renamemissing", "/renamed"), -(Errno::ENOENT as i32));
+        assert_eq!(cage.mkdir_syscall("/renamesrc", S_IRWXA), 0);
+        assert_eq!(cage.mkdir_syscall("/renamedst", S_IRWXA), 0);
+        let fd = cage.open_syscall("/renamesrc/file", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage.write_syscall(fd, str2cbuf("moved"), 5), 5);
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //files move between directories, with the entry counts of both directories following them
+        assert_eq!(cage.rename_syscall("/renamesrc/file", "/renamedst/file2"), 0);
+        assert_eq!(cage.stat_syscall("/renamesrc/file", &mut statdata), -(Errno::ENOENT as i32));
+        assert_eq!(cage.stat_syscall("/renamesrc", &mut statdata), 0);
+        assert_eq!(statdata.st_nlink, 3);
+        assert_eq!(cage.stat_syscall("/renamedst", &mut statdata), 0);
+        assert_eq!(statdata.st_nlink, 4);
+        let fd = cage.open_syscall("/renamedst/file2", O_RDONLY, S_IRWXA);
+        assert_eq!(cage.read_syscall(fd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cbuf2str(&buf[..5]), "moved");
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //an existing file is replaced, leaving one entry and the moved file's contents, while renaming a file to another name for it does nothing
+        let fd = cage.open_syscall("/renamedst/victim", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.link_syscall("/renamedst/file2", "/renamedst/file3"), 0);
+        assert_eq!(cage.rename_syscall("/renamedst/file2", "/renamedst/file3"), 0);
+        assert_eq!(cage.stat_syscall("/renamedst/file2", &mut statdata), 0);
+        assert_eq!(cage.unlink_syscall("/renamedst/file3"), 0);
+        assert_eq!(cage.rename_syscall("/renamedst/file2", "/renamedst/victim"), 0);
+        assert_eq!(cage.stat_syscall("/renamedst/victim", &mut statdata), 0);
+        assert_eq!(statdata.st_size, 5);
+        assert_eq!(statdata.st_nlink, 1);
+        assert_eq!(cage.stat_syscall("/renamedst", &mut statdata), 0);
+        assert_eq!(statdata.st_nlink, 4);
+
+        //files and directories only replace their own kind, and directories only empty ones
+        assert_eq!(cage.mkdir_syscall("/renamedst/sub", S_IRWXA), 0);
+        assert_eq!(cage.rename_syscall("/renamedst/victim", "/renamedst/sub"), -(Errno::EISDIR as i32));
+        assert_eq!(cage.rename_syscall("/renamedst/sub", "/renamedst/victim"), -(Errno::ENOTDIR as i32));
+        assert_eq!(cage.rename_syscall("/renamesrc", "/renamedst"), -(Errno::ENOTEMPTY as i32));
+        assert_eq!(cage.mkdir_syscall("/renameempty", S_IRWXA), 0);
+        assert_eq!(cage.rename_syscall("/renameempty", "/renamesrc"), 0);
+        assert_eq!(cage.stat_syscall("/renameempty", &mut statdata), -(Errno::ENOENT as i32));
+
+        //a replaced directory which is still open goes away once it is closed, as does one removed while open
+        assert_eq!(cage.mkdir_syscall("/renameempty", S_IRWXA), 0);
+        assert_eq!(cage.mkdir_syscall("/renameopen", S_IRWXA), 0);
+        let dirfd = cage.open_syscall("/renameopen", O_RDONLY, S_IRWXA);
+        assert!(dirfd >= 0);
+        assert_eq!(cage.fstat_syscall(dirfd, &mut statdata), 0);
+        let openinode = statdata.st_ino;
+        assert_eq!(cage.rename_syscall("/renameempty", "/renameopen"), 0);
+        assert_eq!(cage.fstat_syscall(dirfd, &mut statdata), 0);
+        assert_eq!(statdata.st_ino, openinode);
+        assert_eq!(cage.close_syscall(dirfd), 0);
+        assert!(!filesystem::FS_METADATA.read().unwrap().inodetable.contains_key(&openinode));
+
+        let dirfd = cage.open_syscall("/renameopen", O_RDONLY, S_IRWXA);
+        assert_eq!(cage.fstat_syscall(dirfd, &mut statdata), 0);
+        let openinode = statdata.st_ino;
+        assert_eq!(cage.rmdir_syscall("/renameopen"), 0);
+        assert_eq!(cage.fstat_syscall(dirfd, &mut statdata), 0);
+        assert_eq!(statdata.st_ino, openinode);
+        assert_eq!(cage.close_syscall(dirfd), 0);
+        assert!(!filesystem::FS_METADATA.read().unwrap().inodetable.contains_key(&openinode));
+
+        //a directory can't go inside itself, and one moved elsewhere has its .. follow it
+        assert_eq!(cage.rename_syscall("/renamedst", "/renamedst/sub/inner"), -(Errno::EINVAL as i32));
+        assert_eq!(cage.rename_syscall("/renamedst", "/renamedst/inner"), -(Errno::EINVAL as i32));
+        assert_eq!(cage.rename_syscall("/renamedst/sub", "/renamesrc/sub"), 0);
+        assert_eq!(cage.mkdir_syscall("/renamesrc/sub/deeper", S_IRWXA), 0);
+        assert_eq!(cage.rename_syscall("/renamesrc", "/renamesrc/sub/deeper/inner"), -(Errno::EINVAL as i32));
+        assert_eq!(cage.rename_syscall("/renamedst", "/renamesrc/sub/deeper/inner"), 0);
+        assert_eq!(cage.rename_syscall("/renamesrc/sub/deeper/inner", "/renamedst"), 0);
+        assert_eq!(cage.rmdir_syscall("/renamesrc/sub/deeper"), 0);
+        assert_eq!(cage.stat_syscall("/renamedst", &mut statdata), 0);
+        assert_eq!(statdata.st_nlink, 4);
+        assert_eq!(cage.stat_syscall("/renamesrc", &mut statdata), 0);
+        assert_eq!(statdata.st_nlink, 4);
+
+        assert_eq!(cage.rmdir_syscall("/renamesrc/sub"), 0);
+        assert_eq!(cage.rmdir_syscall("/renamesrc"), 0);
+        assert_eq!(cage.unlink_syscall("/renamedst/victim"), 0);
+        assert_eq!(cage.rmdir_syscall("/renamedst"), 0);
+
         assert_eq!(cage.exit_syscall(), 0);
         lindrustfinalize();
     }
@@ -853,6 +1215,23 @@ mod fs_tests {
         let mut buf1 = sizecbuf(7);
         assert_eq!(cage.read_syscall(fd, buf1.as_mut_ptr(), 7), 5);
         assert_eq!(cbuf2str(&buf1), "Hello\0\0");
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        // check that a file no one has open can be truncated too
+        assert_eq!(cage.truncate_syscall(&path, 3), 0);
+        let fd = cage.open_syscall(&path, O_RDONLY, 0);
+        let mut buf2 = sizecbuf(5);
+        assert_eq!(cage.read_syscall(fd, buf2.as_mut_ptr(), 5), 3);
+        assert_eq!(cbuf2str(&buf2[..3]), "Hel");
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        // only regular files can be truncated, and a fifo isn't opened (and so waited on) to find that out
+        assert_eq!(cage.mkfifo_syscall("/truncatefifo", S_IRWXA), 0);
+        assert_eq!(cage.truncate_syscall("/truncatefifo", 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.truncate_syscall("/", 0), -(Errno::EISDIR as i32));
+        assert_eq!(cage.truncate_syscall("/truncatenothing", 0), -(Errno::ENOENT as i32));
+        assert_eq!(cage.unlink_syscall("/truncatefifo"), 0);
+        assert_eq!(cage.unlink_syscall(&path), 0);
 
         assert_eq!(cage.exit_syscall(), 0);
         lindrustfinalize();
@@ -867,7 +1246,7 @@ mod fs_tests {
         let baseptr: *mut u8 = &mut vec[0];
         
         assert_eq!(cage.mkdir_syscall("/getdents", S_IRWXA), 0);
-        let fd = cage.open_syscall("/getdents", O_RDWR, S_IRWXA);
+        let fd = cage.open_syscall("/getdents", O_RDONLY, S_IRWXA);
         assert_eq!(cage.getdents_syscall(fd, baseptr, bufsize), 48);
 
         unsafe{
@@ -889,4 +1268,298 @@ mod fs_tests {
         assert_eq!(cage.exit_syscall(), 0);
         lindrustfinalize();
     }
+
+    pub fn ut_lind_fs_symlink() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(20);
+        let mut statdata = StatData::default();
+
+        assert_eq!(cage.mkdir_syscall("/symdir", S_IRWXA), 0);
+        let fd = cage.open_syscall("/symdir/file", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert_eq!(cage.write_syscall(fd, str2cbuf("hi"), 2), 2);
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //an absolute link is followed by stat, and described itself by lstat
+        assert_eq!(cage.symlink_syscall("/symdir/file", "/abslink"), 0);
+        assert_eq!(cage.symlink_syscall("/symdir/file", "/abslink"), -(Errno::EEXIST as i32));
+        assert_eq!(cage.stat_syscall("/abslink", &mut statdata), 0);
+        assert_eq!(statdata.st_size, 2);
+        assert!(is_reg(statdata.st_mode));
+        assert_eq!(cage.lstat_syscall("/abslink", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFLNK as u32 | S_IRWXA);
+        assert_eq!(statdata.st_size, 12);
+
+        //readlink gives the target as it was given, without a null terminator and truncated to fit
+        assert_eq!(cage.readlink_syscall("/abslink", buf.as_mut_ptr(), 20), 12);
+        assert_eq!(cbuf2str(&buf[..12]), "/symdir/file");
+        assert_eq!(cage.readlink_syscall("/abslink", buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cbuf2str(&buf[..4]), "/sym");
+        assert_eq!(cage.readlink_syscall("/symdir/file", buf.as_mut_ptr(), 20), -(Errno::EINVAL as i32));
+        assert_eq!(cage.readlink_syscall("/nosuchlink", buf.as_mut_ptr(), 20), -(Errno::ENOENT as i32));
+
+        //relative targets are looked up from the directory the link is in, .. included, and links are followed midway through paths
+        assert_eq!(cage.symlink_syscall("symdir", "/rellink"), 0);
+        assert_eq!(cage.symlink_syscall("../symdir/file", "/symdir/uplink"), 0);
+        for path in ["/rellink/file", "/symdir/uplink", "/rellink/uplink"].iter() {
+            let fd = cage.open_syscall(path, O_RDONLY, S_IRWXA);
+            assert!(fd >= 0);
+            assert_eq!(cage.read_syscall(fd, buf.as_mut_ptr(), 20), 2);
+            assert_eq!(cbuf2str(&buf[..2]), "hi");
+            assert_eq!(cage.close_syscall(fd), 0);
+        }
+        assert_eq!(cage.chdir_syscall("/rellink"), 0);
+        assert_eq!(cage.access_syscall("file", F_OK), 0);
+        assert_eq!(cage.chdir_syscall("/"), 0);
+
+        //O_NOFOLLOW refuses a link at the end of the path, but not one before it
+        assert_eq!(cage.open_syscall("/abslink", O_RDONLY | O_NOFOLLOW, S_IRWXA), -(Errno::ELOOP as i32));
+        let fd = cage.open_syscall("/rellink/file", O_RDONLY | O_NOFOLLOW, S_IRWXA);
+        assert!(fd >= 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //creating through a dangling link creates its target, unless O_EXCL is given
+        assert_eq!(cage.symlink_syscall("/symdir/created", "/dangling"), 0);
+        assert_eq!(cage.stat_syscall("/dangling", &mut statdata), -(Errno::ENOENT as i32));
+        assert_eq!(cage.open_syscall("/dangling", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA), -(Errno::EEXIST as i32));
+        let fd = cage.open_syscall("/dangling", O_CREAT | O_WRONLY, S_IRWXA);
+        assert!(fd >= 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.stat_syscall("/symdir/created", &mut statdata), 0);
+
+        //links which loop give ELOOP once followed, though they can still be looked at themselves
+        assert_eq!(cage.symlink_syscall("/loop2", "/loop1"), 0);
+        assert_eq!(cage.symlink_syscall("/loop1", "/loop2"), 0);
+        assert_eq!(cage.stat_syscall("/loop1", &mut statdata), -(Errno::ELOOP as i32));
+        assert_eq!(cage.open_syscall("/loop1/file", O_RDONLY, S_IRWXA), -(Errno::ELOOP as i32));
+        assert_eq!(cage.lstat_syscall("/loop1", &mut statdata), 0);
+
+        //unlinking a link leaves what it points to alone, and rmdir won't follow one to a directory
+        assert_eq!(cage.rmdir_syscall("/rellink"), -(Errno::ENOTDIR as i32));
+        for path in ["/abslink", "/rellink", "/symdir/uplink", "/dangling", "/loop1", "/loop2"].iter() {
+            assert_eq!(cage.unlink_syscall(path), 0);
+        }
+        assert_eq!(cage.stat_syscall("/symdir/file", &mut statdata), 0);
+        assert_eq!(cage.unlink_syscall("/symdir/file"), 0);
+        assert_eq!(cage.unlink_syscall("/symdir/created"), 0);
+        assert_eq!(cage.rmdir_syscall("/symdir"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_permissions() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //a file with no permission bits can't be opened for reading or writing
+        let fd = cage.open_syscall("/permfile", O_CREAT | O_EXCL | O_WRONLY, 0);
+        assert!(fd >= 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.open_syscall("/permfile", O_RDONLY, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.open_syscall("/permfile", O_WRONLY, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.truncate_syscall("/permfile", 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.access_syscall("/permfile", F_OK), 0);
+        assert_eq!(cage.access_syscall("/permfile", R_OK), -(Errno::EACCES as i32));
+
+        //read permission alone allows reading but not writing
+        assert_eq!(cage.chmod_syscall("/permfile", S_IRUSR), 0);
+        assert_eq!(cage.access_syscall("/permfile", R_OK), 0);
+        assert_eq!(cage.access_syscall("/permfile", R_OK | W_OK), -(Errno::EACCES as i32));
+        let fd = cage.open_syscall("/permfile", O_RDONLY, 0);
+        assert!(fd >= 0);
+        assert_eq!(cage.ftruncate_syscall(fd, 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.open_syscall("/permfile", O_RDWR, 0), -(Errno::EACCES as i32));
+
+        //only the owner's bits apply to the owner, even when others are allowed more
+        assert_eq!(cage.chmod_syscall("/permfile", S_IRWXO), 0);
+        assert_eq!(cage.open_syscall("/permfile", O_RDONLY, 0), -(Errno::EACCES as i32));
+
+        //nothing can be created in or removed from a directory without write permission
+        assert_eq!(cage.mkdir_syscall("/permdir", S_IRWXA), 0);
+        assert_eq!(cage.mkdir_syscall("/permdir/sub", S_IRWXA), 0);
+        let fd = cage.open_syscall("/permdir/file", O_CREAT | O_EXCL | O_WRONLY, S_IRWXA);
+        assert!(fd >= 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.chmod_syscall("/permdir", S_IRUSR | S_IXUSR), 0);
+        assert_eq!(cage.open_syscall("/permdir/new", O_CREAT | O_WRONLY, S_IRWXA), -(Errno::EACCES as i32));
+        assert_eq!(cage.mkdir_syscall("/permdir/newdir", S_IRWXA), -(Errno::EACCES as i32));
+        assert_eq!(cage.symlink_syscall("/permfile", "/permdir/link"), -(Errno::EACCES as i32));
+        assert_eq!(cage.link_syscall("/permfile", "/permdir/link"), -(Errno::EACCES as i32));
+        assert_eq!(cage.unlink_syscall("/permdir/file"), -(Errno::EACCES as i32));
+        assert_eq!(cage.rmdir_syscall("/permdir/sub"), -(Errno::EACCES as i32));
+        assert_eq!(cage.rename_syscall("/permdir/file", "/movedfile"), -(Errno::EACCES as i32));
+
+        //existing files in it can still be opened, since the directory is searchable
+        let fd = cage.open_syscall("/permdir/file", O_RDWR, 0);
+        assert!(fd >= 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+
+        //a directory that can't be searched hides everything below it
+        assert_eq!(cage.chmod_syscall("/permdir", S_IRUSR | S_IWUSR), 0);
+        assert_eq!(cage.open_syscall("/permdir/file", O_RDONLY, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.access_syscall("/permdir/file", F_OK), -(Errno::EACCES as i32));
+        assert_eq!(cage.chdir_syscall("/permdir"), -(Errno::EACCES as i32));
+
+        assert_eq!(cage.chmod_syscall("/permdir", S_IRWXA), 0);
+        assert_eq!(cage.rename_syscall("/permdir/file", "/movedfile"), 0);
+        assert_eq!(cage.unlink_syscall("/movedfile"), 0);
+        assert_eq!(cage.rmdir_syscall("/permdir/sub"), 0);
+
+        //directories can't be opened for writing
+        assert_eq!(cage.open_syscall("/permdir", O_WRONLY, 0), -(Errno::EISDIR as i32));
+        assert_eq!(cage.open_syscall("/permdir", O_RDWR, 0), -(Errno::EISDIR as i32));
+
+        assert_eq!(cage.chmod_syscall("/permfile", S_IRWXA), 0);
+        assert_eq!(cage.unlink_syscall("/permfile"), 0);
+        assert_eq!(cage.rmdir_syscall("/permdir"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_timestamps() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut statdata = StatData::default();
+        let mut before = StatData::default();
+        let mut dirbefore = StatData::default();
+        let mut buf = sizecbuf(4);
+        let nap = || interface::sleep_ms(interface::RustDuration::from_millis(5));
+
+        //a new file has all of its times set to when it was made, as does the directory it's made in
+        assert_eq!(cage.mkdir_syscall("/timedir", S_IRWXA), 0);
+        assert_eq!(cage.stat_syscall("/timedir", &mut dirbefore), 0);
+        nap();
+        let fd = cage.open_syscall("/timedir/file", O_CREAT | O_EXCL | O_RDWR, S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH);
+        assert_eq!(cage.fstat_syscall(fd, &mut before), 0);
+        assert_eq!(before.st_atim, before.st_mtim);
+        assert_eq!(before.st_mtim, before.st_ctim);
+        assert_eq!(cage.stat_syscall("/timedir", &mut statdata), 0);
+        assert!(statdata.st_mtim > dirbefore.st_mtim);
+        assert_eq!(statdata.st_mtim, statdata.st_ctim);
+        assert_eq!(statdata.st_atim, dirbefore.st_atim);
+
+        //writing modifies the file, reading accesses it, and chmod changes it
+        nap();
+        assert_eq!(cage.write_syscall(fd, str2cbuf("time"), 4), 4);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert!(statdata.st_mtim > before.st_mtim);
+        assert_eq!(statdata.st_ctim, statdata.st_mtim);
+        assert_eq!(statdata.st_atim, before.st_atim);
+        let written = statdata.st_mtim;
+        nap();
+        assert_eq!(cage.pread_syscall(fd, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert!(statdata.st_atim > written);
+        assert_eq!(statdata.st_mtim, written);
+        nap();
+        assert_eq!(cage.chmod_syscall("/timedir/file", S_IRUSR | S_IWUSR | S_IRGRP | S_IROTH), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert!(statdata.st_ctim > written);
+        assert_eq!(statdata.st_mtim, written);
+
+        //truncating modifies it too, and gives it its new size
+        nap();
+        assert_eq!(cage.ftruncate_syscall(fd, 2), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert!(statdata.st_mtim > written);
+        assert_eq!(statdata.st_size, 2);
+
+        //times can be set explicitly, to now, or be left alone, while the change time is always now
+        assert_eq!(cage.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(1000, 500), (2000, 0)]), 0), 0);
+        assert_eq!(cage.stat_syscall("/timedir/file", &mut statdata), 0);
+        assert_eq!(statdata.st_atim, (1000, 500));
+        assert_eq!(statdata.st_mtim, (2000, 0));
+        assert!(statdata.st_ctim > written);
+        assert_eq!(cage.futimens_syscall(fd, Some([(0, UTIME_OMIT), (0, UTIME_NOW)])), 0);
+        assert_eq!(cage.fstat_syscall(fd, &mut statdata), 0);
+        assert_eq!(statdata.st_atim, (1000, 500));
+        assert!(statdata.st_mtim > written);
+        assert_eq!(cage.utimes_syscall("/timedir/file", Some([(3000, 7), (4000, 8)])), 0);
+        assert_eq!(cage.stat_syscall("/timedir/file", &mut statdata), 0);
+        assert_eq!(statdata.st_atim, (3000, 7000));
+        assert_eq!(statdata.st_mtim, (4000, 8000));
+        assert_eq!(cage.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(0, UTIME_OMIT), (0, UTIME_OMIT)]), 0), 0);
+        assert_eq!(cage.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(0, 1_000_000_000), (0, 0)]), 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.utimes_syscall("/timedir/file", Some([(0, 1_000_000), (0, 0)])), -(Errno::EINVAL as i32));
+        assert_eq!(cage.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(u64::MAX / 1_000_000_000 + 1, 0), (0, 0)]), 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.utimes_syscall("/timedir/file", Some([(0, 0), (u64::MAX, 0)])), -(Errno::EINVAL as i32));
+        assert_eq!(cage.utimensat_syscall(AT_FDCWD, "/timedir/nothing", None, 0), -(Errno::ENOENT as i32));
+
+        //a relative path can be taken from an open directory, and a link can have its own times set
+        let dirfd = cage.open_syscall("/timedir", O_RDONLY, 0);
+        assert!(dirfd >= 0);
+        assert_eq!(cage.symlink_syscall("file", "/timedir/link"), 0);
+        assert_eq!(cage.utimensat_syscall(dirfd, "link", Some([(5000, 0), (6000, 0)]), AT_SYMLINK_NOFOLLOW), 0);
+        assert_eq!(cage.lstat_syscall("/timedir/link", &mut statdata), 0);
+        assert_eq!(statdata.st_mtim, (6000, 0));
+        assert_eq!(cage.stat_syscall("/timedir/link", &mut statdata), 0);
+        assert_eq!(statdata.st_mtim, (4000, 8000));
+        assert_eq!(cage.utimensat_syscall(fd, "link", None, 0), -(Errno::ENOTDIR as i32));
+        assert_eq!(cage.utimensat_syscall(dirfd, "link", None, 0x1), -(Errno::EINVAL as i32));
+
+        //someone else can only set the times to now, and only if they can write to the file
+        assert_eq!(cage.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        *cage2.creds.write().unwrap() = Credentials::new(2000, 2000);
+        assert_eq!(cage2.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(0, 0), (0, 0)]), 0), -(Errno::EPERM as i32));
+        assert_eq!(cage2.utimensat_syscall(AT_FDCWD, "/timedir/file", None, 0), -(Errno::EACCES as i32));
+        assert_eq!(cage.chmod_syscall("/timedir/file", S_IRWXA), 0);
+        assert_eq!(cage2.utimensat_syscall(AT_FDCWD, "/timedir/file", Some([(0, UTIME_NOW), (0, UTIME_NOW)]), 0), 0);
+        assert_eq!(cage2.exit_syscall(), 0);
+
+        //removing an entry modifies the directory, and changes what was removed if it still has links
+        assert_eq!(cage.link_syscall("/timedir/file", "/timedir/file2"), 0);
+        assert_eq!(cage.stat_syscall("/timedir", &mut dirbefore), 0);
+        assert_eq!(cage.stat_syscall("/timedir/file", &mut before), 0);
+        nap();
+        assert_eq!(cage.unlink_syscall("/timedir/file2"), 0);
+        assert_eq!(cage.stat_syscall("/timedir", &mut statdata), 0);
+        assert!(statdata.st_mtim > dirbefore.st_mtim);
+        assert_eq!(cage.stat_syscall("/timedir/file", &mut statdata), 0);
+        assert!(statdata.st_ctim > before.st_ctim);
+        assert_eq!(statdata.st_mtim, before.st_mtim);
+
+        assert_eq!(cage.close_syscall(dirfd), 0);
+        assert_eq!(cage.close_syscall(fd), 0);
+        assert_eq!(cage.unlink_syscall("/timedir/link"), 0);
+        assert_eq!(cage.unlink_syscall("/timedir/file"), 0);
+        assert_eq!(cage.rmdir_syscall("/timedir"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_metadata_upgrade() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //metadata persisted before it was versioned kept its times in seconds, which are converted when it's restored
+        let mut metadata = FS_METADATA.write().unwrap();
+        let mut old = FilesystemMetadata::blank_fs_init();
+        old.version = 0;
+        if let Some(Inode::Dir(rootdir)) = old.inodetable.get_mut(&ROOTDIRECTORYINODE) {
+            rootdir.atime = 1_600_000_000;
+            rootdir.mtime = 1_600_000_001;
+            rootdir.ctime = 1_600_000_002;
+        }
+        persist_metadata(&old);
+        let mut restored = FilesystemMetadata::blank_fs_init();
+        restore_metadata(&mut restored);
+        assert_eq!(restored.version, METADATA_VERSION);
+        let roottimes = restored.inodetable.get(&ROOTDIRECTORYINODE).unwrap().times();
+        assert_eq!(roottimes, (1_600_000_000_000_000_000, 1_600_000_001_000_000_000, 1_600_000_002_000_000_000));
+
+        //while current metadata is restored as it was
+        persist_metadata(&metadata);
+        let mut restored = FilesystemMetadata::blank_fs_init();
+        restore_metadata(&mut restored);
+        assert_eq!(restored.inodetable.get(&ROOTDIRECTORYINODE).unwrap().times(), metadata.inodetable.get(&ROOTDIRECTORYINODE).unwrap().times());
+        drop(metadata);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
 }
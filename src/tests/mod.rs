@@ -1,9 +1,20 @@
 mod fs_tests;
+mod networking_tests;
 mod pipe_tests;
+mod stdio_tests;
 
 use crate::interface;
 use crate::safeposix::{cage::*, filesystem::*};
 
+//lind keeps its cages and filesystem in global tables, so test modules which init and finalize
+//lind must take this lock to keep from running at the same time as each other
+pub static TESTMUTEX: interface::RustLazyGlobal<std::sync::Mutex<()>> = interface::RustLazyGlobal::new(|| std::sync::Mutex::new(()));
+
+pub fn lock_tests() -> std::sync::MutexGuard<'static, ()> {
+    //a failed test poisons the lock, which shouldn't stop the other modules from running
+    TESTMUTEX.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 pub fn str2cbuf(ruststr: &str) -> *mut u8 {
     let cbuflenexpected = ruststr.len();
     let (ptr, len, _) = ruststr.to_string().into_raw_parts();
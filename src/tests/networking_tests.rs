@@ -0,0 +1,1790 @@
+#[cfg(test)]
+mod net_tests {
+    use crate::interface;
+    use crate::safeposix::{cage::*, dispatcher::*, filesystem};
+    use super::super::*;
+
+    #[test]
+    pub fn net_tests() {
+        let _guard = lock_tests();
+
+        ut_lind_net_socket();
+        ut_lind_net_bind();
+        ut_lind_net_listen();
+        ut_lind_net_tcp_connect_basic();
+        ut_lind_net_tcp_read_write();
+        ut_lind_net_tcp_fork_dup();
+        ut_lind_net_getsockname_getpeername();
+        ut_lind_net_udp_sendto_recvfrom();
+        ut_lind_net_udp_connect();
+        ut_lind_net_udp_ipv6();
+        ut_lind_net_loopback_host_client();
+        ut_lind_net_unix_stream();
+        ut_lind_net_unix_errors();
+        ut_lind_net_unix_dgram();
+        ut_lind_net_unix_dgram_full();
+        ut_lind_net_emulated_eof_epipe();
+        ut_lind_net_socketpair_stream();
+        ut_lind_net_socketpair_dgram();
+        ut_lind_net_socketpair_fork();
+        ut_lind_net_socketpair_full_duplex();
+        ut_lind_net_sockopt();
+        ut_lind_net_sockopt_reuseport();
+        ut_lind_net_shutdown_emulated();
+        ut_lind_net_shutdown_wakes_recv();
+        ut_lind_net_shutdown_host();
+        ut_lind_net_blocked_calls_release_descriptor();
+        ut_lind_net_poll_fd_types();
+        ut_lind_net_poll_blocking();
+        ut_lind_net_select();
+        ut_lind_net_select_blocking();
+        ut_lind_net_epoll_ctl();
+        ut_lind_net_epoll_triggering();
+        ut_lind_net_epoll_blocking();
+        ut_lind_net_epoll_fd_reuse();
+        ut_lind_net_nonblocking_emulated();
+        ut_lind_net_nonblocking_host();
+    }
+
+    //builds a 127.0.0.1 address with the given port
+    pub fn loopback_addr(port: u16) -> interface::GenSockaddr {
+        let mut addr = interface::GenSockaddr::V4(interface::SockaddrV4 {
+            sin_family: AF_INET as u16,
+            sin_addr: interface::V4Addr {s_addr: u32::from_ne_bytes([127, 0, 0, 1])},
+            ..Default::default()
+        });
+        addr.set_port(port);
+        addr
+    }
+
+    //builds a ::1 address with the given port
+    pub fn loopback_addr_v6(port: u16) -> interface::GenSockaddr {
+        let mut s6_addr = [0u8; 16];
+        s6_addr[15] = 1;
+        let mut addr = interface::GenSockaddr::V6(interface::SockaddrV6 {
+            sin6_family: AF_INET6 as u16,
+            sin6_addr: interface::V6Addr {s6_addr: s6_addr},
+            ..Default::default()
+        });
+        addr.set_port(port);
+        addr
+    }
+
+    //binds fd to addr with the port left to the host, giving back the address it ended up with
+    pub fn bind_ephemeral(cage: &Cage, fd: i32, addr: &interface::GenSockaddr) -> interface::GenSockaddr {
+        assert_eq!(cage.bind_syscall(fd, addr), 0);
+        let mut bound = *addr;
+        assert_eq!(cage.getsockname_syscall(fd, &mut bound), 0);
+        assert_ne!(bound.port(), 0);
+        bound
+    }
+
+    pub fn setsockopt_int(cage: &Cage, fd: i32, level: i32, optname: i32, value: i32) -> i32 {
+        cage.setsockopt_syscall(fd, level, optname, &value as *const i32 as *const u8, 4)
+    }
+
+    //returns the option's value, or the error if getsockopt failed
+    pub fn getsockopt_int(cage: &Cage, fd: i32, level: i32, optname: i32) -> i32 {
+        let mut value: i32 = -1;
+        let mut optlen: u32 = 4;
+        let ret = cage.getsockopt_syscall(fd, level, optname, &mut value as *mut i32 as *mut u8, &mut optlen);
+        if ret != 0 {return ret;}
+        assert_eq!(optlen, 4);
+        value
+    }
+
+    pub fn ut_lind_net_socket() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let sockfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert!(sockfd > 0);
+        let sockfd2 = cage.socket_syscall(AF_INET, SOCK_STREAM, IPPROTO_TCP);
+        assert!(sockfd2 > 0);
+        assert_ne!(sockfd, sockfd2);
+
+        assert_eq!(cage.socket_syscall(-1, SOCK_STREAM, 0), -(Errno::EAFNOSUPPORT as i32));
+        assert_eq!(cage.socket_syscall(AF_INET, SOCK_STREAM, IPPROTO_UDP), -(Errno::EPROTONOSUPPORT as i32));
+
+        //socket calls on things that are not sockets
+        assert_eq!(cage.listen_syscall(0, 10), -(Errno::ENOTSOCK as i32));
+        assert_eq!(cage.listen_syscall(123, 10), -(Errno::EBADF as i32));
+
+        assert_eq!(cage.close_syscall(sockfd), 0);
+        assert_eq!(cage.close_syscall(sockfd2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_bind() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let sockfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, sockfd, &loopback_addr(0));
+        //a socket can only be bound once
+        assert_eq!(cage.bind_syscall(sockfd, &addr), -(Errno::EINVAL as i32));
+
+        //the address is still in use by the first socket
+        let sockfd2 = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(sockfd2, &addr), -(Errno::EADDRINUSE as i32));
+
+        assert_eq!(cage.close_syscall(sockfd), 0);
+        assert_eq!(cage.close_syscall(sockfd2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_listen() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let sockfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let mut addr = loopback_addr(0);
+
+        //accepting and sending need a listening or connected socket
+        assert_eq!(cage.accept_syscall(sockfd, &mut addr), -(Errno::EINVAL as i32));
+        assert_eq!(cage.send_syscall(sockfd, str2cbuf("test"), 4, 0), -(Errno::ENOTCONN as i32));
+
+        //listen binds an unbound socket to an ephemeral port, and may be called again
+        assert_eq!(cage.listen_syscall(sockfd, 10), 0);
+        assert_eq!(cage.listen_syscall(sockfd, 20), 0);
+        assert_eq!(cage.connect_syscall(sockfd, &loopback_addr(0)), -(Errno::EISCONN as i32));
+
+        assert_eq!(cage.close_syscall(sockfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_tcp_connect_basic() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        assert_eq!(cage.fork_syscall(2), 0);
+
+        let thread = std::thread::spawn(move || {
+            let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+            assert_eq!(cage2.close_syscall(serverfd), 0);
+
+            let clientfd = cage2.socket_syscall(AF_INET, SOCK_STREAM, 0);
+            assert_eq!(cage2.connect_syscall(clientfd, &addr), 0);
+            assert_eq!(cage2.send_syscall(clientfd, str2cbuf("hello"), 5, 0), 5);
+
+            let mut buf = sizecbuf(5);
+            assert_eq!(cage2.recv_syscall(clientfd, buf.as_mut_ptr(), 5, 0), 5);
+            assert_eq!(cbuf2str(&buf), "world");
+
+            assert_eq!(cage2.close_syscall(clientfd), 0);
+            assert_eq!(cage2.exit_syscall(), 0);
+        });
+
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+        assert_eq!(peeraddr.get_family(), AF_INET as u16);
+
+        let mut buf = sizecbuf(5);
+        assert_eq!(cage.recv_syscall(connfd, buf.as_mut_ptr(), 5, 0), 5);
+        assert_eq!(cbuf2str(&buf), "hello");
+        assert_eq!(cage.send_syscall(connfd, str2cbuf("world"), 5, 0), 5);
+
+        thread.join().unwrap();
+
+        //the client has closed its end, so we read end of file
+        assert_eq!(cage.recv_syscall(connfd, buf.as_mut_ptr(), 5, 0), 0);
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_tcp_read_write() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        //connecting completes against the listen backlog before we accept
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), -(Errno::EISCONN as i32));
+
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+
+        //read and write on a connected socket behave like recv and send
+        assert_eq!(cage.write_syscall(clientfd, str2cbuf("via write"), 9), 9);
+        let mut buf = sizecbuf(9);
+        assert_eq!(cage.read_syscall(connfd, buf.as_mut_ptr(), 9), 9);
+        assert_eq!(cbuf2str(&buf), "via write");
+
+        assert_eq!(cage.close_syscall(clientfd), 0);
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_tcp_fork_dup() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+
+        //the host socket stays open while any duplicate of the descriptor is still around
+        let dupfd = cage.dup_syscall(clientfd, None);
+        assert!(dupfd > 0);
+        assert_eq!(cage.fork_syscall(2), 0);
+        assert_eq!(cage.close_syscall(clientfd), 0);
+
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(cage2.send_syscall(clientfd, str2cbuf("fork"), 4, 0), 4);
+        assert_eq!(cage.send_syscall(dupfd, str2cbuf("dup"), 3, 0), 3);
+
+        let mut buf = sizecbuf(7);
+        let mut received = 0;
+        while received < 7 {
+            let ret = cage.recv_syscall(connfd, unsafe{buf.as_mut_ptr().add(received)}, 7 - received, 0);
+            assert!(ret > 0);
+            received += ret as usize;
+        }
+        assert_eq!(cbuf2str(&buf), "forkdup");
+
+        //once every reference is closed the peer sees end of file
+        assert_eq!(cage2.exit_syscall(), 0);
+        assert_eq!(cage.close_syscall(dupfd), 0);
+        assert_eq!(cage.recv_syscall(connfd, buf.as_mut_ptr(), 7, 0), 0);
+
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_getsockname_getpeername() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let mut retaddr = loopback_addr(1);
+
+        //unbound sockets report the unspecified address, and nothing is connected yet
+        assert_eq!(cage.getsockname_syscall(serverfd, &mut retaddr), 0);
+        assert_eq!(retaddr, interface::GenSockaddr::new_unspecified(AF_INET as u16).unwrap());
+        assert_eq!(cage.getpeername_syscall(serverfd, &mut retaddr), -(Errno::ENOTCONN as i32));
+
+        //binding to port 0 leaves the port to the host, and getsockname says which it picked
+        assert_eq!(cage.bind_syscall(serverfd, &loopback_addr(0)), 0);
+        assert_eq!(cage.getsockname_syscall(serverfd, &mut retaddr), 0);
+        assert_ne!(retaddr.port(), 0);
+        let addr = loopback_addr(retaddr.port());
+        assert_eq!(retaddr, addr);
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        assert_eq!(cage.getpeername_syscall(clientfd, &mut retaddr), 0);
+        assert_eq!(retaddr, addr);
+
+        //the client was implicitly bound to an ephemeral port, which the server sees as its peer
+        let mut clientaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(clientfd, &mut clientaddr), 0);
+        assert_ne!(clientaddr.port(), 0);
+
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+        assert_eq!(peeraddr, clientaddr);
+        assert_eq!(cage.getpeername_syscall(connfd, &mut retaddr), 0);
+        assert_eq!(retaddr, clientaddr);
+
+        assert_eq!(cage.getsockname_syscall(0, &mut retaddr), -(Errno::ENOTSOCK as i32));
+
+        assert_eq!(cage.close_syscall(clientfd), 0);
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_udp_sendto_recvfrom() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let receiverfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let senderfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, IPPROTO_UDP);
+        let addr = bind_ephemeral(&cage, receiverfd, &loopback_addr(0));
+
+        //datagram sockets have no peer until connected, and never listen
+        assert_eq!(cage.send_syscall(senderfd, str2cbuf("test"), 4, 0), -(Errno::EDESTADDRREQ as i32));
+        assert_eq!(cage.listen_syscall(receiverfd, 10), -(Errno::EOPNOTSUPP as i32));
+
+        //nothing has been sent yet
+        let mut buf = sizecbuf(16);
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 16, MSG_DONTWAIT, &mut None), -(Errno::EAGAIN as i32));
+
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("first"), 5, 0, &addr), 5);
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("second"), 6, 0, &addr), 6);
+
+        //the sender was bound when it first sent, and that is who the receiver hears from
+        let mut senderaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(senderfd, &mut senderaddr), 0);
+        assert_ne!(senderaddr.port(), 0);
+
+        //peeking leaves the datagram in place
+        let mut srcaddr = loopback_addr(0);
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 16, MSG_PEEK, &mut Some(&mut srcaddr)), 5);
+        assert_eq!(cbuf2str(&buf[0..5]), "first");
+        assert_eq!(srcaddr.port(), senderaddr.port());
+
+        //each receive returns exactly one datagram, even with room for more
+        let mut buf = sizecbuf(16);
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 16, 0, &mut Some(&mut srcaddr)), 5);
+        assert_eq!(cbuf2str(&buf[0..5]), "first");
+
+        //a short buffer truncates the datagram, and the rest of it is gone
+        let mut buf = sizecbuf(3);
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 3, 0), 3);
+        assert_eq!(cbuf2str(&buf), "sec");
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 3, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+
+        //datagrams over the maximum udp payload size are rejected
+        let bigbuf = vec![0u8; 70000];
+        assert_eq!(cage.sendto_syscall(senderfd, bigbuf.as_ptr(), 70000, 0, &addr), -(Errno::EMSGSIZE as i32));
+
+        assert_eq!(cage.close_syscall(senderfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_udp_connect() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let receiverfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let receiverfd2 = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let senderfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let addr = bind_ephemeral(&cage, receiverfd, &loopback_addr(0));
+        let addr2 = bind_ephemeral(&cage, receiverfd2, &loopback_addr(0));
+
+        //connecting a datagram socket sets the peer for send, and may be done again
+        assert_eq!(cage.connect_syscall(senderfd, &addr), 0);
+        assert_eq!(cage.send_syscall(senderfd, str2cbuf("one"), 3, 0), 3);
+        assert_eq!(cage.connect_syscall(senderfd, &addr2), 0);
+        assert_eq!(cage.write_syscall(senderfd, str2cbuf("two"), 3), 3);
+
+        let mut retaddr = loopback_addr(0);
+        assert_eq!(cage.getpeername_syscall(senderfd, &mut retaddr), 0);
+        assert_eq!(retaddr, addr2);
+
+        let mut buf = sizecbuf(3);
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 3, 0), 3);
+        assert_eq!(cbuf2str(&buf), "one");
+        assert_eq!(cage.read_syscall(receiverfd2, buf.as_mut_ptr(), 3), 3);
+        assert_eq!(cbuf2str(&buf), "two");
+
+        assert_eq!(cage.close_syscall(senderfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_udp_ipv6() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let receiverfd = cage.socket_syscall(AF_INET6, SOCK_DGRAM, 0);
+        let senderfd = cage.socket_syscall(AF_INET6, SOCK_DGRAM, 0);
+        let addr = bind_ephemeral(&cage, receiverfd, &loopback_addr_v6(0));
+
+        //addresses must match the domain of the socket
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("v4"), 2, 0, &loopback_addr(addr.port())), -(Errno::EAFNOSUPPORT as i32));
+
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("v6"), 2, 0, &addr), 2);
+
+        let mut buf = sizecbuf(2);
+        let mut srcaddr = loopback_addr(0);
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 2, 0, &mut Some(&mut srcaddr)), 2);
+        assert_eq!(cbuf2str(&buf), "v6");
+        assert_eq!(srcaddr.get_family(), AF_INET6 as u16);
+
+        let mut senderaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(senderfd, &mut senderaddr), 0);
+        assert_eq!(srcaddr.port(), senderaddr.port());
+
+        assert_eq!(cage.close_syscall(senderfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_loopback_host_client() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //a socket bound to every address takes connections both from within lind and from the host
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let wildcard = interface::GenSockaddr::new_unspecified(AF_INET as u16).unwrap();
+        let port = bind_ephemeral(&cage, serverfd, &wildcard).port();
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &loopback_addr(port)), 0);
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+
+        let mut clientaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(clientfd, &mut clientaddr), 0);
+        assert_eq!(peeraddr, clientaddr);
+
+        let thread = std::thread::spawn(move || {
+            use std::io::Write;
+            let mut hoststream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+            hoststream.write_all(b"host").unwrap();
+        });
+
+        let hostconnfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(hostconnfd > 0);
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.recv_syscall(hostconnfd, buf.as_mut_ptr(), 4, MSG_WAITALL), 4);
+        assert_eq!(cbuf2str(&buf), "host");
+        thread.join().unwrap();
+
+        assert_eq!(cage.close_syscall(hostconnfd), 0);
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(clientfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_unix_stream() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert!(serverfd > 0);
+        let addr = interface::GenSockaddr::new_unix("/unixstreamsock");
+        assert_eq!(cage.bind_syscall(serverfd, &addr), 0);
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        //binding creates a socket file
+        let mut statdata = StatData::default();
+        assert_eq!(cage.stat_syscall("/unixstreamsock", &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFSOCK as u32 | (S_IRWXA & !DEFAULT_UMASK));
+        assert_eq!(cage.open_syscall("/unixstreamsock", O_RDONLY, 0), -(Errno::ENXIO as i32));
+
+        assert_eq!(cage.fork_syscall(2), 0);
+
+        let thread = std::thread::spawn(move || {
+            let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+            assert_eq!(cage2.close_syscall(serverfd), 0);
+
+            let clientfd = cage2.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+            assert_eq!(cage2.connect_syscall(clientfd, &addr), 0);
+            assert_eq!(cage2.write_syscall(clientfd, str2cbuf("unix"), 4), 4);
+
+            let mut buf = sizecbuf(5);
+            assert_eq!(cage2.read_syscall(clientfd, buf.as_mut_ptr(), 5), 5);
+            assert_eq!(cbuf2str(&buf), "reply");
+
+            assert_eq!(cage2.close_syscall(clientfd), 0);
+            assert_eq!(cage2.exit_syscall(), 0);
+        });
+
+        let mut peeraddr = interface::GenSockaddr::new_unix("");
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+        //the client never bound, so it has no name
+        assert_eq!(peeraddr.get_family(), AF_UNIX as u16);
+        assert_eq!(peeraddr.path(), "");
+
+        let mut localaddr = interface::GenSockaddr::new_unix("");
+        assert_eq!(cage.getsockname_syscall(connfd, &mut localaddr), 0);
+        assert_eq!(localaddr.path(), "/unixstreamsock");
+
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.read_syscall(connfd, buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cbuf2str(&buf), "unix");
+        assert_eq!(cage.write_syscall(connfd, str2cbuf("reply"), 5), 5);
+
+        thread.join().unwrap();
+        assert_eq!(cage.read_syscall(connfd, buf.as_mut_ptr(), 4), 0);
+
+        assert_eq!(cage.close_syscall(connfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+        assert_eq!(cage.unlink_syscall("/unixstreamsock"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_unix_errors() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let addr = interface::GenSockaddr::new_unix("/unixerrsock");
+        let clientfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), -(Errno::ENOENT as i32));
+        assert_eq!(cage.listen_syscall(clientfd, 10), -(Errno::EINVAL as i32));
+        assert_eq!(cage.socket_syscall(AF_UNIX, SOCK_STREAM, IPPROTO_TCP), -(Errno::EPROTONOSUPPORT as i32));
+
+        let serverfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(serverfd, &addr), 0);
+
+        //bound but not listening
+        assert_eq!(cage.connect_syscall(clientfd, &addr), -(Errno::ECONNREFUSED as i32));
+
+        //the socket file is in the way even for another socket
+        let otherfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(otherfd, &addr), -(Errno::EADDRINUSE as i32));
+        assert_eq!(cage.bind_syscall(otherfd, &interface::GenSockaddr::new_unix("/nonexistentdir/sock")), -(Errno::ENOENT as i32));
+        assert_eq!(cage.bind_syscall(otherfd, &loopback_addr(0)), -(Errno::EAFNOSUPPORT as i32));
+
+        //once the socket is closed its file remains, but nothing answers on it
+        assert_eq!(cage.close_syscall(serverfd), 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), -(Errno::ECONNREFUSED as i32));
+        assert_eq!(cage.unlink_syscall("/unixerrsock"), 0);
+
+        //a regular file is not a socket
+        let filefd = cage.open_syscall("/unixerrfile", O_CREAT | O_RDWR, S_IRWXA);
+        assert_eq!(cage.close_syscall(filefd), 0);
+        assert_eq!(cage.connect_syscall(clientfd, &interface::GenSockaddr::new_unix("/unixerrfile")), -(Errno::ECONNREFUSED as i32));
+        assert_eq!(cage.unlink_syscall("/unixerrfile"), 0);
+
+        assert_eq!(cage.close_syscall(otherfd), 0);
+        assert_eq!(cage.close_syscall(clientfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_unix_dgram() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let receiverfd = cage.socket_syscall(AF_UNIX, SOCK_DGRAM, 0);
+        let senderfd = cage.socket_syscall(AF_UNIX, SOCK_DGRAM, 0);
+        let recvaddr = interface::GenSockaddr::new_unix("/unixdgramrecv");
+        let sendaddr = interface::GenSockaddr::new_unix("/unixdgramsend");
+        assert_eq!(cage.bind_syscall(receiverfd, &recvaddr), 0);
+        assert_eq!(cage.bind_syscall(senderfd, &sendaddr), 0);
+
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("first"), 5, 0, &recvaddr), 5);
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("second"), 6, 0, &recvaddr), 6);
+
+        //datagrams keep their boundaries
+        let mut buf = sizecbuf(10);
+        let mut srcaddr = interface::GenSockaddr::new_unix("");
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 10, 0, &mut Some(&mut srcaddr)), 5);
+        assert_eq!(srcaddr.path(), "/unixdgramsend");
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 10, 0, &mut None), 6);
+        assert_eq!(cage.recvfrom_syscall(receiverfd, buf.as_mut_ptr(), 10, MSG_DONTWAIT, &mut None), -(Errno::EAGAIN as i32));
+
+        //replies go back through the connected default peer
+        assert_eq!(cage.connect_syscall(receiverfd, &sendaddr), 0);
+        assert_eq!(cage.send_syscall(receiverfd, str2cbuf("back"), 4, 0), 4);
+        assert_eq!(cage.recv_syscall(senderfd, buf.as_mut_ptr(), 10, 0), 4);
+
+        //a stream socket file is the wrong type to send datagrams to
+        let streamfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(streamfd, &interface::GenSockaddr::new_unix("/unixdgramstream")), 0);
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("x"), 1, 0, &interface::GenSockaddr::new_unix("/unixdgramstream")), -(Errno::ECONNREFUSED as i32));
+
+        assert_eq!(cage.close_syscall(streamfd), 0);
+        assert_eq!(cage.close_syscall(senderfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd), 0);
+        assert_eq!(cage.unlink_syscall("/unixdgramstream"), 0);
+        assert_eq!(cage.unlink_syscall("/unixdgramsend"), 0);
+        assert_eq!(cage.unlink_syscall("/unixdgramrecv"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_unix_dgram_full() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let receiverfd = cage.socket_syscall(AF_UNIX, SOCK_DGRAM, 0);
+        let senderfd = cage.socket_syscall(AF_UNIX, SOCK_DGRAM, 0);
+        let recvaddr = interface::GenSockaddr::new_unix("/unixdgramfull");
+        assert_eq!(cage.bind_syscall(receiverfd, &recvaddr), 0);
+
+        //datagrams aren't dropped once the receiver's queue is full, a nonblocking sender is told to try again
+        let datagram = vec![7u8; 100000];
+        assert_eq!(cage.sendto_syscall(senderfd, datagram.as_ptr(), 100000, MSG_DONTWAIT, &recvaddr), 100000);
+        assert_eq!(cage.sendto_syscall(senderfd, datagram.as_ptr(), 100000, MSG_DONTWAIT, &recvaddr), 100000);
+        assert_eq!(cage.sendto_syscall(senderfd, datagram.as_ptr(), 100000, MSG_DONTWAIT, &recvaddr), -(Errno::EAGAIN as i32));
+
+        //while a blocking sender waits until a datagram is received to make room
+        let cage2 = cage.clone();
+        let sent = interface::RustRfc::new(interface::RustAtomicBool::new(false));
+        let sent2 = sent.clone();
+        let thread = std::thread::spawn(move || {
+            let datagram = vec![8u8; 100000];
+            let recvaddr = interface::GenSockaddr::new_unix("/unixdgramfull");
+            assert_eq!(cage2.sendto_syscall(senderfd, datagram.as_ptr(), 100000, 0, &recvaddr), 100000);
+            sent2.store(true, interface::RustAtomicOrdering::SeqCst);
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert!(!sent.load(interface::RustAtomicOrdering::SeqCst));
+        let mut buf = sizecbuf(100000);
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 100000, 0), 100000);
+        thread.join().unwrap();
+        assert!(sent.load(interface::RustAtomicOrdering::SeqCst));
+
+        //and every datagram arrives, in order
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 100000, 0), 100000);
+        assert_eq!(buf[0], 7);
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 100000, 0), 100000);
+        assert_eq!(buf[0], 8);
+        assert_eq!(cage.recv_syscall(receiverfd, buf.as_mut_ptr(), 100000, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+
+        assert_eq!(cage.close_syscall(senderfd), 0);
+        assert_eq!(cage.close_syscall(receiverfd), 0);
+        assert_eq!(cage.unlink_syscall("/unixdgramfull"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_emulated_eof_epipe() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+
+        //data written before the close is still read before end of file
+        assert_eq!(cage.send_syscall(connfd, str2cbuf("last"), 4, 0), 4);
+        assert_eq!(cage.close_syscall(connfd), 0);
+
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, MSG_PEEK), 4);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "last");
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, 0), 0);
+        assert_eq!(cage.send_syscall(clientfd, str2cbuf("gone"), 4, 0), -(Errno::EPIPE as i32));
+
+        assert_eq!(cage.close_syscall(clientfd), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        //the port is free again once the listener is closed
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(serverfd, &addr), 0);
+        assert_eq!(cage.close_syscall(serverfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_socketpair_stream() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_INET, SOCK_STREAM, 0, &mut sv), -(Errno::EOPNOTSUPP as i32));
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, IPPROTO_TCP, &mut sv), -(Errno::EPROTONOSUPPORT as i32));
+
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        assert!(sv.sock1 > 0 && sv.sock2 > 0);
+        assert_ne!(sv.sock1, sv.sock2);
+
+        //both ends can talk, and the pair is already connected
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("ping"), 4, 0), 4);
+        assert_eq!(cage.write_syscall(sv.sock2, str2cbuf("pong"), 4), 4);
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.read_syscall(sv.sock2, buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cbuf2str(&buf), "ping");
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "pong");
+
+        let mut peeraddr = loopback_addr(0);
+        assert_eq!(cage.getpeername_syscall(sv.sock1, &mut peeraddr), 0);
+        assert_eq!(peeraddr.get_family(), AF_UNIX as u16);
+        assert_eq!(cage.connect_syscall(sv.sock1, &interface::GenSockaddr::new_unix("/anything")), -(Errno::EISCONN as i32));
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+
+        //a stream pair doesn't keep message boundaries
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("ab"), 2, 0), 2);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("cd"), 2, 0), 2);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "abcd");
+
+        //the peer sees end of file only once every duplicate of the other end is closed
+        let dupfd = cage.dup_syscall(sv.sock1, None);
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.close_syscall(dupfd), 0);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, 0), 0);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("gone"), 4, 0), -(Errno::EPIPE as i32));
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_socketpair_dgram() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_DGRAM, 0, &mut sv), 0);
+
+        //each message is received whole and separately, with whatever doesn't fit discarded
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("first"), 5, 0), 5);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("second"), 6, 0), 6);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf(""), 0, 0), 0);
+        let mut buf = sizecbuf(10);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 10, MSG_PEEK), 5);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 10, 0), 5);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 3, 0), 3);
+        assert_eq!(cbuf2str(&buf[..3]), "sec");
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 10, MSG_DONTWAIT), 0);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 10, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+
+        //too large to ever fit
+        let bigbuf = vec![0u8; EMULATEDBUFFERSIZE + 1];
+        assert_eq!(cage.send_syscall(sv.sock2, bigbuf.as_ptr(), bigbuf.len(), 0), -(Errno::EMSGSIZE as i32));
+
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("x"), 1, 0), -(Errno::EPIPE as i32));
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 10, 0), 0);
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_socketpair_fork() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        assert_eq!(cage.fork_syscall(2), 0);
+
+        //like a pipe, each side closes the end it doesn't use
+        let thread = std::thread::spawn(move || {
+            let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+            assert_eq!(cage2.close_syscall(sv.sock1), 0);
+
+            let mut buf = sizecbuf(7);
+            assert_eq!(cage2.recv_syscall(sv.sock2, buf.as_mut_ptr(), 7, MSG_WAITALL), 7);
+            assert_eq!(cbuf2str(&buf), "request");
+            assert_eq!(cage2.send_syscall(sv.sock2, str2cbuf("response"), 8, 0), 8);
+            assert_eq!(cage2.exit_syscall(), 0);
+        });
+
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("request"), 7, 0), 7);
+
+        let mut buf = sizecbuf(8);
+        let mut received = 0;
+        while received < 8 {
+            let ret = cage.recv_syscall(sv.sock1, unsafe{buf.as_mut_ptr().add(received)}, 8 - received, 0);
+            assert!(ret > 0);
+            received += ret as usize;
+        }
+        assert_eq!(cbuf2str(&buf), "response");
+
+        //the child exiting closes its end
+        thread.join().unwrap();
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 8, 0), 0);
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_socketpair_full_duplex() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(4);
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+
+        //one thread of the cage is blocked receiving on a socket while another sends on it
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            let mut buf = sizecbuf(4);
+            assert_eq!(cage2.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+            assert_eq!(cbuf2str(&buf), "pong");
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("ping"), 4, 0), 4);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "ping");
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("pong"), 4, 0), 4);
+        thread.join().unwrap();
+
+        //a send blocked on a full socket neither holds up the rest of the cage nor hides that the socket is readable
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            let bigbuf = vec![b'a'; EMULATEDBUFFERSIZE + 4];
+            assert_eq!(cage2.write_syscall(sv.sock1, bigbuf.as_ptr(), EMULATEDBUFFERSIZE + 4), (EMULATEDBUFFERSIZE + 4) as i32);
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert!(!thread.is_finished());
+        let otherfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert!(otherfd > 0);
+        assert_eq!(cage.close_syscall(otherfd), 0);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        let mut fds = [pollfd(sv.sock1, POLLIN | POLLOUT)];
+        assert_eq!(cage.poll_syscall(&mut fds, 0), 1);
+        assert_eq!(fds[0].revents, POLLIN);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+
+        let mut bigbuf = vec![0u8; EMULATEDBUFFERSIZE + 4];
+        let mut total = 0;
+        while total < EMULATEDBUFFERSIZE + 4 {
+            let bytesreceived = cage.recv_syscall(sv.sock2, bigbuf[total..].as_mut_ptr(), EMULATEDBUFFERSIZE + 4 - total, 0);
+            assert!(bytesreceived > 0);
+            total += bytesreceived as usize;
+        }
+        thread.join().unwrap();
+
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_sockopt() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let tcpfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let udpfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let unixfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+
+        assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_TYPE), SOCK_STREAM);
+        assert_eq!(getsockopt_int(&cage, udpfd, SOL_SOCKET, SO_TYPE), SOCK_DGRAM);
+        assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_ERROR), 0);
+        assert_eq!(setsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_ERROR, 1), -(Errno::ENOPROTOOPT as i32));
+        assert_eq!(setsockopt_int(&cage, tcpfd, SOL_SOCKET, 1234, 1), -(Errno::ENOPROTOOPT as i32));
+        assert_eq!(setsockopt_int(&cage, tcpfd, 1234, SO_REUSEADDR, 1), -(Errno::ENOPROTOOPT as i32));
+        assert_eq!(setsockopt_int(&cage, 0, SOL_SOCKET, SO_REUSEADDR, 1), -(Errno::ENOTSOCK as i32));
+
+        //boolean options
+        for optname in [SO_REUSEADDR, SO_KEEPALIVE].iter() {
+            assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, *optname), 0);
+            assert_eq!(setsockopt_int(&cage, tcpfd, SOL_SOCKET, *optname, 5), 0);
+            assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, *optname), 1);
+        }
+        assert_eq!(setsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_KEEPALIVE, 0), 0);
+        assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_KEEPALIVE), 0);
+        assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_REUSEADDR), 1);
+
+        assert_eq!(cage.setsockopt_syscall(tcpfd, SOL_SOCKET, SO_REUSEADDR, str2cbuf("a"), 1), -(Errno::EINVAL as i32));
+
+        //tcp options only apply to inet stream sockets
+        assert_eq!(setsockopt_int(&cage, tcpfd, IPPROTO_TCP, TCP_NODELAY, 1), 0);
+        assert_eq!(getsockopt_int(&cage, tcpfd, IPPROTO_TCP, TCP_NODELAY), 1);
+        assert_eq!(setsockopt_int(&cage, udpfd, IPPROTO_TCP, TCP_NODELAY, 1), -(Errno::ENOPROTOOPT as i32));
+        assert_eq!(setsockopt_int(&cage, unixfd, IPPROTO_TCP, TCP_NODELAY, 1), -(Errno::EOPNOTSUPP as i32));
+
+        //buffer sizes are doubled, within limits
+        assert_eq!(setsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_SNDBUF, 10000), 0);
+        assert_eq!(getsockopt_int(&cage, tcpfd, SOL_SOCKET, SO_SNDBUF), 20000);
+        assert_eq!(setsockopt_int(&cage, unixfd, SOL_SOCKET, SO_RCVBUF, 1), 0);
+        assert_eq!(getsockopt_int(&cage, unixfd, SOL_SOCKET, SO_RCVBUF), RCVBUF_MIN);
+        assert_eq!(getsockopt_int(&cage, unixfd, SOL_SOCKET, SO_SNDBUF), EMULATEDBUFFERSIZE as i32);
+
+        let linger = interface::Linger {l_onoff: 1, l_linger: 5};
+        assert_eq!(cage.setsockopt_syscall(tcpfd, SOL_SOCKET, SO_LINGER, &linger as *const interface::Linger as *const u8, 8), 0);
+        let mut lingerout = interface::Linger::default();
+        let mut optlen: u32 = 8;
+        assert_eq!(cage.getsockopt_syscall(tcpfd, SOL_SOCKET, SO_LINGER, &mut lingerout as *mut interface::Linger as *mut u8, &mut optlen), 0);
+        assert_eq!(optlen, 8);
+        assert!(lingerout == linger);
+
+        //a connection accepted from a listener starts with the listener's options
+        let addr = bind_ephemeral(&cage, tcpfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(tcpfd, 10), 0);
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(tcpfd, &mut peeraddr);
+        assert_eq!(getsockopt_int(&cage, connfd, SOL_SOCKET, SO_REUSEADDR), 1);
+        assert_eq!(getsockopt_int(&cage, connfd, IPPROTO_TCP, TCP_NODELAY), 1);
+        assert_eq!(getsockopt_int(&cage, connfd, SOL_SOCKET, SO_SNDBUF), 20000);
+
+        //shrinking the receive buffer of a datagram pair limits the largest message
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_DGRAM, 0, &mut sv), 0);
+        assert_eq!(setsockopt_int(&cage, sv.sock2, SOL_SOCKET, SO_RCVBUF, 2048), 0);
+        let bigbuf = vec![0u8; 5000];
+        assert_eq!(cage.send_syscall(sv.sock1, bigbuf.as_ptr(), bigbuf.len(), 0), -(Errno::EMSGSIZE as i32));
+        assert_eq!(cage.send_syscall(sv.sock1, bigbuf.as_ptr(), 4000, 0), 4000);
+
+        for fd in [sv.sock1, sv.sock2, connfd, clientfd, tcpfd, udpfd, unixfd].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_sockopt_reuseport() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let serverfd1 = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let serverfd2 = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let serverfd3 = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(setsockopt_int(&cage, serverfd1, SOL_SOCKET, SO_REUSEPORT, 1), 0);
+        assert_eq!(setsockopt_int(&cage, serverfd2, SOL_SOCKET, SO_REUSEPORT, 1), 0);
+
+        //every socket sharing the address must ask to
+        let addr = bind_ephemeral(&cage, serverfd1, &loopback_addr(0));
+        assert_eq!(cage.bind_syscall(serverfd2, &addr), 0);
+        assert_eq!(cage.bind_syscall(serverfd3, &addr), -(Errno::EADDRINUSE as i32));
+        assert_eq!(cage.listen_syscall(serverfd1, 10), 0);
+        assert_eq!(cage.listen_syscall(serverfd2, 10), 0);
+
+        //the address stays reachable as long as one of the sockets is still bound to it
+        assert_eq!(cage.close_syscall(serverfd1), 0);
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd2, &mut peeraddr);
+        assert!(connfd > 0);
+
+        for fd in [connfd, clientfd, serverfd2, serverfd3].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_shutdown_emulated() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        assert_eq!(cage.shutdown_syscall(sv.sock1, 5), -(Errno::EINVAL as i32));
+        assert_eq!(cage.shutdown_syscall(0, SHUT_RD), -(Errno::ENOTSOCK as i32));
+        assert_eq!(cage.shutdown_syscall(123, SHUT_RD), -(Errno::EBADF as i32));
+
+        //after shutting down writing, what was already sent is still read before end of file
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("tail"), 4, 0), 4);
+        assert_eq!(cage.shutdown_syscall(sv.sock1, SHUT_WR), 0);
+        assert_eq!(cage.send_syscall(sv.sock1, str2cbuf("more"), 4, 0), -(Errno::EPIPE as i32));
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "tail");
+        assert_eq!(cage.recv_syscall(sv.sock2, buf.as_mut_ptr(), 4, 0), 0);
+
+        //the other direction still works
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("back"), 4, 0), 4);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "back");
+
+        //once the reader shuts down, the writer gets EPIPE and the reader end of file
+        assert_eq!(cage.shutdown_syscall(sv.sock1, SHUT_RD), 0);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("lost"), 4, 0), -(Errno::EPIPE as i32));
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 0);
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+
+        //unconnected sockets can't be shut down
+        let unixfd = cage.socket_syscall(AF_UNIX, SOCK_STREAM, 0);
+        assert_eq!(cage.shutdown_syscall(unixfd, SHUT_RDWR), -(Errno::ENOTCONN as i32));
+        assert_eq!(cage.close_syscall(unixfd), 0);
+
+        //datagram sockets exchanging through lind
+        let recvfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let sendfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let addr = bind_ephemeral(&cage, recvfd, &loopback_addr(0));
+        assert_eq!(cage.connect_syscall(sendfd, &addr), 0);
+        assert_eq!(cage.connect_syscall(recvfd, &addr), 0);
+        assert_eq!(cage.shutdown_syscall(sendfd, SHUT_WR), 0);
+        assert_eq!(cage.send_syscall(sendfd, str2cbuf("x"), 1, 0), -(Errno::EPIPE as i32));
+        assert_eq!(cage.sendto_syscall(sendfd, str2cbuf("x"), 1, 0, &addr), -(Errno::EPIPE as i32));
+        assert_eq!(cage.shutdown_syscall(recvfd, SHUT_RD), 0);
+        assert_eq!(cage.recv_syscall(recvfd, buf.as_mut_ptr(), 4, 0), 0);
+        assert_eq!(cage.close_syscall(sendfd), 0);
+        assert_eq!(cage.close_syscall(recvfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_shutdown_wakes_recv() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+
+        //another thread of the same cage is blocked reading when we shut the socket down
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            let mut buf = sizecbuf(4);
+            assert_eq!(cage2.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 0);
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert_eq!(cage.shutdown_syscall(sv.sock1, SHUT_RD), 0);
+        thread.join().unwrap();
+
+        assert_eq!(cage.close_syscall(sv.sock1), 0);
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_shutdown_host() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //a peer outside lind, so the connection goes through the host
+        let hostlistener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = hostlistener.local_addr().unwrap().port();
+        let thread = std::thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut hoststream, _) = hostlistener.accept().unwrap();
+            let mut received = Vec::new();
+            hoststream.read_to_end(&mut received).unwrap();
+            assert_eq!(received, b"half");
+            hoststream.write_all(b"closed").unwrap();
+        });
+
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &loopback_addr(port)), 0);
+        assert_eq!(cage.send_syscall(clientfd, str2cbuf("half"), 4, 0), 4);
+        assert_eq!(cage.shutdown_syscall(clientfd, SHUT_WR), 0);
+        assert_eq!(cage.send_syscall(clientfd, str2cbuf("more"), 4, 0), -(Errno::EPIPE as i32));
+
+        let mut buf = sizecbuf(6);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 6, MSG_WAITALL), 6);
+        assert_eq!(cbuf2str(&buf), "closed");
+        thread.join().unwrap();
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 6, 0), 0);
+        assert_eq!(getsockopt_int(&cage, clientfd, SOL_SOCKET, SO_ERROR), 0);
+        assert_eq!(cage.close_syscall(clientfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_blocked_calls_release_descriptor() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let wildcard = interface::GenSockaddr::new_unspecified(AF_INET as u16).unwrap();
+
+        //bound to every address, so connections can come both from within lind and from the host
+        let listenfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.bind_syscall(listenfd, &wildcard), 0);
+        assert_eq!(cage.listen_syscall(listenfd, 4), 0);
+        let mut listenaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(listenfd, &mut listenaddr), 0);
+        let port = listenaddr.port();
+
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            let mut addr = loopback_addr(0);
+            let first = cage2.accept_syscall(listenfd, &mut addr);
+            let second = cage2.accept_syscall(listenfd, &mut addr);
+            (first, second)
+        });
+
+        //calls needing the descriptor exclusively don't wait on the blocked accept
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert_eq!(getsockopt_int(&cage, listenfd, SOL_SOCKET, SO_ERROR), 0);
+        assert_eq!(setsockopt_int(&cage, listenfd, SOL_SOCKET, SO_KEEPALIVE, 1), 0);
+        assert_eq!(cage.fcntl_syscall(listenfd, F_SETFL, 0), 0);
+
+        //the accept is woken by a connection from within lind as well as by one from the host
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &loopback_addr(port)), 0);
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        let hostclient = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let (first, second) = thread.join().unwrap();
+        assert!(first > 0 && second > 0);
+        drop(hostclient);
+
+        //likewise for a datagram socket waiting in recvfrom
+        let recvfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        assert_eq!(cage.bind_syscall(recvfd, &wildcard), 0);
+        let mut recvaddr = loopback_addr(0);
+        assert_eq!(cage.getsockname_syscall(recvfd, &mut recvaddr), 0);
+        let port = recvaddr.port();
+
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                let mut buf = sizecbuf(4);
+                assert_eq!(cage2.recvfrom_syscall(recvfd, buf.as_mut_ptr(), 4, 0, &mut None), 4);
+                received.push(cbuf2str(&buf).to_string());
+            }
+            received
+        });
+
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        assert_eq!(getsockopt_int(&cage, recvfd, SOL_SOCKET, SO_ERROR), 0);
+        assert_eq!(cage.fcntl_syscall(recvfd, F_SETFL, 0), 0);
+
+        let hostsender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert_eq!(hostsender.send_to(b"host", ("127.0.0.1", port)).unwrap(), 4);
+        interface::sleep_ms(interface::RustDuration::from_millis(50));
+        let senderfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        assert_eq!(cage.sendto_syscall(senderfd, str2cbuf("lind"), 4, 0, &loopback_addr(port)), 4);
+        let mut received = thread.join().unwrap();
+        received.sort();
+        assert_eq!(received, vec!["host".to_string(), "lind".to_string()]);
+
+        for fd in [listenfd, clientfd, first, second, recvfd, senderfd].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn pollfd(fd: i32, events: i16) -> PollStruct {
+        PollStruct {fd: fd, events: events, revents: 0}
+    }
+
+    pub fn ut_lind_net_poll_fd_types() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let filefd = cage.open_syscall("/pollfile", O_CREAT | O_RDWR, S_IRWXA);
+        let mut pipefds = PipeArray::default();
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+
+        let mut fds = [pollfd(filefd, POLLIN | POLLOUT), pollfd(1, POLLIN | POLLOUT), pollfd(pipefds.readfd, POLLIN),
+                       pollfd(pipefds.writefd, POLLOUT), pollfd(sv.sock1, POLLIN | POLLOUT), pollfd(500, POLLIN), pollfd(-1, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, 0), 5);
+        assert_eq!(fds[0].revents, POLLIN | POLLOUT);
+        assert_eq!(fds[1].revents, POLLOUT);
+        assert_eq!(fds[2].revents, 0);
+        assert_eq!(fds[3].revents, POLLOUT);
+        assert_eq!(fds[4].revents, POLLOUT);
+        assert_eq!(fds[5].revents, POLLNVAL);
+        assert_eq!(fds[6].revents, 0);
+
+        //data arriving makes the read ends readable
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        let mut fds = [pollfd(pipefds.readfd, POLLIN), pollfd(sv.sock1, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, 0), 2);
+        assert_eq!(fds[0].revents, POLLIN);
+        assert_eq!(fds[1].revents, POLLIN);
+
+        //closing the other end hangs up, which is reported even though we didn't ask
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+        let mut fds = [pollfd(pipefds.readfd, 0), pollfd(sv.sock1, 0)];
+        assert_eq!(cage.poll_syscall(&mut fds, 0), 2);
+        assert_eq!(fds[0].revents, POLLHUP);
+        assert_eq!(fds[1].revents, POLLHUP);
+
+        //a shut down socket reads end of file
+        let mut sv2 = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv2), 0);
+        assert_eq!(cage.shutdown_syscall(sv2.sock1, SHUT_RD), 0);
+        let mut fds = [pollfd(sv2.sock1, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, 0), 1);
+        assert_eq!(fds[0].revents, POLLIN);
+
+        for fd in [filefd, pipefds.readfd, sv.sock1, sv2.sock1, sv2.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+        assert_eq!(cage.unlink_syscall("/pollfile"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_poll_blocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //nothing happening means we wait out the timeout
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        let mut fds = [pollfd(sv.sock1, POLLIN)];
+        let starttime = interface::starttimer();
+        assert_eq!(cage.poll_syscall(&mut fds, 50), 0);
+        assert!(interface::readtimer(starttime) >= interface::RustDuration::from_millis(50));
+
+        //we are woken by a connection arriving on a listening socket, without a timeout
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            let clientfd = cage2.socket_syscall(AF_INET, SOCK_STREAM, 0);
+            assert_eq!(cage2.connect_syscall(clientfd, &addr), 0);
+            clientfd
+        });
+        let mut fds = [pollfd(serverfd, POLLIN), pollfd(sv.sock1, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, -1), 1);
+        assert_eq!(fds[0].revents, POLLIN);
+        assert_eq!(fds[1].revents, 0);
+        let clientfd = thread.join().unwrap();
+        let mut peeraddr = loopback_addr(0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+
+        //datagrams from outside lind arrive through the host socket
+        let udpfd = cage.socket_syscall(AF_INET, SOCK_DGRAM, 0);
+        let port = bind_ephemeral(&cage, udpfd, &loopback_addr(0)).port();
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            let hostsock = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            hostsock.send_to(b"host", ("127.0.0.1", port)).unwrap();
+        });
+        let mut fds = [pollfd(udpfd, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, 5000), 1);
+        assert_eq!(fds[0].revents, POLLIN);
+        thread.join().unwrap();
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.recv_syscall(udpfd, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "host");
+
+        for fd in [udpfd, connfd, clientfd, serverfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_select() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray::default();
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        let nfds = interface::rust_max(pipefds.writefd, sv.sock2) + 1;
+
+        //nothing to read yet, but both write ends have room
+        let mut readfds = interface::FdSet::new();
+        readfds.set(pipefds.readfd);
+        readfds.set(sv.sock1);
+        let mut writefds = interface::FdSet::new();
+        writefds.set(pipefds.writefd);
+        writefds.set(sv.sock2);
+        let mut timeout = interface::TimeVal {tv_sec: 0, tv_usec: 0};
+        assert_eq!(cage.select_syscall(nfds, Some(&mut readfds), Some(&mut writefds), None, Some(&mut timeout)), 2);
+        assert!(!readfds.is_set(pipefds.readfd));
+        assert!(!readfds.is_set(sv.sock1));
+        assert!(writefds.is_set(pipefds.writefd));
+        assert!(writefds.is_set(sv.sock2));
+
+        //the sets are rewritten on return, so they have to be rebuilt each time
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        readfds.zero();
+        readfds.set(pipefds.readfd);
+        readfds.set(sv.sock1);
+        let mut exceptfds = interface::FdSet::new();
+        exceptfds.set(sv.sock1);
+        assert_eq!(cage.select_syscall(nfds, Some(&mut readfds), None, Some(&mut exceptfds), Some(&mut timeout)), 2);
+        assert!(readfds.is_set(pipefds.readfd));
+        assert!(readfds.is_set(sv.sock1));
+        assert!(!exceptfds.is_set(sv.sock1));
+
+        //descriptors at or above nfds are ignored, and a zero timeout returns right away
+        readfds.zero();
+        readfds.set(sv.sock1);
+        assert_eq!(cage.select_syscall(sv.sock1, Some(&mut readfds), None, None, Some(&mut timeout)), 0);
+        assert!(!readfds.is_set(sv.sock1));
+
+        //end of file counts as readable
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        readfds.zero();
+        readfds.set(pipefds.readfd);
+        assert_eq!(cage.select_syscall(nfds, Some(&mut readfds), None, None, Some(&mut timeout)), 1);
+        assert!(readfds.is_set(pipefds.readfd));
+
+        //bad descriptors, counts and timeouts
+        readfds.zero();
+        readfds.set(pipefds.writefd);
+        assert_eq!(cage.select_syscall(nfds, Some(&mut readfds), None, None, Some(&mut timeout)), -(Errno::EBADF as i32));
+        assert_eq!(cage.select_syscall(-1, None, None, None, Some(&mut timeout)), -(Errno::EINVAL as i32));
+        assert_eq!(cage.select_syscall(interface::FD_SET_MAX_FD + 1, None, None, None, Some(&mut timeout)), -(Errno::EINVAL as i32));
+        let mut badtimeout = interface::TimeVal {tv_sec: 0, tv_usec: 1000000};
+        assert_eq!(cage.select_syscall(nfds, None, None, None, Some(&mut badtimeout)), -(Errno::EINVAL as i32));
+
+        for fd in [pipefds.readfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_select_blocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //waiting out the timeout leaves none of it behind
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        let mut readfds = interface::FdSet::new();
+        readfds.set(sv.sock1);
+        let mut timeout = interface::TimeVal {tv_sec: 0, tv_usec: 50000};
+        let starttime = interface::starttimer();
+        assert_eq!(cage.select_syscall(sv.sock1 + 1, Some(&mut readfds), None, None, Some(&mut timeout)), 0);
+        assert!(interface::readtimer(starttime) >= interface::RustDuration::from_millis(50));
+        assert_eq!(timeout.tv_sec, 0);
+        assert_eq!(timeout.tv_usec, 0);
+
+        //a connection arriving wakes us, with the unused part of the timeout written back
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            let clientfd = cage2.socket_syscall(AF_INET, SOCK_STREAM, 0);
+            assert_eq!(cage2.connect_syscall(clientfd, &addr), 0);
+            clientfd
+        });
+        readfds.zero();
+        readfds.set(serverfd);
+        readfds.set(sv.sock1);
+        let mut timeout = interface::TimeVal {tv_sec: 5, tv_usec: 0};
+        assert_eq!(cage.select_syscall(interface::rust_max(serverfd, sv.sock1) + 1, Some(&mut readfds), None, None, Some(&mut timeout)), 1);
+        assert!(readfds.is_set(serverfd));
+        assert!(!readfds.is_set(sv.sock1));
+        assert!(timeout.tv_sec < 5 && timeout.tv_sec >= 4);
+        let clientfd = thread.join().unwrap();
+
+        //without a timeout we wait as long as it takes
+        let cage2 = cage.clone();
+        let sock2 = sv.sock2;
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert_eq!(cage2.send_syscall(sock2, str2cbuf("data"), 4, 0), 4);
+        });
+        readfds.zero();
+        readfds.set(sv.sock1);
+        assert_eq!(cage.select_syscall(sv.sock1 + 1, Some(&mut readfds), None, None, None), 1);
+        assert!(readfds.is_set(sv.sock1));
+        thread.join().unwrap();
+
+        for fd in [clientfd, serverfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn epollevent(fd: i32, events: u32) -> EpollEvent {
+        EpollEvent {events: events, fd: fd}
+    }
+
+    pub fn ut_lind_net_epoll_ctl() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        assert_eq!(cage.epoll_create_syscall(0), -(Errno::EINVAL as i32));
+        let epfd = cage.epoll_create_syscall(1);
+        assert!(epfd > 0);
+
+        let filefd = cage.open_syscall("/epollfile", O_CREAT | O_RDWR, S_IRWXA);
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        let otherepfd = cage.epoll_create_syscall(1);
+
+        //descriptors which can't be watched
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, 500, &epollevent(500, EPOLLIN)), -(Errno::EBADF as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, epfd, &epollevent(epfd, EPOLLIN)), -(Errno::EINVAL as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, filefd, &epollevent(filefd, EPOLLIN)), -(Errno::EPERM as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, otherepfd, &epollevent(otherepfd, EPOLLIN)), -(Errno::EINVAL as i32));
+        assert_eq!(cage.epoll_ctl_syscall(sv.sock2, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), -(Errno::EINVAL as i32));
+
+        //adding, modifying and deleting a registration
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_MOD, sv.sock1, &epollevent(sv.sock1, EPOLLOUT)), -(Errno::ENOENT as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), -(Errno::EEXIST as i32));
+        let mut events = [EpollEvent::default(); 4];
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_MOD, sv.sock1, &epollevent(sv.sock1, EPOLLIN | EPOLLOUT)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, sv.sock1);
+        assert_eq!(events[0].events, EPOLLOUT);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, sv.sock1, &epollevent(sv.sock1, 0)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, sv.sock1, &epollevent(sv.sock1, 0)), -(Errno::ENOENT as i32));
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, 4, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), -(Errno::EINVAL as i32));
+
+        //bad arguments to epoll_wait
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 0, 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 5, 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.epoll_wait_syscall(sv.sock1, &mut events, 4, 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.epoll_wait_syscall(500, &mut events, 4, 0), -(Errno::EBADF as i32));
+
+        //closed descriptors are forgotten
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock2, &epollevent(sv.sock2, EPOLLOUT)), 0);
+        assert_eq!(cage.close_syscall(sv.sock2), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].events, EPOLLIN | EPOLLHUP);
+
+        for fd in [epfd, otherepfd, filefd, sv.sock1].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+        assert_eq!(cage.unlink_syscall("/epollfile"), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_epoll_triggering() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let epfd = cage.epoll_create_syscall(1);
+        let mut pipefds = PipeArray::default();
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        let mut events = [EpollEvent::default(); 4];
+        let mut buf = sizecbuf(4);
+
+        //level triggered reports readiness for as long as it lasts
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN)), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, pipefds.readfd);
+        assert_eq!(events[0].events, EPOLLIN);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+
+        //edge triggered only reports a descriptor becoming ready
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN | EPOLLET)), 0);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, sv.sock1);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, sv.sock1);
+        //activity on other descriptors doesn't report it again, but more data for it does even if the old data is still there
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, pipefds.readfd, &epollevent(pipefds.readfd, 0)), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 4), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.send_syscall(sv.sock2, str2cbuf("data"), 4, 0), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, sv.sock1);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, sv.sock1, &epollevent(sv.sock1, 0)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN)), 0);
+
+        //oneshot reports once, then stays quiet until it is rearmed
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock2, &epollevent(sv.sock2, EPOLLOUT | EPOLLONESHOT)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, sv.sock2);
+        assert_eq!(events[0].events, EPOLLOUT);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock2, &epollevent(sv.sock2, EPOLLOUT)), -(Errno::EEXIST as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_MOD, sv.sock2, &epollevent(sv.sock2, EPOLLOUT | EPOLLONESHOT)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+
+        //no more than maxevents are returned at once
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_MOD, sv.sock2, &epollevent(sv.sock2, EPOLLOUT)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, pipefds.writefd, &epollevent(pipefds.writefd, EPOLLOUT)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 2, 0), 2);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 1, 0), 1);
+
+        //the pipe's write end closing hangs up the read end
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, pipefds.writefd, &epollevent(pipefds.writefd, 0)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, sv.sock2, &epollevent(sv.sock2, 0)), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, pipefds.readfd);
+        assert_eq!(events[0].events, EPOLLHUP);
+
+        for fd in [epfd, pipefds.readfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_epoll_blocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let epfd = cage.epoll_create_syscall(1);
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM, 0, &mut sv), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, sv.sock1, &epollevent(sv.sock1, EPOLLIN)), 0);
+        let mut events = [EpollEvent::default(); 4];
+
+        //nothing happening means we wait out the timeout
+        let starttime = interface::starttimer();
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 50), 0);
+        assert!(interface::readtimer(starttime) >= interface::RustDuration::from_millis(50));
+
+        //a write from another thread wakes us
+        let cage2 = cage.clone();
+        let sock2 = sv.sock2;
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert_eq!(cage2.send_syscall(sock2, str2cbuf("data"), 4, 0), 4);
+        });
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, -1), 1);
+        assert_eq!(events[0].fd, sv.sock1);
+        thread.join().unwrap();
+
+        //as does a connection arriving on a listening socket
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, sv.sock1, &epollevent(sv.sock1, 0)), 0);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, serverfd, &epollevent(serverfd, EPOLLIN)), 0);
+
+        let cage2 = cage.clone();
+        let thread = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            let clientfd = cage2.socket_syscall(AF_INET, SOCK_STREAM, 0);
+            assert_eq!(cage2.connect_syscall(clientfd, &addr), 0);
+            clientfd
+        });
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 5000), 1);
+        assert_eq!(events[0].fd, serverfd);
+        assert_eq!(events[0].events, EPOLLIN);
+        let clientfd = thread.join().unwrap();
+
+        for fd in [epfd, clientfd, serverfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_epoll_fd_reuse() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let epfd = cage.epoll_create_syscall(1);
+        let mut pipefds = PipeArray::default();
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut events = [EpollEvent::default(); 4];
+
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN | EPOLLET)), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+
+        //a pipe made after closing the first one gets its fd numbers, but not its registration
+        let oldreadfd = pipefds.readfd;
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(pipefds.readfd, oldreadfd);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_MOD, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN)), -(Errno::ENOENT as i32));
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_DEL, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN)), -(Errno::ENOENT as i32));
+
+        //registering it anew starts it out fresh, so its data is reported even though it is edge triggered
+        assert_eq!(cage.epoll_ctl_syscall(epfd, EPOLL_CTL_ADD, pipefds.readfd, &epollevent(pipefds.readfd, EPOLLIN | EPOLLET)), 0);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 1);
+        assert_eq!(events[0].fd, pipefds.readfd);
+
+        //and once it is gone too, waiting doesn't report the next descriptor with its number
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(pipefds.readfd, oldreadfd);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("data"), 4), 4);
+        assert_eq!(cage.epoll_wait_syscall(epfd, &mut events, 4, 0), 0);
+
+        for fd in [epfd, pipefds.readfd, pipefds.writefd].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_nonblocking_emulated() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //nonblocking sockets from creation
+        let mut sv = SockPair::default();
+        assert_eq!(cage.socketpair_syscall(AF_UNIX, SOCK_STREAM | SOCK_NONBLOCK, 0, &mut sv), 0);
+        let mut buf = sizecbuf(EMULATEDBUFFERSIZE);
+        assert_eq!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), 4, 0), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.read_syscall(sv.sock1, buf.as_mut_ptr(), 4), -(Errno::EAGAIN as i32));
+
+        //filling the connection leaves no room for more
+        let bigbuf = vec![b'a'; EMULATEDBUFFERSIZE];
+        let mut sent = 0;
+        loop {
+            let ret = cage.send_syscall(sv.sock2, bigbuf.as_ptr(), EMULATEDBUFFERSIZE, 0);
+            if ret < 0 {
+                assert_eq!(ret, -(Errno::EAGAIN as i32));
+                break;
+            }
+            sent += ret;
+        }
+        assert!(sent > 0);
+        assert_eq!(cage.write_syscall(sv.sock2, bigbuf.as_ptr(), 1), -(Errno::EAGAIN as i32));
+        assert!(cage.recv_syscall(sv.sock1, buf.as_mut_ptr(), EMULATEDBUFFERSIZE, 0) > 0);
+
+        //or set later with fcntl, here on a listening socket with nothing to accept
+        let serverfd = cage.socket_syscall(AF_INET, SOCK_STREAM, 0);
+        let addr = bind_ephemeral(&cage, serverfd, &loopback_addr(0));
+        assert_eq!(cage.listen_syscall(serverfd, 10), 0);
+        assert_eq!(cage.fcntl_syscall(serverfd, F_SETFL, O_NONBLOCK), 0);
+        let mut peeraddr = loopback_addr(0);
+        assert_eq!(cage.accept_syscall(serverfd, &mut peeraddr), -(Errno::EAGAIN as i32));
+
+        //connections within lind are made right away even when nonblocking
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0);
+        assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        let connfd = cage.accept_syscall(serverfd, &mut peeraddr);
+        assert!(connfd > 0);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, 0), -(Errno::EAGAIN as i32));
+
+        //MSG_DONTWAIT makes a single call nonblocking on a blocking socket
+        assert_eq!(cage.recv_syscall(connfd, buf.as_mut_ptr(), 4, MSG_DONTWAIT), -(Errno::EAGAIN as i32));
+
+        //and so do datagram sockets
+        let udpfd = cage.socket_syscall(AF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0);
+        bind_ephemeral(&cage, udpfd, &loopback_addr(0));
+        assert_eq!(cage.recvfrom_syscall(udpfd, buf.as_mut_ptr(), 4, 0, &mut None), -(Errno::EAGAIN as i32));
+
+        for fd in [udpfd, connfd, clientfd, serverfd, sv.sock1, sv.sock2].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_net_nonblocking_host() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //a listener outside lind, so that the connect goes through the host
+        let hostlistener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let clientfd = cage.socket_syscall(AF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0);
+        let addr = loopback_addr(hostlistener.local_addr().unwrap().port());
+        let connectret = cage.connect_syscall(clientfd, &addr);
+        assert!(connectret == 0 || connectret == -(Errno::EINPROGRESS as i32));
+
+        //poll says when the connection is made, and SO_ERROR how it went
+        let mut fds = [pollfd(clientfd, POLLOUT)];
+        assert_eq!(cage.poll_syscall(&mut fds, 5000), 1);
+        assert_eq!(fds[0].revents, POLLOUT);
+        assert_eq!(getsockopt_int(&cage, clientfd, SOL_SOCKET, SO_ERROR), 0);
+        let mut peeraddr = loopback_addr(0);
+        assert_eq!(cage.getpeername_syscall(clientfd, &mut peeraddr), 0);
+        assert_eq!(peeraddr, addr);
+        if connectret != 0 {
+            assert_eq!(cage.connect_syscall(clientfd, &addr), 0);
+        }
+        assert_eq!(cage.connect_syscall(clientfd, &addr), -(Errno::EISCONN as i32));
+
+        //the host side only reads once we have checked that we would block
+        let (mut hoststream, _) = hostlistener.accept().unwrap();
+        let mut buf = sizecbuf(4);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, 0), -(Errno::EAGAIN as i32));
+        std::io::Write::write_all(&mut hoststream, b"host").unwrap();
+        let mut fds = [pollfd(clientfd, POLLIN)];
+        assert_eq!(cage.poll_syscall(&mut fds, 5000), 1);
+        assert_eq!(cage.recv_syscall(clientfd, buf.as_mut_ptr(), 4, 0), 4);
+        assert_eq!(cbuf2str(&buf), "host");
+
+        //a nonblocking connect which has finished can be shut down without calling connect again
+        let shutdownfd = cage.socket_syscall(AF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0);
+        let connectret = cage.connect_syscall(shutdownfd, &addr);
+        assert!(connectret == 0 || connectret == -(Errno::EINPROGRESS as i32));
+        let mut fds = [pollfd(shutdownfd, POLLOUT)];
+        assert_eq!(cage.poll_syscall(&mut fds, 5000), 1);
+        assert_eq!(cage.shutdown_syscall(shutdownfd, SHUT_WR), 0);
+        let (mut hoststream, _) = hostlistener.accept().unwrap();
+        let mut received = Vec::new();
+        std::io::Read::read_to_end(&mut hoststream, &mut received).unwrap();
+        assert!(received.is_empty());
+
+        //a refused nonblocking connect reports the failure through SO_ERROR, here on the port nothing listens on anymore
+        drop(hostlistener);
+        let refusedfd = cage.socket_syscall(AF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0);
+        let connectret = cage.connect_syscall(refusedfd, &addr);
+        if connectret == -(Errno::EINPROGRESS as i32) {
+            let mut fds = [pollfd(refusedfd, POLLOUT)];
+            assert_eq!(cage.poll_syscall(&mut fds, 5000), 1);
+            assert!(fds[0].revents & POLLERR != 0);
+            assert_eq!(getsockopt_int(&cage, refusedfd, SOL_SOCKET, SO_ERROR), Errno::ECONNREFUSED as i32);
+            assert_eq!(cage.getpeername_syscall(refusedfd, &mut peeraddr), -(Errno::ENOTCONN as i32));
+        } else {
+            assert_eq!(connectret, -(Errno::ECONNREFUSED as i32));
+        }
+
+        for fd in [clientfd, shutdownfd, refusedfd].iter() {
+            assert_eq!(cage.close_syscall(*fd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+}
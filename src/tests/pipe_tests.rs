@@ -7,11 +7,25 @@ mod pipe_tests {
     use std::fs::OpenOptions;
     use std::time::Instant;
 
-    //#[test]
+    #[test]
     pub fn test_pipe() {
+        let _guard = lock_tests();
+
         // These can't really run until we figure out a better testing system/fsutils
         // ut_lind_write_pipefile();
         // ut_lind_fs_pipe();
+        ut_lind_fs_pipe_nonblocking();
+        ut_lind_fs_pipe_blocking();
+        ut_lind_fs_pipe_multiple_writers();
+        ut_lind_fs_pipe_multiple_readers();
+        ut_lind_fs_pipe_eof_epipe();
+        ut_lind_fs_pipe_refcounts();
+        ut_lind_fs_pipe2();
+        ut_lind_fs_fifo();
+        ut_lind_fs_pipe_size();
+        ut_lind_fs_splice();
+        ut_lind_fs_splice_blocking();
+        ut_lind_fs_splice_concurrent();
     }
 
 
@@ -106,4 +120,681 @@ mod pipe_tests {
 
         lindrustfinalize();
     }
+
+    pub fn ut_lind_fs_pipe_nonblocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+
+        //setting the status flags leaves the access mode alone
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFL, O_NONBLOCK), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETFL, O_NONBLOCK | O_RDONLY), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETFL, 0) & (O_RDWRFLAGS | O_NONBLOCK), O_RDONLY | O_NONBLOCK);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_GETFL, 0) & (O_RDWRFLAGS | O_NONBLOCK), O_WRONLY | O_NONBLOCK);
+
+        //an empty pipe with a writer has nothing for us yet
+        let mut buf = sizecbuf(PIPE_CAPACITY);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+
+        //reads return what is there rather than waiting for more
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cbuf2str(&buf[0..5]), "hello");
+
+        //a full pipe takes what fits of a large write, but none of a small one that doesn't fit
+        let bigbuf = vec![b'a'; PIPE_CAPACITY + 100];
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), PIPE_CAPACITY + 100), PIPE_CAPACITY as i32);
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), 1), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 10);
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), 100), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), 10), 10);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), PIPE_CAPACITY), PIPE_CAPACITY as i32);
+
+        //once the writer is gone an empty pipe is at end of file
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_blocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+
+        //a reader waits for data, then takes what there is rather than waiting to fill its buffer
+        let cage2 = cage.clone();
+        let writer = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert_eq!(cage2.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+        });
+        let mut buf = sizecbuf(100);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), 5);
+        assert_eq!(cbuf2str(&buf[0..5]), "hello");
+        writer.join().unwrap();
+
+        //a writer with more than fits waits for the reader to make room
+        let cage2 = cage.clone();
+        let writer = std::thread::spawn(move || {
+            let bigbuf = vec![b'a'; PIPE_CAPACITY * 3];
+            assert_eq!(cage2.write_syscall(pipefds.writefd, bigbuf.as_ptr(), PIPE_CAPACITY * 3), (PIPE_CAPACITY * 3) as i32);
+        });
+        let mut bigbuf = sizecbuf(PIPE_CAPACITY * 3);
+        let mut total = 0;
+        while total < PIPE_CAPACITY * 3 {
+            let bytesread = cage.read_syscall(pipefds.readfd, bigbuf[total..].as_mut_ptr(), PIPE_CAPACITY * 3 - total);
+            assert!(bytesread > 0);
+            total += bytesread as usize;
+        }
+        writer.join().unwrap();
+        assert!(bigbuf.iter().all(|byte| *byte == b'a'));
+
+        //changing the flags of a descriptor a reader is waiting on neither waits for the reader nor keeps a writer from waking it
+        let cage2 = cage.clone();
+        let reader = std::thread::spawn(move || {
+            let mut buf = sizecbuf(100);
+            assert_eq!(cage2.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), 5);
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFL, O_NONBLOCK), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+        reader.join().unwrap();
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFL, 0), 0);
+
+        //a waiting reader is woken when the last writer goes away
+        let cage2 = cage.clone();
+        let closer = std::thread::spawn(move || {
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert_eq!(cage2.close_syscall(pipefds.writefd), 0);
+        });
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), 0);
+        closer.join().unwrap();
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_multiple_writers() {
+        let chunks: usize = 64;
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+
+        //forked writers each send PIPE_BUF sized chunks of their own byte, more than the pipe holds
+        let mut writers = vec![];
+        for (childid, fill) in vec![(2, b'x'), (3, b'y')] {
+            assert_eq!(cage.fork_syscall(childid), 0);
+            writers.push(std::thread::spawn(move || {
+                let child = {CAGE_TABLE.read().unwrap().get(&childid).unwrap().clone()};
+                assert_eq!(child.close_syscall(pipefds.readfd), 0);
+                let chunk = vec![fill; interface::PIPE_BUF];
+                for _ in 0..chunks {
+                    assert_eq!(child.write_syscall(pipefds.writefd, chunk.as_ptr(), interface::PIPE_BUF), interface::PIPE_BUF as i32);
+                }
+                assert_eq!(child.exit_syscall(), 0);
+            }));
+        }
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+
+        //we read until both have exited, and since their writes are atomic no chunk is a mix of the two
+        let mut received = vec![];
+        let mut buf = sizecbuf(1000);
+        loop {
+            let bytesread = cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 1000);
+            assert!(bytesread >= 0);
+            if bytesread == 0 {break;}
+            received.extend_from_slice(&buf[..bytesread as usize]);
+        }
+        for writer in writers {writer.join().unwrap();}
+
+        assert_eq!(received.len(), 2 * chunks * interface::PIPE_BUF);
+        for chunk in received.chunks(interface::PIPE_BUF) {
+            assert!(chunk.iter().all(|byte| *byte == chunk[0]));
+        }
+        assert_eq!(received.iter().filter(|byte| **byte == b'x').count(), chunks * interface::PIPE_BUF);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_multiple_readers() {
+        let total: usize = PIPE_CAPACITY * 4;
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+
+        //forked readers share out everything we write between them, and all see end of file once we close
+        let mut readers = vec![];
+        for childid in vec![2, 3] {
+            assert_eq!(cage.fork_syscall(childid), 0);
+            readers.push(std::thread::spawn(move || {
+                let child = {CAGE_TABLE.read().unwrap().get(&childid).unwrap().clone()};
+                assert_eq!(child.close_syscall(pipefds.writefd), 0);
+                let mut buf = sizecbuf(1000);
+                let mut bytesreceived = 0;
+                loop {
+                    let bytesread = child.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 1000);
+                    assert!(bytesread >= 0);
+                    if bytesread == 0 {break;}
+                    bytesreceived += bytesread as usize;
+                }
+                assert_eq!(child.exit_syscall(), 0);
+                bytesreceived
+            }));
+        }
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        let bigbuf = vec![b'a'; total];
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), total), total as i32);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+
+        let bytesreceived: usize = readers.into_iter().map(|reader| reader.join().unwrap()).sum();
+        assert_eq!(bytesreceived, total);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_eof_epipe() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        //what is left in the pipe is still read after the writer goes, then end of file
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        let mut buf = sizecbuf(10);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        //writing with no readers fails
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), -(Errno::EPIPE as i32));
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+
+        //a writer waiting for room gives up when the last reader goes, reporting what it did write
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let cage2 = cage.clone();
+        let writer = std::thread::spawn(move || {
+            let bigbuf = vec![b'a'; PIPE_CAPACITY * 2];
+            cage2.write_syscall(pipefds.writefd, bigbuf.as_ptr(), PIPE_CAPACITY * 2)
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(writer.join().unwrap(), PIPE_CAPACITY as i32);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), -(Errno::EPIPE as i32));
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_refcounts() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(10);
+
+        //a dup of the write end keeps the pipe open after the original is closed
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFL, O_NONBLOCK), 0);
+        let dupfd = cage.dup_syscall(pipefds.writefd, None);
+        assert!(dupfd > 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+
+        //as does dup2, while dup2 onto itself doesn't take another reference
+        assert_eq!(cage.dup2_syscall(dupfd, 20), 20);
+        assert_eq!(cage.dup2_syscall(dupfd, dupfd), dupfd);
+        assert_eq!(cage.close_syscall(dupfd), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.close_syscall(20), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        //a forked child holds its own reference, given up when it exits
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(cage.fork_syscall(2), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        let child = std::thread::spawn(move || {
+            let child = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert_eq!(child.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+            assert_eq!(child.exit_syscall(), 0);
+        });
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        child.join().unwrap();
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        //exec closes the write end if it is marked close-on-exec, and keeps it otherwise
+        for cloexec in vec![true, false] {
+            assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+            if cloexec {assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETFD, FD_CLOEXEC), 0);}
+            assert_eq!(cage.fork_syscall(2), 0);
+            assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+            assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFL, O_NONBLOCK), 0);
+
+            let child = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+            assert_eq!(child.exec_syscall(3), 0);
+            let execed = {CAGE_TABLE.read().unwrap().get(&3).unwrap().clone()};
+            if cloexec {
+                assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+            } else {
+                assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+                assert_eq!(execed.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+            }
+            assert_eq!(execed.exit_syscall(), 0);
+            if !cloexec {assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);}
+            assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+            assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        }
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe2() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(10);
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_APPEND), -(Errno::EINVAL as i32));
+        assert_eq!(pipefds.readfd, -1);
+
+        //nonblocking applies to both ends
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_NONBLOCK), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETFL, 0), O_RDONLY | O_NONBLOCK);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_GETFL, 0), O_WRONLY | O_NONBLOCK);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETFD, 0), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+        let bigbuf = vec![b'a'; PIPE_CAPACITY + 1];
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), PIPE_CAPACITY + 1), PIPE_CAPACITY as i32);
+        assert_eq!(cage.write_syscall(pipefds.writefd, bigbuf.as_ptr(), 1), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+
+        //close-on-exec can be cleared again through fcntl
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_CLOEXEC), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETFD, 0), FD_CLOEXEC);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_GETFD, 0), FD_CLOEXEC);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETFD, 0), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETFD, 0), 0);
+
+        //so after exec only the read end is left
+        assert_eq!(cage.fork_syscall(2), 0);
+        let child = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(child.exec_syscall(3), 0);
+        let execed = {CAGE_TABLE.read().unwrap().get(&3).unwrap().clone()};
+        assert_eq!(execed.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), -(Errno::EBADF as i32));
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(execed.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(execed.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(execed.exit_syscall(), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        //close-on-exec belongs to the fd, so a dup of a close-on-exec end is kept open across exec while the original isn't
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_CLOEXEC), 0);
+        let dupfd = cage.dup_syscall(pipefds.writefd, None);
+        let fcntldupfd = cage.fcntl_syscall(pipefds.writefd, F_DUPFD, 0);
+        assert!(dupfd > 0 && fcntldupfd > 0);
+        assert_eq!(cage.fcntl_syscall(dupfd, F_GETFD, 0), 0);
+        assert_eq!(cage.fcntl_syscall(fcntldupfd, F_GETFD, 0), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_GETFD, 0), FD_CLOEXEC);
+        assert_eq!(cage.fcntl_syscall(fcntldupfd, F_SETFD, FD_CLOEXEC), 0);
+        assert_eq!(cage.fcntl_syscall(dupfd, F_GETFD, 0), 0);
+        assert_eq!(cage.fork_syscall(2), 0);
+        let child = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(child.fcntl_syscall(pipefds.writefd, F_GETFD, 0), FD_CLOEXEC);
+        assert_eq!(child.exec_syscall(3), 0);
+        let execed = {CAGE_TABLE.read().unwrap().get(&3).unwrap().clone()};
+        assert_eq!(execed.write_syscall(pipefds.writefd, str2cbuf("hello"), 5), -(Errno::EBADF as i32));
+        assert_eq!(execed.write_syscall(fcntldupfd, str2cbuf("hello"), 5), -(Errno::EBADF as i32));
+        assert_eq!(execed.write_syscall(dupfd, str2cbuf("hello"), 5), 5);
+        assert_eq!(execed.exit_syscall(), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.close_syscall(dupfd), 0);
+        assert_eq!(cage.close_syscall(fcntldupfd), 0);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_fifo() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(10);
+        let path = "/fifotest";
+        let _ = cage.unlink_syscall(path);
+
+        assert_eq!(cage.mkfifo_syscall(path, S_IRUSR | S_IWUSR), 0);
+        assert_eq!(cage.mkfifo_syscall(path, S_IRUSR | S_IWUSR), -(Errno::EEXIST as i32));
+        let mut statdata = StatData::default();
+        assert_eq!(cage.stat_syscall(path, &mut statdata), 0);
+        assert_eq!(statdata.st_mode, S_IFIFO as u32 | S_IRUSR | S_IWUSR);
+
+        //without blocking, a writer can't open before there is a reader but a reader can open right away, seeing end of file
+        assert_eq!(cage.open_syscall(path, O_WRONLY | O_NONBLOCK, 0), -(Errno::ENXIO as i32));
+        let readfd = cage.open_syscall(path, O_RDONLY | O_NONBLOCK, 0);
+        assert!(readfd > 0);
+        assert_eq!(cage.read_syscall(readfd, buf.as_mut_ptr(), 10), 0);
+        let writefd = cage.open_syscall(path, O_WRONLY | O_NONBLOCK, 0);
+        assert!(writefd > 0);
+        assert_eq!(cage.read_syscall(readfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.write_syscall(writefd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.read_syscall(readfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cbuf2str(&buf[..5]), "hello");
+
+        //both ends describe the fifo
+        let mut fstatdata = StatData::default();
+        assert_eq!(cage.fstat_syscall(writefd, &mut fstatdata), 0);
+        assert_eq!(fstatdata.st_ino, statdata.st_ino);
+        assert_eq!(fstatdata.st_mode, statdata.st_mode);
+        assert_eq!(cage.close_syscall(writefd), 0);
+        assert_eq!(cage.read_syscall(readfd, buf.as_mut_ptr(), 10), 0);
+        assert_eq!(cage.close_syscall(readfd), 0);
+
+        //otherwise opening either end waits for the other
+        for readerfirst in vec![true, false] {
+            let cage2 = cage.clone();
+            let first = std::thread::spawn(move || {
+                cage2.open_syscall(path, if readerfirst {O_RDONLY} else {O_WRONLY}, 0)
+            });
+            interface::sleep_ms(interface::RustDuration::from_millis(20));
+            assert!(!first.is_finished());
+            let secondfd = cage.open_syscall(path, if readerfirst {O_WRONLY} else {O_RDONLY}, 0);
+            let firstfd = first.join().unwrap();
+            assert!(firstfd > 0 && secondfd > 0);
+            let (readfd, writefd) = if readerfirst {(firstfd, secondfd)} else {(secondfd, firstfd)};
+            assert_eq!(cage.write_syscall(writefd, str2cbuf("hello"), 5), 5);
+            assert_eq!(cage.read_syscall(readfd, buf.as_mut_ptr(), 10), 5);
+            assert_eq!(cage.close_syscall(readfd), 0);
+            assert_eq!(cage.close_syscall(writefd), 0);
+        }
+
+        //opening for both reading and writing never waits, and the contents are gone once every end is closed
+        let rdwrfd = cage.open_syscall(path, O_RDWR | O_NONBLOCK, 0);
+        assert!(rdwrfd > 0);
+        assert_eq!(cage.write_syscall(rdwrfd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.close_syscall(rdwrfd), 0);
+        let rdwrfd = cage.open_syscall(path, O_RDWR | O_NONBLOCK, 0);
+        assert_eq!(cage.read_syscall(rdwrfd, buf.as_mut_ptr(), 10), -(Errno::EAGAIN as i32));
+
+        //an unlinked fifo keeps working until its last end is closed
+        assert_eq!(cage.unlink_syscall(path), 0);
+        assert_eq!(cage.stat_syscall(path, &mut statdata), -(Errno::ENOENT as i32));
+        assert_eq!(cage.write_syscall(rdwrfd, str2cbuf("hello"), 5), 5);
+        assert_eq!(cage.read_syscall(rdwrfd, buf.as_mut_ptr(), 10), 5);
+        assert_eq!(cage.close_syscall(rdwrfd), 0);
+        assert!(!filesystem::FS_METADATA.read().unwrap().inodetable.contains_key(&statdata.st_ino));
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_pipe_size() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_NONBLOCK), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETPIPE_SZ, 0), PIPE_CAPACITY as i32);
+        assert_eq!(cage.fcntl_syscall(1, F_GETPIPE_SZ, 0), -(Errno::EBADF as i32));
+        assert_eq!(cage.fcntl_syscall(1, F_SETPIPE_SZ, 8192), -(Errno::EBADF as i32));
+
+        //sizes are rounded up and capped, and both ends see the change
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, PIPE_MAX_SIZE as i32 + 1), -(Errno::EPERM as i32));
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, 0), PIPE_MIN_SIZE as i32);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, 5000), 8192);
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_GETPIPE_SZ, 0), 8192);
+
+        //the pipe holds only as much as its size, and can't shrink below what it holds
+        let abuf = vec![b'a'; 10000];
+        assert_eq!(cage.write_syscall(pipefds.writefd, abuf.as_ptr(), 10000), 8192);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, 4096), -(Errno::EBUSY as i32));
+
+        //growing keeps what was buffered
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, 16384), 16384);
+        let bbuf = vec![b'b'; 10000];
+        assert_eq!(cage.write_syscall(pipefds.writefd, bbuf.as_ptr(), 10000), 8192);
+        let mut buf = sizecbuf(20000);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 20000), 16384);
+        assert!(buf[..8192].iter().all(|b| *b == b'a'));
+        assert!(buf[8192..16384].iter().all(|b| *b == b'b'));
+
+        //a writer waiting for room gets it when the pipe grows
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETPIPE_SZ, 4096), 4096);
+        assert_eq!(cage.write_syscall(pipefds.writefd, abuf.as_ptr(), 4096), 4096);
+        assert_eq!(cage.fcntl_syscall(pipefds.writefd, F_SETFL, 0), 0);
+        let cage2 = cage.clone();
+        let writer = std::thread::spawn(move || {
+            let bbuf = vec![b'b'; 4096];
+            cage2.write_syscall(pipefds.writefd, bbuf.as_ptr(), 4096)
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert!(!writer.is_finished());
+        assert_eq!(cage.fcntl_syscall(pipefds.readfd, F_SETPIPE_SZ, 8192), 8192);
+        assert_eq!(writer.join().unwrap(), 4096);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 20000), 8192);
+
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_splice() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(20);
+
+        let filefd = cage.open_syscall("/splicetest", O_CREAT | O_TRUNC | O_RDWR, S_IRWXA);
+        assert!(filefd > 0);
+        assert_eq!(cage.write_syscall(filefd, str2cbuf("hello world"), 11), 11);
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe2_syscall(&mut pipefds, O_NONBLOCK), 0);
+        let mut pipefds2 = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe2_syscall(&mut pipefds2, O_NONBLOCK), 0);
+
+        //neither end a pipe, offsets for pipes, the same pipe at both ends, and unknown flags are all rejected
+        assert_eq!(cage.splice_syscall(filefd, None, filefd, None, 5, 0), -(Errno::EINVAL as i32));
+        let mut offset: i64 = 0;
+        assert_eq!(cage.splice_syscall(pipefds.readfd, Some(&mut offset), filefd, None, 5, 0), -(Errno::ESPIPE as i32));
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, pipefds.writefd, None, 5, 0), -(Errno::EINVAL as i32));
+        assert_eq!(cage.splice_syscall(pipefds.writefd, None, filefd, None, 5, 0), -(Errno::EBADF as i32));
+        assert_eq!(cage.splice_syscall(filefd, None, pipefds.writefd, None, 5, 0x10), -(Errno::EINVAL as i32));
+        assert_eq!(cage.tee_syscall(pipefds.readfd, filefd, 5, 0), -(Errno::EINVAL as i32));
+
+        //from a file at an offset, which moves the offset but not the file's position
+        let mut offset: i64 = 6;
+        assert_eq!(cage.splice_syscall(filefd, Some(&mut offset), pipefds.writefd, None, 100, 0), 5);
+        assert_eq!(offset, 11);
+        assert_eq!(cage.lseek_syscall(filefd, 0, SEEK_CUR), 11);
+
+        //from a file at its position, which moves it
+        assert_eq!(cage.lseek_syscall(filefd, 0, SEEK_SET), 0);
+        assert_eq!(cage.splice_syscall(filefd, None, pipefds.writefd, None, 5, 0), 5);
+        assert_eq!(cage.lseek_syscall(filefd, 0, SEEK_CUR), 5);
+
+        //tee copies without taking anything out of the input
+        assert_eq!(cage.tee_syscall(pipefds.readfd, pipefds2.writefd, 100, 0), 10);
+        assert_eq!(cage.read_syscall(pipefds2.readfd, buf.as_mut_ptr(), 20), 10);
+        assert_eq!(cbuf2str(&buf[..10]), "worldhello");
+
+        //into a file, after which the pipe is empty
+        let mut offset: i64 = 20;
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, filefd, Some(&mut offset), 100, 0), 10);
+        assert_eq!(offset, 30);
+        assert_eq!(cage.pread_syscall(filefd, buf.as_mut_ptr(), 20, 20), 10);
+        assert_eq!(cbuf2str(&buf[..10]), "worldhello");
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, filefd, None, 100, SPLICE_F_NONBLOCK), -(Errno::EAGAIN as i32));
+
+        //between pipes, moving only what the output has room for
+        assert_eq!(cage.fcntl_syscall(pipefds2.writefd, F_SETPIPE_SZ, 4096), 4096);
+        let abuf = vec![b'a'; 4090];
+        assert_eq!(cage.write_syscall(pipefds2.writefd, abuf.as_ptr(), 4090), 4090);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("0123456789"), 10), 10);
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, pipefds2.writefd, None, 100, 0), 6);
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, pipefds2.writefd, None, 100, 0), -(Errno::EAGAIN as i32));
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 20), 4);
+        assert_eq!(cbuf2str(&buf[..4]), "6789");
+
+        //an input pipe with no writers is at end of file
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.splice_syscall(pipefds.readfd, None, filefd, None, 100, 0), 0);
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds2.writefd), 0);
+        assert_eq!(cage.close_syscall(pipefds2.readfd), 0);
+
+        //sendfile reads from a file only, and writes to anything
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        assert_eq!(cage.sendfile_syscall(filefd, pipefds.readfd, None, 5), -(Errno::EINVAL as i32));
+        let mut offset: i64 = 0;
+        assert_eq!(cage.sendfile_syscall(pipefds.writefd, filefd, Some(&mut offset), 11), 11);
+        assert_eq!(offset, 11);
+        assert_eq!(cage.lseek_syscall(filefd, 0, SEEK_SET), 0);
+        assert_eq!(cage.sendfile_syscall(pipefds.writefd, filefd, None, 5), 5);
+        assert_eq!(cage.lseek_syscall(filefd, 0, SEEK_CUR), 5);
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 20), 16);
+        assert_eq!(cbuf2str(&buf[..16]), "hello worldhello");
+
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.close_syscall(filefd), 0);
+        assert_eq!(cage.unlink_syscall("/splicetest"), 0);
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_splice_blocking() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(4096);
+
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut pipefds2 = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds2), 0);
+        assert_eq!(cage.fcntl_syscall(pipefds2.writefd, F_SETPIPE_SZ, 4096), 4096);
+        let abuf = vec![b'a'; 4096];
+        assert_eq!(cage.write_syscall(pipefds2.writefd, abuf.as_ptr(), 4096), 4096);
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("0123456789"), 10), 10);
+
+        //a splice waiting for room in its output takes nothing from its input meanwhile, so other readers still see it all in order
+        let cage2 = cage.clone();
+        let splicer = std::thread::spawn(move || {
+            assert_eq!(cage2.splice_syscall(pipefds.readfd, None, pipefds2.writefd, None, 100, 0), 3);
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), 10);
+        assert_eq!(cbuf2str(&buf[..10]), "0123456789");
+
+        //once there's room it waits for data instead, and never fills the output past its size
+        assert_eq!(cage.read_syscall(pipefds2.readfd, buf.as_mut_ptr(), 3), 3);
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert_eq!(cage.write_syscall(pipefds.writefd, str2cbuf("abcdef"), 6), 6);
+        splicer.join().unwrap();
+        assert_eq!(cage.read_syscall(pipefds2.readfd, buf.as_mut_ptr(), 4096), 4096);
+        assert_eq!(cbuf2str(&buf[4093..4096]), "abc");
+        assert_eq!(cage.read_syscall(pipefds.readfd, buf.as_mut_ptr(), 100), 3);
+        assert_eq!(cbuf2str(&buf[..3]), "def");
+
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds.writefd), 0);
+        assert_eq!(cage.close_syscall(pipefds2.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds2.writefd), 0);
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_fs_splice_concurrent() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let wordcount: u32 = 65536;
+
+        let filefd = cage.open_syscall("/spliceconcurrent", O_CREAT | O_TRUNC | O_RDWR, S_IRWXA);
+        assert!(filefd > 0);
+        let mut pipefds = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds), 0);
+        let mut pipefds2 = PipeArray {readfd: -1, writefd: -1};
+        assert_eq!(cage.pipe_syscall(&mut pipefds2), 0);
+
+        //numbered words go into one pipe, and are spliced out of it into a file and another pipe at the same time,
+        //everything moving in whole words so that each can be found again
+        let cage2 = cage.clone();
+        let writer = std::thread::spawn(move || {
+            let data: Vec<u8> = (0..wordcount).flat_map(|word| word.to_ne_bytes()).collect();
+            for chunk in data.chunks(4096) {
+                assert_eq!(cage2.write_syscall(pipefds.writefd, chunk.as_ptr(), chunk.len()), chunk.len() as i32);
+            }
+            assert_eq!(cage2.close_syscall(pipefds.writefd), 0);
+        });
+        let cage2 = cage.clone();
+        let tofile = std::thread::spawn(move || {
+            let mut offset: i64 = 0;
+            loop {
+                let moved = cage2.splice_syscall(pipefds.readfd, None, filefd, Some(&mut offset), 4096, 0);
+                assert!(moved >= 0 && moved % 4 == 0);
+                if moved == 0 {return offset as usize;}
+            }
+        });
+        let cage2 = cage.clone();
+        let topipe = std::thread::spawn(move || {
+            loop {
+                let moved = cage2.splice_syscall(pipefds.readfd, None, pipefds2.writefd, None, 4096, 0);
+                assert!(moved >= 0 && moved % 4 == 0);
+                if moved == 0 {break;}
+            }
+            assert_eq!(cage2.close_syscall(pipefds2.writefd), 0);
+        });
+
+        let mut piped = vec![];
+        let mut buf = sizecbuf(4096);
+        loop {
+            let bytesread = cage.read_syscall(pipefds2.readfd, buf.as_mut_ptr(), 4096);
+            assert!(bytesread >= 0);
+            if bytesread == 0 {break;}
+            piped.extend_from_slice(&buf[..bytesread as usize]);
+        }
+        writer.join().unwrap();
+        let filesize = tofile.join().unwrap();
+        topipe.join().unwrap();
+
+        //every word made it to exactly one of the two
+        let mut filed = vec![0u8; filesize];
+        assert_eq!(cage.pread_syscall(filefd, filed.as_mut_ptr(), filesize, 0), filesize as i32);
+        assert_eq!(filesize % 4, 0);
+        assert_eq!(piped.len() % 4, 0);
+        let mut words: Vec<u32> = filed.chunks(4).chain(piped.chunks(4)).map(|word| u32::from_ne_bytes([word[0], word[1], word[2], word[3]])).collect();
+        words.sort();
+        assert!(words.into_iter().eq(0..wordcount));
+
+        assert_eq!(cage.close_syscall(pipefds.readfd), 0);
+        assert_eq!(cage.close_syscall(pipefds2.readfd), 0);
+        assert_eq!(cage.close_syscall(filefd), 0);
+        assert_eq!(cage.unlink_syscall("/spliceconcurrent"), 0);
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
 }
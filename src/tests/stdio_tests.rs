@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod stdio_tests {
+    use crate::interface;
+    use crate::safeposix::{cage::*, dispatcher::*};
+    use super::super::*;
+    use std::io::Read;
+    use std::sync::mpsc;
+
+    #[test]
+    pub fn test_stdio() {
+        let _guard = lock_tests();
+
+        ut_lind_stdio_hostinput();
+        ut_lind_stdio_stdin();
+        ut_lind_stdio_redirect();
+    }
+
+    //stands in for host stdin, giving whatever is sent to it and end of file once the sender is dropped
+    struct ChannelInput {
+        receiver: mpsc::Receiver<Vec<u8>>,
+    }
+
+    impl Read for ChannelInput {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.receiver.recv() {
+                Ok(data) => {
+                    buf[..data.len()].copy_from_slice(&data);
+                    Ok(data.len())
+                }
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    pub fn ut_lind_stdio_hostinput() {
+        let (sender, receiver) = mpsc::channel();
+        let input = interface::RustRfc::new(interface::HostInput::new());
+        input.spawn_reader(ChannelInput {receiver: receiver});
+        let mut buf = sizecbuf(10);
+
+        assert_eq!(input.read(buf.as_mut_ptr(), 10, true), Err(libc::EAGAIN));
+        assert_eq!(input.poll_state(), (false, false, 0));
+
+        //a blocking read waits for input
+        let input2 = input.clone();
+        let reader = std::thread::spawn(move || {
+            let mut buf = sizecbuf(10);
+            let bytesread = input2.read(buf.as_mut_ptr(), 10, false).unwrap();
+            cbuf2str(&buf[..bytesread]).to_string()
+        });
+        interface::sleep_ms(interface::RustDuration::from_millis(20));
+        assert!(!reader.is_finished());
+        sender.send(b"hello".to_vec()).unwrap();
+        assert_eq!(reader.join().unwrap(), "hello");
+
+        //input is read as it is asked for, with what's left over kept for the next read
+        let generation = interface::readiness_generation();
+        sender.send(b"world".to_vec()).unwrap();
+        interface::wait_for_readiness(generation, Some(interface::RustDuration::from_secs(5)));
+        assert_eq!(input.poll_state(), (true, false, 2));
+        assert_eq!(input.read(buf.as_mut_ptr(), 3, true), Ok(3));
+        assert_eq!(cbuf2str(&buf[..3]), "wor");
+
+        //what was sent before end of file is still read, then reads give 0 bytes from then on
+        drop(sender);
+        let start = interface::starttimer();
+        while input.poll_state() != (true, true, 2) {
+            assert!(interface::readtimer(start) < interface::RustDuration::from_secs(5));
+            interface::sleep_ms(interface::RustDuration::from_millis(1));
+        }
+        assert_eq!(input.read(buf.as_mut_ptr(), 10, false), Ok(2));
+        assert_eq!(cbuf2str(&buf[..2]), "ld");
+        assert_eq!(input.poll_state(), (false, true, 2));
+        assert_eq!(input.read(buf.as_mut_ptr(), 10, false), Ok(0));
+        assert_eq!(input.read(buf.as_mut_ptr(), 10, true), Ok(0));
+    }
+
+    pub fn ut_lind_stdio_stdin() {
+        lindrustinit();
+        let cage = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(10);
+
+        //only stdin is open for reading, and only stdout and stderr for writing
+        assert_eq!(cage.read_syscall(1, buf.as_mut_ptr(), 10), -(Errno::EBADF as i32));
+        assert_eq!(cage.read_syscall(2, buf.as_mut_ptr(), 10), -(Errno::EBADF as i32));
+        assert_eq!(cage.write_syscall(0, str2cbuf("hello"), 5), -(Errno::EBADF as i32));
+
+        //what the host gives as stdin while testing is out of our hands, but reading it without blocking never waits
+        assert_eq!(cage.fcntl_syscall(0, F_SETFL, O_NONBLOCK), 0);
+        let bytesread = cage.read_syscall(0, buf.as_mut_ptr(), 10);
+        assert!(bytesread >= 0 || bytesread == -(Errno::EAGAIN as i32));
+
+        assert_eq!(cage.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+
+    pub fn ut_lind_stdio_redirect() {
+        lindrustinit();
+        let cage1 = {CAGE_TABLE.read().unwrap().get(&1).unwrap().clone()};
+        let mut buf = sizecbuf(20);
+
+        //only the standard streams can be redirected
+        assert_eq!(cage1.redirect_stdio(3, StdioTarget::Host), -(Errno::EBADF as i32));
+
+        //a child started with its streams on buffers reads the input given and then end of file, and its output is captured
+        assert_eq!(cage1.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        let input = interface::RustRfc::new(interface::StdioBuffer::from_input(b"input"));
+        let output = interface::RustRfc::new(interface::StdioBuffer::new());
+        let errors = interface::RustRfc::new(interface::StdioBuffer::new());
+        assert_eq!(cage2.redirect_stdio(0, StdioTarget::Buffer(input)), 0);
+        assert_eq!(cage2.redirect_stdio(1, StdioTarget::Buffer(output.clone())), 0);
+        assert_eq!(cage2.redirect_stdio(2, StdioTarget::Buffer(errors.clone())), 0);
+        assert_eq!(cage2.read_syscall(0, buf.as_mut_ptr(), 20), 5);
+        assert_eq!(cbuf2str(&buf[..5]), "input");
+        assert_eq!(cage2.read_syscall(0, buf.as_mut_ptr(), 20), 0);
+        assert_eq!(cage2.write_syscall(1, str2cbuf("out"), 3), 3);
+        assert_eq!(cage2.write_syscall(2, str2cbuf("err"), 3), 3);
+        assert_eq!(output.contents(), b"out".to_vec());
+        assert_eq!(errors.contents(), b"err".to_vec());
+
+        //the parent's streams are left alone
+        assert_eq!(cage1.write_syscall(1, str2cbuf(""), 0), 0);
+        assert_eq!(output.contents(), b"out".to_vec());
+        assert_eq!(cage2.exit_syscall(), 0);
+
+        //streams attached to files write to and read from them like a shell's redirections
+        assert_eq!(cage1.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(cage2.redirect_stdio(0, StdioTarget::File("/redirectmissing".to_string())), -(Errno::ENOENT as i32));
+        assert_eq!(cage2.redirect_stdio(1, StdioTarget::File("/redirectout".to_string())), 0);
+        assert_eq!(cage2.write_syscall(1, str2cbuf("to a file"), 9), 9);
+        assert_eq!(cage2.redirect_stdio(0, StdioTarget::File("/redirectout".to_string())), 0);
+        assert_eq!(cage2.read_syscall(0, buf.as_mut_ptr(), 20), 9);
+        assert_eq!(cbuf2str(&buf[..9]), "to a file");
+        assert_eq!(cage2.exit_syscall(), 0);
+        assert_eq!(cage1.unlink_syscall("/redirectout"), 0);
+
+        //a pipe held outside of lind sees the child's output, then end of file once the child exits
+        let outpipe = interface::RustRfc::new(interface::new_unopened_pipe(PIPE_CAPACITY));
+        outpipe.incr_ref(O_RDONLY);
+        let inpipe = interface::RustRfc::new(interface::new_unopened_pipe(PIPE_CAPACITY));
+        inpipe.incr_ref(O_WRONLY);
+        assert_eq!(cage1.fork_syscall(2), 0);
+        let cage2 = {CAGE_TABLE.read().unwrap().get(&2).unwrap().clone()};
+        assert_eq!(cage2.redirect_stdio(0, StdioTarget::Pipe(inpipe.clone())), 0);
+        assert_eq!(cage2.redirect_stdio(1, StdioTarget::Pipe(outpipe.clone())), 0);
+        assert_eq!(inpipe.write_to_pipe(str2cbuf("piped in"), 8, false), Ok(8));
+        release_pipe_end(&inpipe, O_WRONLY);
+        assert_eq!(cage2.read_syscall(0, buf.as_mut_ptr(), 20), 8);
+        assert_eq!(cbuf2str(&buf[..8]), "piped in");
+        assert_eq!(cage2.read_syscall(0, buf.as_mut_ptr(), 20), 0);
+        assert_eq!(cage2.write_syscall(1, str2cbuf("piped out"), 9), 9);
+        assert_eq!(cage2.exit_syscall(), 0);
+        assert_eq!(outpipe.read_from_pipe(buf.as_mut_ptr(), 20, false), Ok(9));
+        assert_eq!(cbuf2str(&buf[..9]), "piped out");
+        assert_eq!(outpipe.read_from_pipe(buf.as_mut_ptr(), 20, false), Ok(0));
+        release_pipe_end(&outpipe, O_RDONLY);
+        assert!(!PIPE_TABLE.read().unwrap().values().any(|pipe| interface::RustRfc::ptr_eq(pipe, &outpipe) || interface::RustRfc::ptr_eq(pipe, &inpipe)));
+
+        assert_eq!(cage1.exit_syscall(), 0);
+        lindrustfinalize();
+    }
+}
@@ -81,7 +81,8 @@ cp [hostsource] [linddest]      : Copies files from the host file system into th
                                   former file in the host file system to the latter in lind's fs.
                                   Directories are handled recursively, cp bar/etc /etc/ will make a
                                   directory at /etc in the lind fs, and then populate it with all
-                                  of the files in the root fs.
+                                  of the files in the root fs. Symlinks are copied as symlinks with
+                                  the same target, which is then looked up within lind.
 deltree [linddir]               : Delete a directory on the lind file system and all it contains
 format                          : Make a new blank fs, removing the current one
 help                            : Print this message
@@ -108,7 +109,9 @@ fn main() {
     let utilcage = Cage{cageid: 0,
                         cwd: interface::RustLock::new(interface::RustRfc::new(interface::RustPathBuf::from("/"))),
                         parent: 0, 
-                        filedescriptortable: interface::RustLock::new(interface::RustHashMap::new())};
+                        filedescriptortable: interface::RustLock::new(interface::RustHashMap::new()),
+                        creds: interface::RustLock::new(Credentials::new(DEFAULT_UID, DEFAULT_GID)),
+                        umask: interface::RustAtomicU32::new(DEFAULT_UMASK)};
 
     args.next();//first arg is executable, we don't care
     let command = if let Some(cmd) = args.next() {
@@ -91,12 +91,13 @@ fn update_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepat
 }
 
 pub fn cp_dir_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepath: &str, create_missing_dirs: bool) {
-    if hostfilepath.exists() {
-        if let Ok(_) = hostfilepath.read_link() {
-            println!("Ignore broken symlink at {:?} on host fs", hostfilepath);
-            return;
-        } //if read_link succeeds it's a symlink
-    } else {
+    //symlinks are copied as symlinks rather than followed, whether or not what they point to exists
+    if let Ok(_) = hostfilepath.read_link() {
+        cp_symlink_into_lind(cage, hostfilepath, lindfilepath, create_missing_dirs);
+        return;
+    } //if read_link succeeds it's a symlink
+
+    if !hostfilepath.exists() {
         eprintln!("Cannot locate file on host fs: {:?}", hostfilepath);
         return
     }
@@ -129,7 +130,65 @@ fn cp_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepath: &
     }
 
     let lindtruepath = normpath(convpath(lindfilepath), cage);
+    if !make_lind_ancestors(cage, &lindtruepath, create_missing_dirs) {return;}
+
+    //copy file contents into lind file system
+    let mut host_fileobj = File::open(hostfilepath).unwrap();
+    let mut filecontents: Vec<u8> = Vec::new();
+    host_fileobj.read_to_end(&mut filecontents).unwrap();
+
+    let lindfd = cage.open_syscall(lindtruepath.to_str().unwrap(), O_CREAT | O_TRUNC | O_WRONLY, S_IRWXA);
+    assert!(lindfd >= 0);
+    let veclen = filecontents.len();
+    let fileslice = filecontents.as_slice();
+    let writtenlen = cage.write_syscall(lindfd, fileslice.as_ptr(), veclen);
+
+    //confirm that write succeeded
+    assert_eq!(veclen as i32, writtenlen);
+
+    //get diagnostic data to print
+    let mut lindstat_res: StatData = StatData::default();
+    let _stat_us = cage.fstat_syscall(lindfd, &mut lindstat_res);
+    let inode = lindstat_res.st_ino;
+
+    assert_eq!(cage.close_syscall(lindfd), 0);
+
+    println!("Copied {:?} as {} ({})", hostfilepath, lindfilepath, inode);
+}
+
+//makes a symlink in lind with the same target as the one on the host, which is left as is, so an absolute target
+//refers to a path within lind
+fn cp_symlink_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepath: &str, create_missing_dirs: bool) {
+    let target = hostfilepath.read_link().unwrap();
+    let targetstr = if let Some(targetstr) = target.to_str() {targetstr} else {
+        eprintln!("Symlink target is not valid unicode on host fs: {:?}", hostfilepath);
+        return
+    };
+
+    let lindtruepath = normpath(convpath(lindfilepath), cage);
+    if !make_lind_ancestors(cage, &lindtruepath, create_missing_dirs) {return;}
 
+    //replace whatever non-directory is already there, as copying a file over it would
+    let mut lindstat_res: StatData = StatData::default();
+    if cage.lstat_syscall(lindtruepath.to_str().unwrap(), &mut lindstat_res) == 0 {
+        if is_dir(lindstat_res.st_mode) {
+            eprintln!("Cannot replace directory with symlink on lind fs: {}", lindfilepath);
+            return;
+        }
+        cage.unlink_syscall(lindtruepath.to_str().unwrap());
+    }
+
+    if cage.symlink_syscall(targetstr, lindtruepath.to_str().unwrap()) != 0 {
+        eprintln!("Failed to create symlink on lind fs: {}", lindfilepath);
+        return;
+    }
+
+    println!("Copied symlink {:?} as {} -> {}", hostfilepath, lindfilepath, targetstr);
+}
+
+//makes sure the directories lindtruepath is in exist, creating those which don't if create_missing_dirs is set,
+//returning whether they all exist now
+fn make_lind_ancestors(cage: &Cage, lindtruepath: &interface::RustPath, create_missing_dirs: bool) -> bool {
     //if a directory in the lindfilepath does not exist in the lind file system, create it!
     let mut ancestor = interface::RustPathBuf::from("/");
     for component in lindtruepath.parent().unwrap().components() {
@@ -141,13 +200,13 @@ fn cp_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepath: &
         if stat_us == 0 {
             if !is_dir(lindstat_res.st_mode) {
                 eprintln!("Fatal error in trying to create child of non-directory file");
-                return;
+                return false;
             }
             continue;
         }
         if stat_us != -(Errno::ENOENT as i32) {
             eprintln!("Fatal error in trying to get lind file path");
-            return;
+            return false;
         }
 
         //check whether we are supposed to create missing directories, and whether we'd be
@@ -155,36 +214,14 @@ fn cp_into_lind(cage: &Cage, hostfilepath: &interface::RustPath, lindfilepath: &
         if create_missing_dirs {
             if cage.mkdir_syscall(ancestor.to_str().unwrap(), S_IRWXA) != 0 { //let's not mirror stat data
                 eprintln!("Lind fs path does not exist but should not be created (is rooted at non-directory) {:?}", ancestor);
-                return;
+                return false;
             }
         } else {
             eprintln!("Lind fs path does not exist but should not be created {:?}", ancestor);
-            return;
+            return false;
         }
     }
-
-    //copy file contents into lind file system
-    let mut host_fileobj = File::open(hostfilepath).unwrap();
-    let mut filecontents: Vec<u8> = Vec::new();
-    host_fileobj.read_to_end(&mut filecontents).unwrap();
-
-    let lindfd = cage.open_syscall(lindtruepath.to_str().unwrap(), O_CREAT | O_TRUNC | O_WRONLY, S_IRWXA);
-    assert!(lindfd >= 0);
-    let veclen = filecontents.len();
-    let fileslice = filecontents.as_slice();
-    let writtenlen = cage.write_syscall(lindfd, fileslice.as_ptr(), veclen);
-
-    //confirm that write succeeded
-    assert_eq!(veclen as i32, writtenlen);
-
-    //get diagnostic data to print
-    let mut lindstat_res: StatData = StatData::default();
-    let _stat_us = cage.fstat_syscall(lindfd, &mut lindstat_res);
-    let inode = lindstat_res.st_ino;
-
-    assert_eq!(cage.close_syscall(lindfd), 0);
-
-    println!("Copied {:?} as {} ({})", hostfilepath, lindfilepath, inode);
+    true
 }
 
 pub fn visit_children(cage: &Cage, path: &str, arg: Option<usize>, visitor: fn(&Cage, &str, bool, Option<usize>)) {
@@ -225,9 +262,9 @@ pub fn visit_children(cage: &Cage, path: &str, arg: Option<usize>, visitor: fn(&
                 [path, "/", filenamestr].join("")
             };
 
-            //stat to tell whether it's a directory
+            //stat to tell whether it's a directory, a link to one not counting so that it isn't descended into
             let mut lindstat_res: StatData = StatData::default();
-            let _stat_us = cage.stat_syscall(fullstatpath.as_str(), &mut lindstat_res);
+            let _stat_us = cage.lstat_syscall(fullstatpath.as_str(), &mut lindstat_res);
 
             //call the visitor function on the child path
             visitor(cage, fullstatpath.as_str(), is_dir(lindstat_res.st_mode), arg);
@@ -238,7 +275,7 @@ pub fn visit_children(cage: &Cage, path: &str, arg: Option<usize>, visitor: fn(&
 
 pub fn lind_deltree(cage: &Cage, path: &str) {
     let mut lindstat_res: StatData = StatData::default();
-    let stat_us = cage.stat_syscall(path, &mut lindstat_res);
+    let stat_us = cage.lstat_syscall(path, &mut lindstat_res);
 
     if stat_us == 0 {
         if !is_dir(lindstat_res.st_mode) {